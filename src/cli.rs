@@ -1,4 +1,5 @@
-use clap::{Parser, Subcommand};
+use clap::{ArgGroup, Args, Parser, Subcommand, ValueEnum};
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -6,16 +7,334 @@ use std::path::PathBuf;
 #[command(about = "A CLI tool for documentation-code mapping verification")]
 #[command(version = "0.1.0")]
 pub struct Cli {
+    /// Print plain ASCII markers instead of emoji (implied when stdout is not a terminal)
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
+    /// Use this mapping file instead of searching upward for .doks
+    #[arg(long = "file", global = true, value_name = "PATH")]
+    pub doks_file: Option<PathBuf>,
+
+    /// Run as if started in this directory
+    #[arg(long, global = true, value_name = "DIR")]
+    pub cwd: Option<PathBuf>,
+
     #[command(subcommand)]
     pub command: Commands,
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
-    New { path: Option<PathBuf> },
-    Add,
-    Edit { id: String },
-    RemoveFailed,
-    Test,
+    New {
+        path: Option<PathBuf>,
+        /// Default documentation file; skips scanning and the interactive prompt
+        #[arg(long)]
+        doc: Option<String>,
+        /// Also offer files matching this pattern, e.g. '*.txt' (repeatable)
+        #[arg(long = "doc-glob", value_name = "PATTERN")]
+        doc_globs: Vec<String>,
+    },
+    Add(AddArgs),
+    AddBatch {
+        /// Manifest with one 'doc_partition code_partition [description]' per line
+        file: PathBuf,
+    },
+    Check,
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    Coverage {
+        /// Source file to report documentation coverage for
+        file: PathBuf,
+    },
+    Doctor,
+    Edit(EditArgs),
+    Export {
+        /// Output format for the mapping table
+        #[arg(long, value_enum, default_value_t = ExportFormat::Json)]
+        format: ExportFormat,
+    },
+    Extract {
+        /// Partition to print, e.g. 'src/lib.rs:10-20'
+        partition: String,
+    },
+    Import {
+        /// JSON array of mappings, in the shape written by 'export'
+        file: PathBuf,
+        /// Replace existing mappings with the same ID instead of skipping them
+        #[arg(long)]
+        overwrite: bool,
+    },
+    List {
+        /// Output format for the mapping list
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+        /// Also check each mapping and report whether it passes
+        #[arg(long)]
+        with_status: bool,
+    },
+    Merge {
+        /// The .doks file whose mappings are appended
+        other: PathBuf,
+        /// Directory the other file's partitions are relative to, e.g. where
+        /// its repository now lives
+        #[arg(long, value_name = "DIR")]
+        prefix: Option<String>,
+    },
+    #[command(group(
+        ArgGroup::new("target")
+            .args(["to_doc", "to_code"])
+            .required(true)
+            .multiple(true)
+    ))]
+    Move {
+        /// ID of the mapping (a unique prefix is enough)
+        id: String,
+        /// New documentation partition
+        #[arg(long)]
+        to_doc: Option<String>,
+        /// New code partition
+        #[arg(long)]
+        to_code: Option<String>,
+    },
+    Remove {
+        id: String,
+    },
+    RemoveFailed {
+        /// Remove without asking for confirmation
+        #[arg(long, short)]
+        yes: bool,
+        /// Only print the number of failed mappings; nothing is removed
+        /// unless --yes is also given
+        #[arg(long)]
+        count: bool,
+    },
+    RenameFile {
+        /// Path the partitions currently point at, relative to the .doks directory
+        from: String,
+        /// New path for those partitions
+        to: String,
+        /// Also move the file on disk
+        #[arg(long)]
+        move_file: bool,
+    },
+    Show {
+        id: String,
+        /// Also show this many lines of the file around each partition
+        #[arg(long, value_name = "N", default_value_t = 0)]
+        context: usize,
+    },
+    Sort,
+    Stats,
+    Sync {
+        /// Report which mappings would be re-hashed without writing .doks
+        #[arg(long)]
+        check: bool,
+    },
+    Test(TestArgs),
     TestInteractive,
+    Undo,
+    Validate,
+    Watch,
+}
+
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// Print a header value, defaults included
+    Get {
+        /// Header key, e.g. 'default_doc' or 'preview_chars'
+        key: String,
+    },
+    /// Change a header value; mappings are left as they are
+    Set {
+        /// Header key, e.g. 'default_doc' or 'preview_chars'
+        key: String,
+        value: String,
+    },
+}
+
+#[derive(Args)]
+pub struct AddArgs {
+    /// Documentation partition; with --code, skips the interactive prompts
+    #[arg(long)]
+    pub doc: Option<String>,
+    /// Code partition; with --doc, skips the interactive prompts
+    #[arg(long)]
+    pub code: Option<String>,
+    /// Optional description for the mapping
+    #[arg(long)]
+    pub description: Option<String>,
+    /// Ignore indentation, trailing whitespace and blank lines when hashing
+    #[arg(long)]
+    pub normalize_whitespace: bool,
+    /// Ignore comments in the code partition when hashing, so comment-only
+    /// edits don't break the mapping
+    #[arg(long)]
+    pub strip_comments: bool,
+    /// Tag the mapping, e.g. with its feature area, to test it with
+    /// 'test --tag' (repeatable, or comma-separated)
+    #[arg(long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+    /// Also store the content inline, so 'test --verbose' can show a diff
+    /// when it drifts, even without the files or git history
+    #[arg(long)]
+    pub store_snippet: bool,
+    /// Preview the content and print the mapping line without saving it
+    #[arg(long)]
+    pub dry_run: bool,
+    /// With json, print only `{"id": "..."}` for the new mapping, for editor
+    /// integrations; requires --doc and --code
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = OutputFormat::Text,
+        requires_all = ["doc", "code"],
+        conflicts_with = "dry_run"
+    )]
+    pub format: OutputFormat,
+    /// Also show this many lines of the file around each partition preview
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub context: usize,
+}
+
+#[derive(Args)]
+pub struct TestArgs {
+    /// Output format for the results
+    #[arg(long, value_enum, default_value_t = TestFormat::Text)]
+    pub format: TestFormat,
+    /// Only print the summary and failure details
+    #[arg(long, short)]
+    pub quiet: bool,
+    /// Print the current content of each failing partition
+    #[arg(long, short)]
+    pub verbose: bool,
+    /// Stop at the first failing mapping
+    #[arg(long)]
+    pub fail_fast: bool,
+    /// Print nothing; exit 0 if all pass, 1 if any fail, 2 if .doks is
+    /// invalid or a mapping can't be checked
+    #[arg(long, conflicts_with_all = ["format", "quiet", "verbose"])]
+    pub status_only: bool,
+    /// Warn about mappings whose documentation is a verbatim copy of the code
+    #[arg(long, conflicts_with = "status_only")]
+    pub warn_identical: bool,
+    /// Fail those mappings instead of only warning
+    #[arg(long, requires = "warn_identical")]
+    pub deny_identical: bool,
+    /// Cut line ranges that run past the end of a shrunken file short at its
+    /// last line, with a warning, instead of failing them
+    #[arg(long)]
+    pub clamp: bool,
+    /// Only test mappings whose id starts with this prefix (repeatable)
+    #[arg(long, value_name = "ID_PREFIX")]
+    pub only: Vec<String>,
+    /// Skip mappings whose id starts with this prefix (repeatable)
+    #[arg(long, value_name = "ID_PREFIX")]
+    pub exclude: Vec<String>,
+    /// Only test mappings with this tag (repeatable; a mapping with any of them is tested)
+    #[arg(long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+    /// Pass with a notice instead of failing when there is no .doks file
+    #[arg(long)]
+    pub allow_missing: bool,
+    /// Read the .doks content from stdin instead of looking for a file;
+    /// partitions are resolved from the working directory
+    #[arg(long, conflicts_with = "allow_missing")]
+    pub stdin: bool,
+    /// Resolve documentation partitions from this directory instead of the one containing .doks
+    #[arg(long, value_name = "DIR")]
+    pub doc_base: Option<PathBuf>,
+    /// Resolve code partitions from this directory, e.g. a build output directory
+    #[arg(long, value_name = "DIR")]
+    pub code_base: Option<PathBuf>,
+    /// Verify at most this many mappings at once (default: one per CPU; 1 is sequential)
+    #[arg(long, value_name = "N")]
+    pub parallel: Option<NonZeroUsize>,
+    /// Point partitions whose file is missing at the one file of the same
+    /// name elsewhere in the tree, saving the change when the mapping then passes
+    #[arg(long, conflicts_with_all = ["status_only", "stdin", "doc_base", "code_base"])]
+    pub repair: bool,
+    /// Section the failure details of the text report by file
+    #[arg(long, value_enum, value_name = "KEY", conflicts_with = "status_only")]
+    pub group_by: Option<GroupBy>,
+}
+
+#[derive(Args)]
+pub struct EditArgs {
+    /// ID of the mapping (a unique prefix is enough)
+    pub id: String,
+    /// New documentation partition; any change flag skips the interactive menu
+    #[arg(long)]
+    pub doc: Option<String>,
+    /// New code partition
+    #[arg(long)]
+    pub code: Option<String>,
+    /// New description
+    #[arg(long, conflicts_with = "clear_description")]
+    pub description: Option<String>,
+    /// Remove the description
+    #[arg(long)]
+    pub clear_description: bool,
+    /// Replace the mapping's tags (repeatable, or comma-separated)
+    #[arg(long = "tag", value_name = "TAG", conflicts_with = "clear_tags")]
+    pub tags: Vec<String>,
+    /// Remove all of the mapping's tags
+    #[arg(long)]
+    pub clear_tags: bool,
+    /// Verify the mapping again after it was disabled
+    #[arg(long = "enabled", conflicts_with = "disable")]
+    pub enable: bool,
+    /// Keep the mapping and its hashes but skip it when verifying
+    #[arg(long = "disabled")]
+    pub disable: bool,
+    /// Also show this many lines of the file around each partition preview
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub context: usize,
+}
+
+impl EditArgs {
+    /// Whether any change was given on the command line.
+    pub fn has_changes(&self) -> bool {
+        self.doc.is_some()
+            || self.code.is_some()
+            || self.description.is_some()
+            || self.clear_description
+            || !self.tags.is_empty()
+            || self.clear_tags
+            || self.enable
+            || self.disable
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum TestFormat {
+    Text,
+    Json,
+    /// GitHub Actions workflow commands, shown as annotations on the PR
+    Github,
+    /// A SARIF 2.1.0 log, for code scanning dashboards
+    Sarif,
+    /// JUnit XML, for CI test reporting
+    Junit,
+    /// A Markdown table, e.g. for a pull request comment
+    Markdown,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum GroupBy {
+    /// The file of each mapping's first failing partition
+    File,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    Json,
+    Csv,
 }
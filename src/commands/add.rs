@@ -1,38 +1,101 @@
 use anyhow::{anyhow, Result};
 use dialoguer::{Confirm, Input};
+use serde_json::json;
+use std::path::Path;
 use uuid::Uuid;
 
-use crate::config::{DoksConfig, Mapping};
-use crate::hash::hash_content;
+use crate::cli::{AddArgs, OutputFormat};
+use crate::config::{parse_tags, snippet, timestamp_now, DoksConfig, Mapping, MAX_SNIPPET_BYTES};
+use crate::hash::{hash_content, line_digests, HashOptions};
+use crate::output::Marker;
 use crate::partition::Partition;
 
-pub fn handle() -> Result<()> {
+use super::Preview;
+
+pub fn handle(args: AddArgs) -> Result<()> {
+    let AddArgs {
+        doc,
+        code,
+        description,
+        normalize_whitespace,
+        strip_comments,
+        tags,
+        store_snippet,
+        dry_run,
+        format,
+        context,
+    } = args;
+    let tags = parse_tags(&tags.join(","))?;
+
     // Find the .doks file
-    let doks_file_path = DoksConfig::find_doks_file()
-        .ok_or_else(|| anyhow!("No .doks file found. Run 'doksnet new' first."))?;
+    let doks_file_path = super::find_doks_file()?;
 
     let mut config = DoksConfig::from_file(&doks_file_path)?;
+    let preview = Preview {
+        root: &config.root,
+        chars: config.preview_chars,
+        context,
+    };
+    let hash_options = HashOptions {
+        normalize_whitespace,
+        ..config.hash_options
+    };
+
+    if let (Some(doc), Some(code)) = (&doc, &code) {
+        let doc_content = extract(&config.root, doc, "documentation")?;
+        let code_content = extract(&config.root, code, "code")?;
+        if dry_run {
+            print_preview(&preview, Marker::Doc, "Documentation", doc, &doc_content);
+            print_preview(&preview, Marker::Code, "Code", code, &code_content);
+        }
+
+        let mut mapping = mapping_from_content(
+            doc,
+            code,
+            &doc_content,
+            &code_content,
+            description.unwrap_or_default(),
+            hash_options,
+            strip_comments,
+        );
+        mapping.tags = tags;
+        if store_snippet {
+            store_snippets(&mut mapping, &doc_content, &code_content, format);
+        }
+        if dry_run {
+            print_dry_run(&mapping);
+            return Ok(());
+        }
+
+        let id = mapping.id.clone();
+        config.add_mapping(mapping);
+        config.to_file(&doks_file_path)?;
 
-    println!("📝 Adding new documentation-code mapping");
+        if format == OutputFormat::Json {
+            println!("{}", json!({ "id": id }));
+            return Ok(());
+        }
+        println!("{}Successfully added mapping {}", Marker::Ok, id);
+        println!("{}Total mappings: {}", Marker::Stats, config.mappings.len());
+        return Ok(());
+    }
+
+    println!("{}Adding new documentation-code mapping", Marker::Note);
     println!("Current default documentation file: {}", config.default_doc);
 
     let doc_partition_str: String = Input::new()
         .with_prompt("Documentation partition (e.g., README.md:10-20 or README.md:10-20@5-15)")
-        .with_initial_text(format!("{}:", config.default_doc))
+        .with_initial_text(doc.unwrap_or_else(|| format!("{}:", config.default_doc)))
         .interact_text()?;
 
-    let doc_partition = Partition::parse(&doc_partition_str)?;
-    let doc_content = doc_partition
-        .extract_content()
-        .map_err(|e| anyhow!("Failed to extract documentation content: {}", e))?;
-
-    println!("\n📄 Documentation content preview:");
-    println!("---");
-    println!("{}", doc_content.chars().take(200).collect::<String>());
-    if doc_content.len() > 200 {
-        println!("... (truncated)");
-    }
-    println!("---");
+    let doc_content = extract(&config.root, &doc_partition_str, "documentation")?;
+    print_preview(
+        &preview,
+        Marker::Doc,
+        "Documentation",
+        &doc_partition_str,
+        &doc_content,
+    );
 
     let confirm_doc = Confirm::new()
         .with_prompt("Is this the correct documentation content?")
@@ -40,26 +103,23 @@ pub fn handle() -> Result<()> {
         .interact()?;
 
     if !confirm_doc {
-        println!("❌ Documentation selection cancelled");
+        println!("{}Documentation selection cancelled", Marker::Fail);
         return Ok(());
     }
 
     let code_partition_str: String = Input::new()
         .with_prompt("Code partition (e.g., src/main.rs:15-30 or src/lib.rs:5-25@10-50)")
+        .with_initial_text(code.unwrap_or_default())
         .interact_text()?;
 
-    let code_partition = Partition::parse(&code_partition_str)?;
-    let code_content = code_partition
-        .extract_content()
-        .map_err(|e| anyhow!("Failed to extract code content: {}", e))?;
-
-    println!("\n💻 Code content preview:");
-    println!("---");
-    println!("{}", code_content.chars().take(200).collect::<String>());
-    if code_content.len() > 200 {
-        println!("... (truncated)");
-    }
-    println!("---");
+    let code_content = extract(&config.root, &code_partition_str, "code")?;
+    print_preview(
+        &preview,
+        Marker::Code,
+        "Code",
+        &code_partition_str,
+        &code_content,
+    );
 
     let confirm_code = Confirm::new()
         .with_prompt("Is this the correct code content?")
@@ -67,38 +127,174 @@ pub fn handle() -> Result<()> {
         .interact()?;
 
     if !confirm_code {
-        println!("❌ Code selection cancelled");
+        println!("{}Code selection cancelled", Marker::Fail);
         return Ok(());
     }
 
     let description: String = Input::new()
         .with_prompt("Optional description for this mapping")
+        .with_initial_text(description.unwrap_or_default())
         .allow_empty(true)
         .interact_text()?;
 
-    let description = if description.trim().is_empty() {
-        None
-    } else {
-        Some(description.trim().to_string())
-    };
-
-    let doc_hash = hash_content(&doc_content);
-    let code_hash = hash_content(&code_content);
+    let tags: String = Input::new()
+        .with_prompt("Optional tags, comma-separated (e.g. auth,api)")
+        .with_initial_text(tags.join(","))
+        .allow_empty(true)
+        .interact_text()?;
+    let tags = parse_tags(&tags)?;
 
-    let mapping = Mapping {
-        id: Uuid::new_v4().to_string(),
-        doc_partition: doc_partition_str,
-        code_partition: code_partition_str,
-        doc_hash,
-        code_hash,
+    let mut mapping = mapping_from_content(
+        &doc_partition_str,
+        &code_partition_str,
+        &doc_content,
+        &code_content,
         description,
-    };
+        hash_options,
+        strip_comments,
+    );
+    mapping.tags = tags;
+    if store_snippet {
+        store_snippets(&mut mapping, &doc_content, &code_content, format);
+    }
+
+    if dry_run {
+        print_dry_run(&mapping);
+        return Ok(());
+    }
 
     config.add_mapping(mapping);
     config.to_file(&doks_file_path)?;
 
-    println!("✅ Successfully added mapping!");
-    println!("📊 Total mappings: {}", config.mappings.len());
+    println!("{}Successfully added mapping!", Marker::Ok);
+    println!("{}Total mappings: {}", Marker::Stats, config.mappings.len());
 
     Ok(())
 }
+
+/// Extracts and hashes both partitions into a new mapping with a fresh ID.
+pub fn build_mapping(
+    root: &Path,
+    doc_partition: &str,
+    code_partition: &str,
+    description: String,
+    hash_options: HashOptions,
+    strip_comments: bool,
+) -> Result<Mapping> {
+    let doc_content = extract(root, doc_partition, "documentation")?;
+    let code_content = extract(root, code_partition, "code")?;
+
+    Ok(mapping_from_content(
+        doc_partition,
+        code_partition,
+        &doc_content,
+        &code_content,
+        description,
+        hash_options,
+        strip_comments,
+    ))
+}
+
+/// A new mapping with a fresh ID, hashing content that was already extracted.
+fn mapping_from_content(
+    doc_partition: &str,
+    code_partition: &str,
+    doc_content: &str,
+    code_content: &str,
+    description: String,
+    hash_options: HashOptions,
+    strip_comments: bool,
+) -> Mapping {
+    let now = timestamp_now();
+    let mut mapping = Mapping {
+        id: Uuid::new_v4().to_string(),
+        doc_partition: doc_partition.to_string(),
+        code_partition: code_partition.to_string(),
+        doc_hash: hash_content(doc_content, hash_options),
+        code_hash: String::new(),
+        description: normalize_description(description),
+        normalize_whitespace: hash_options.normalize_whitespace,
+        strip_comments,
+        tags: Vec::new(),
+        doc_snippet: None,
+        code_snippet: None,
+        doc_lines: line_digests(doc_content, hash_options),
+        code_lines: Vec::new(),
+        comments: Vec::new(),
+        created_at: Some(now.clone()),
+        updated_at: Some(now),
+        enabled: true,
+    };
+    let code_options = mapping.code_hash_options(hash_options);
+    mapping.code_hash = hash_content(code_content, code_options);
+    mapping.code_lines = line_digests(code_content, code_options);
+    mapping
+}
+
+/// Stores both sides' content inline as snippets of what's expected,
+/// skipping, with a warning, any side over [`MAX_SNIPPET_BYTES`]. The warning
+/// goes to stderr with `--format json`, to keep stdout parseable.
+fn store_snippets(
+    mapping: &mut Mapping,
+    doc_content: &str,
+    code_content: &str,
+    format: OutputFormat,
+) {
+    mapping.doc_snippet = snippet(doc_content);
+    mapping.code_snippet = snippet(code_content);
+    for (content, stored, content_type) in [
+        (doc_content, &mapping.doc_snippet, "documentation"),
+        (code_content, &mapping.code_snippet, "code"),
+    ] {
+        if stored.is_none() {
+            let warning = format!(
+                "{}Not storing the {} snippet: {} bytes is over the {}-byte limit",
+                Marker::Warn,
+                content_type,
+                content.len(),
+                MAX_SNIPPET_BYTES
+            );
+            match format {
+                OutputFormat::Text => println!("{}", warning),
+                OutputFormat::Json => eprintln!("{}", warning),
+            }
+        }
+    }
+}
+
+fn print_preview(
+    preview: &Preview,
+    marker: Marker,
+    content_type: &str,
+    partition_str: &str,
+    content: &str,
+) {
+    println!("\n{}{} content preview:", marker, content_type);
+    println!("---");
+    println!("{}", preview.render(partition_str, content));
+    println!("---");
+}
+
+/// Shows what `add` would have written, for `--dry-run`.
+fn print_dry_run(mapping: &Mapping) {
+    println!("\n{}Documentation hash: {}", Marker::Doc, mapping.doc_hash);
+    println!("{}Code hash: {}", Marker::Code, mapping.code_hash);
+    println!("{}Mapping line:", Marker::List);
+    println!("{}", mapping.to_line());
+    println!("{}Dry run: .doks was not changed", Marker::Info);
+}
+
+fn extract(root: &Path, partition_str: &str, content_type: &str) -> Result<String> {
+    let partition = Partition::parse(partition_str)?;
+    partition
+        .extract_content_in(root)
+        .map_err(|e| anyhow!("Failed to extract {} content: {}", content_type, e))
+}
+
+fn normalize_description(description: String) -> Option<String> {
+    if description.trim().is_empty() {
+        None
+    } else {
+        Some(description.trim().to_string())
+    }
+}
@@ -0,0 +1,121 @@
+use anyhow::{anyhow, Result};
+use std::path::PathBuf;
+
+use crate::commands::add::build_mapping;
+use crate::config::DoksConfig;
+use crate::output::Marker;
+use crate::partition::Partition;
+
+/// One manifest line: `doc_partition code_partition [description]`.
+#[derive(Debug, PartialEq)]
+struct ManifestEntry<'a> {
+    doc_partition: &'a str,
+    code_partition: &'a str,
+    description: &'a str,
+}
+
+pub fn handle(file: PathBuf) -> Result<()> {
+    let file = super::resolve_path(file);
+    let doks_file_path = super::find_doks_file()?;
+
+    let mut config = DoksConfig::from_file(&doks_file_path)?;
+
+    let manifest = std::fs::read_to_string(&file)
+        .map_err(|e| anyhow!("Cannot read '{}': {}", file.display(), e))?;
+
+    // A bad line is reported and skipped; the rest of the batch still goes in
+    let mut added = 0;
+    let mut failures = Vec::new();
+    for (index, line) in manifest.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let result = parse_line(line).and_then(|entry| {
+            build_mapping(
+                &config.root,
+                entry.doc_partition,
+                entry.code_partition,
+                entry.description.to_string(),
+                config.hash_options,
+                false,
+            )
+        });
+        match result {
+            Ok(mapping) => {
+                println!(
+                    "{}Line {}: added {} -> {}",
+                    Marker::Ok,
+                    index + 1,
+                    mapping.doc_partition,
+                    mapping.code_partition
+                );
+                config.add_mapping(mapping);
+                added += 1;
+            }
+            Err(e) => {
+                println!("{}Line {}: {}", Marker::Fail, index + 1, e);
+                failures.push(index + 1);
+            }
+        }
+    }
+
+    if added > 0 {
+        config.to_file(&doks_file_path)?;
+    }
+
+    println!();
+    println!(
+        "{}Added {} mapping(s) from {}",
+        Marker::Stats,
+        added,
+        file.display()
+    );
+    if !failures.is_empty() {
+        let lines: Vec<String> = failures.iter().map(usize::to_string).collect();
+        println!("   {}Failed line(s): {}", Marker::Fail, lines.join(", "));
+    }
+
+    Ok(())
+}
+
+fn parse_line(line: &str) -> Result<ManifestEntry<'_>> {
+    let mut fields = line.splitn(3, char::is_whitespace);
+    let doc_partition = fields.next().unwrap_or_default();
+    let code_partition = fields
+        .next()
+        .ok_or_else(|| anyhow!("Expected 'doc_partition code_partition [description]'"))?;
+    let description = fields.next().unwrap_or_default().trim();
+
+    Partition::parse(doc_partition)
+        .map_err(|e| anyhow!("Invalid documentation partition '{}': {}", doc_partition, e))?;
+    Partition::parse(code_partition)
+        .map_err(|e| anyhow!("Invalid code partition '{}': {}", code_partition, e))?;
+
+    Ok(ManifestEntry {
+        doc_partition,
+        code_partition,
+        description,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_line() {
+        assert_eq!(
+            parse_line("README.md:1-5 src/main.rs:10-20 Entry point   docs").unwrap(),
+            ManifestEntry {
+                doc_partition: "README.md:1-5",
+                code_partition: "src/main.rs:10-20",
+                description: "Entry point   docs",
+            }
+        );
+        assert_eq!(parse_line("README.md src/lib.rs").unwrap().description, "");
+        assert!(parse_line("README.md:1-5").is_err());
+        assert!(parse_line("README.md:abc src/lib.rs").is_err());
+    }
+}
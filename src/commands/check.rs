@@ -0,0 +1,211 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use crate::config::{DoksConfig, Mapping};
+use crate::error::DoksError;
+use crate::hash::check_hash;
+use crate::partition::Partition;
+use crate::verify::SideCaches;
+
+/// One broken side of a mapping, printed as `path:line: message`.
+#[derive(Debug, PartialEq)]
+pub struct Failure {
+    pub path: PathBuf,
+    pub line: usize,
+    pub message: String,
+    /// The content was extracted but no longer matches its stored hash, as
+    /// opposed to a partition that can't be parsed, read or extracted.
+    pub drifted: bool,
+}
+
+pub fn handle() -> Result<()> {
+    let doks_file_path = super::find_doks_file()?;
+
+    let config = DoksConfig::from_file(&doks_file_path)?;
+    let caches = SideCaches::for_config(&config);
+    let cwd = super::working_dir()?;
+
+    let mut failed = false;
+    for mapping in config.mappings.iter().filter(|m| m.enabled) {
+        for failure in check_mapping(&config, &doks_file_path, mapping, &caches) {
+            let path = failure.path.strip_prefix(&cwd).unwrap_or(&failure.path);
+            println!("{}:{}: {}", path.display(), failure.line, failure.message);
+            failed = true;
+        }
+    }
+
+    if failed {
+        process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Each broken side of `mapping`, located at the first line its partition
+/// covers; unparsable partitions point at line 1 of the `.doks` file.
+pub fn check_mapping(
+    config: &DoksConfig,
+    doks_file_path: &Path,
+    mapping: &Mapping,
+    caches: &SideCaches,
+) -> Vec<Failure> {
+    let sides = [
+        (
+            &mapping.doc_partition,
+            &mapping.doc_hash,
+            "documentation",
+            config.doc_root(),
+            caches.doc(),
+            mapping.hash_options(config.hash_options),
+        ),
+        (
+            &mapping.code_partition,
+            &mapping.code_hash,
+            "code",
+            config.code_root(),
+            caches.code(),
+            mapping.code_hash_options(config.hash_options),
+        ),
+    ];
+
+    let mut failures = Vec::new();
+    for (partition_str, expected_hash, content_type, root, cache, hash_options) in sides {
+        let partition = match Partition::parse(partition_str) {
+            Ok(partition) => partition,
+            Err(e) => {
+                failures.push(Failure {
+                    path: doks_file_path.to_path_buf(),
+                    line: 1,
+                    message: format!(
+                        "invalid {} partition '{}' in mapping {}: {}",
+                        content_type, partition_str, mapping.id, e
+                    ),
+                    drifted: false,
+                });
+                continue;
+            }
+        };
+
+        let path = partition.locate_in(root).unwrap_or_else(|_| {
+            root.join(
+                partition
+                    .expanded_path()
+                    .unwrap_or(partition.file_path.clone()),
+            )
+        });
+        let content = match cache.read(&partition.file_path) {
+            Ok(content) => content,
+            Err(e) => {
+                failures.push(Failure {
+                    path,
+                    line: 1,
+                    message: e.to_string(),
+                    drifted: false,
+                });
+                continue;
+            }
+        };
+
+        let line = partition
+            .covered_lines(&content)
+            .ok()
+            .and_then(|ranges| ranges.first().map(|&(start, _)| start))
+            .unwrap_or(1);
+        let (message, drifted) = match partition.extract_from(&content) {
+            Err(e) => (
+                format!("failed to extract {} content: {}", content_type, e),
+                false,
+            ),
+            Ok(extracted) => match check_hash(&extracted, expected_hash, hash_options) {
+                Ok(()) => continue,
+                Err(e @ DoksError::MalformedHash(_)) => (
+                    format!("{} {} (mapping {})", content_type, e, mapping.id),
+                    false,
+                ),
+                Err(_) => (
+                    format!(
+                        "{} content has changed (mapping {})",
+                        content_type, mapping.id
+                    ),
+                    true,
+                ),
+            },
+        };
+        failures.push(Failure {
+            path,
+            line,
+            message,
+            drifted,
+        });
+    }
+    failures
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::{hash_content, HashOptions};
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_check_mapping_reports_start_line() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+        fs::write(
+            dir.path().join("lib.rs"),
+            "// lib\nfn run() {}\nfn stop() {}",
+        )
+        .unwrap();
+
+        let mut config = DoksConfig::new("README.md".to_string());
+        config.root = dir.path().to_path_buf();
+        let mapping = Mapping {
+            id: "mapping-1".to_string(),
+            doc_partition: "README.md:2".to_string(),
+            code_partition: "lib.rs:2-3".to_string(),
+            doc_hash: hash_content("Content", HashOptions::default()),
+            code_hash: "0".repeat(64),
+            description: None,
+            normalize_whitespace: false,
+            strip_comments: false,
+            tags: Vec::new(),
+            doc_snippet: None,
+            code_snippet: None,
+            doc_lines: Vec::new(),
+            code_lines: Vec::new(),
+            comments: Vec::new(),
+            created_at: None,
+            updated_at: None,
+            enabled: true,
+        };
+
+        let cache = SideCaches::for_config(&config);
+        let doks = dir.path().join(".doks");
+        assert_eq!(
+            check_mapping(&config, &doks, &mapping, &cache),
+            vec![Failure {
+                path: dir.path().join("lib.rs"),
+                line: 2,
+                message: "code content has changed (mapping mapping-1)".to_string(),
+                drifted: true,
+            }]
+        );
+
+        let broken = Mapping {
+            doc_partition: "README.md:abc".to_string(),
+            code_partition: "missing.rs:1".to_string(),
+            ..mapping
+        };
+        let failures = check_mapping(&config, &doks, &broken, &cache);
+        assert_eq!(failures.len(), 2);
+        assert_eq!(
+            (failures[0].path.as_path(), failures[0].line),
+            (doks.as_path(), 1)
+        );
+        assert_eq!(failures[1].path, dir.path().join("missing.rs"));
+        assert_eq!(failures[1].message, "File not found: missing.rs");
+        assert!(failures.iter().all(|failure| !failure.drifted));
+    }
+}
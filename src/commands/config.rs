@@ -0,0 +1,30 @@
+use anyhow::Result;
+
+use crate::cli::ConfigAction;
+use crate::config::DoksConfig;
+use crate::output::Marker;
+
+/// Reads or changes one header field of `.doks`, e.g. `default_doc`.
+pub fn handle(action: ConfigAction) -> Result<()> {
+    let doks_file_path = super::find_doks_file()?;
+    let mut config = DoksConfig::from_file(&doks_file_path)?;
+
+    match action {
+        ConfigAction::Get { key } => println!("{}", config.header(&key)?),
+        ConfigAction::Set { key, value } => {
+            config.set_header(&key, &value)?;
+            config.to_file(&doks_file_path)?;
+
+            println!("{}Set {}={}", Marker::Ok, key, config.header(&key)?);
+            let rehash_needed = matches!(key.as_str(), "hash_algo" | "normalize_line_endings");
+            if rehash_needed && !config.mappings.is_empty() {
+                println!(
+                    "{}Stored hashes use the old setting; run 'doksnet sync' to refresh them",
+                    Marker::Tip
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
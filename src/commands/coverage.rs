@@ -0,0 +1,151 @@
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::DoksConfig;
+use crate::output::Marker;
+use crate::partition::Partition;
+
+pub fn handle(file: PathBuf) -> Result<()> {
+    let file = super::resolve_path(file);
+    let doks_file_path = super::find_doks_file()?;
+
+    let config = DoksConfig::from_file(&doks_file_path)?;
+
+    let target =
+        fs::canonicalize(&file).map_err(|e| anyhow!("Cannot read '{}': {}", file.display(), e))?;
+    let content = fs::read_to_string(&target)
+        .map_err(|e| anyhow!("Cannot read '{}': {}", file.display(), e))?;
+    let line_count = content.lines().count();
+
+    let mut ranges = Vec::new();
+    let mut mapping_count = 0;
+    for mapping in &config.mappings {
+        let Ok(partition) = Partition::parse(&mapping.code_partition) else {
+            continue;
+        };
+        if !is_same_file(&config.root, &partition, &target) {
+            continue;
+        }
+        mapping_count += 1;
+        match partition.covered_lines(&content) {
+            Ok(covered) => ranges.extend(covered),
+            Err(e) => println!(
+                "{}Ignoring {} ({}): {}",
+                Marker::Warn,
+                mapping.id,
+                mapping.code_partition,
+                e
+            ),
+        }
+    }
+
+    let covered = merge_ranges(ranges);
+    let covered_count: usize = covered.iter().map(|(start, end)| end - start + 1).sum();
+    let uncovered = uncovered_ranges(&covered, line_count);
+
+    println!("{}Coverage for {}", Marker::Stats, file.display());
+    println!("   Mappings: {}", mapping_count);
+    println!(
+        "   Covered lines: {}/{} ({:.1}%)",
+        covered_count,
+        line_count,
+        percentage(covered_count, line_count)
+    );
+
+    if uncovered.is_empty() {
+        println!("\n{}Every line is documented", Marker::Ok);
+    } else {
+        println!("\n{}Uncovered lines:", Marker::Warn);
+        for range in &uncovered {
+            println!("   {}{}", Marker::Bullet, format_range(*range));
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether the file `partition` points at (relative to `root`) is `target`.
+fn is_same_file(root: &Path, partition: &Partition, target: &Path) -> bool {
+    partition
+        .locate_in(root)
+        .ok()
+        .and_then(|path| fs::canonicalize(path).ok())
+        .is_some_and(|path| path == target)
+}
+
+/// Sorts inclusive line ranges and merges overlapping or adjacent ones.
+fn merge_ranges(mut ranges: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+    ranges.sort_unstable();
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some((_, last_end)) if start <= *last_end + 1 => *last_end = (*last_end).max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// The gaps between merged `covered` ranges within lines `1..=line_count`.
+fn uncovered_ranges(covered: &[(usize, usize)], line_count: usize) -> Vec<(usize, usize)> {
+    let mut uncovered = Vec::new();
+    let mut next = 1;
+    for &(start, end) in covered {
+        if start > next {
+            uncovered.push((next, start - 1));
+        }
+        next = next.max(end + 1);
+    }
+    if next <= line_count {
+        uncovered.push((next, line_count));
+    }
+    uncovered
+}
+
+fn percentage(covered: usize, total: usize) -> f64 {
+    if total == 0 {
+        100.0
+    } else {
+        covered as f64 * 100.0 / total as f64
+    }
+}
+
+fn format_range((start, end): (usize, usize)) -> String {
+    if start == end {
+        start.to_string()
+    } else {
+        format!("{}-{}", start, end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_ranges() {
+        assert_eq!(
+            merge_ranges(vec![(5, 6), (1, 3), (2, 4), (10, 12), (7, 7)]),
+            vec![(1, 7), (10, 12)]
+        );
+        assert_eq!(merge_ranges(Vec::new()), Vec::new());
+    }
+
+    #[test]
+    fn test_uncovered_ranges() {
+        assert_eq!(
+            uncovered_ranges(&[(1, 3), (5, 6)], 10),
+            vec![(4, 4), (7, 10)]
+        );
+        assert_eq!(uncovered_ranges(&[(2, 10)], 10), vec![(1, 1)]);
+        assert_eq!(uncovered_ranges(&[], 3), vec![(1, 3)]);
+        assert_eq!(uncovered_ranges(&[(1, 3)], 3), Vec::new());
+    }
+
+    #[test]
+    fn test_percentage() {
+        assert_eq!(percentage(5, 10), 50.0);
+        assert_eq!(percentage(0, 0), 100.0);
+    }
+}
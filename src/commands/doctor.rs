@@ -0,0 +1,161 @@
+use anyhow::Result;
+use std::path::Path;
+use std::process;
+
+use crate::config::{duplicate_ids, DoksConfig};
+use crate::error::DoksError;
+use crate::hash::{is_valid_hash, verify_hash, HashOptions};
+use crate::output::Marker;
+use crate::partition::Partition;
+
+/// Problems found by `doctor`, grouped by category.
+#[derive(Default)]
+struct Report {
+    duplicate_ids: Vec<String>,
+    unparseable: Vec<String>,
+    missing_files: Vec<String>,
+    out_of_range: Vec<String>,
+    malformed_hashes: Vec<String>,
+    hash_mismatches: Vec<String>,
+}
+
+impl Report {
+    fn categories(&self) -> [(&'static str, &[String]); 6] {
+        [
+            ("Duplicate IDs", &self.duplicate_ids),
+            ("Unparseable partitions", &self.unparseable),
+            ("Missing files", &self.missing_files),
+            ("Ranges beyond end of file", &self.out_of_range),
+            ("Malformed stored hashes", &self.malformed_hashes),
+            ("Hash mismatches", &self.hash_mismatches),
+        ]
+    }
+
+    fn is_empty(&self) -> bool {
+        self.categories()
+            .iter()
+            .all(|(_, issues)| issues.is_empty())
+    }
+}
+
+pub fn handle() -> Result<()> {
+    let doks_file_path = super::find_doks_file()?;
+
+    let config = DoksConfig::from_file_allowing_duplicates(&doks_file_path)?;
+
+    if config.mappings.is_empty() {
+        println!(
+            "{}No mappings found. Use 'doksnet add' to create some first.",
+            Marker::Empty
+        );
+        return Ok(());
+    }
+
+    println!(
+        "{}Checking {} mappings",
+        Marker::Inspect,
+        config.mappings.len()
+    );
+
+    let mut report = Report {
+        duplicate_ids: duplicate_ids(&config.mappings),
+        ..Report::default()
+    };
+
+    for mapping in &config.mappings {
+        for (partition_str, expected_hash, content_type, hash_options) in [
+            (
+                &mapping.doc_partition,
+                &mapping.doc_hash,
+                "doc",
+                mapping.hash_options(config.hash_options),
+            ),
+            (
+                &mapping.code_partition,
+                &mapping.code_hash,
+                "code",
+                mapping.code_hash_options(config.hash_options),
+            ),
+        ] {
+            check_partition(
+                &mut report,
+                &config.root,
+                &format!("{} ({})", mapping.id, content_type),
+                partition_str,
+                expected_hash,
+                hash_options,
+                mapping.enabled,
+            );
+        }
+    }
+
+    println!();
+    for (title, issues) in report.categories() {
+        if issues.is_empty() {
+            println!("{}{}: none", Marker::Ok, title);
+        } else {
+            println!("{}{}: {}", Marker::Fail, title, issues.len());
+            for issue in issues {
+                println!("   {}{}", Marker::Bullet, issue);
+            }
+        }
+    }
+
+    if report.is_empty() {
+        println!("\n{}No problems found", Marker::Celebrate);
+        return Ok(());
+    }
+
+    println!(
+        "\n{}Tip: Use 'doksnet edit <id>' to fix partitions, or 'doksnet sync' to accept content changes",
+        Marker::Tip
+    );
+    process::exit(1);
+}
+
+/// Files the first problem with a partition under its category; a partition
+/// that can't be read isn't also reported as a hash mismatch. Without
+/// `check_content`, e.g. for a disabled mapping, drifted content is fine.
+fn check_partition(
+    report: &mut Report,
+    root: &Path,
+    label: &str,
+    partition_str: &str,
+    expected_hash: &str,
+    hash_options: HashOptions,
+    check_content: bool,
+) {
+    let partition = match Partition::parse(partition_str) {
+        Ok(partition) => partition,
+        Err(e) => {
+            report
+                .unparseable
+                .push(format!("{}: '{}': {}", label, partition_str, e));
+            return;
+        }
+    };
+
+    if let Err(e) = partition.locate_in(root) {
+        report.missing_files.push(match e {
+            DoksError::FileNotFound { path, .. } => format!("{}: {}", label, path),
+            e => format!("{}: {}", label, e),
+        });
+        return;
+    }
+
+    match partition.extract_content_in(root) {
+        Ok(_) if !is_valid_hash(expected_hash) => report
+            .malformed_hashes
+            .push(format!("{}: '{}'", label, expected_hash)),
+        Ok(content) => {
+            if check_content && !verify_hash(&content, expected_hash, hash_options) {
+                report
+                    .hash_mismatches
+                    .push(format!("{}: {}", label, partition_str));
+            }
+        }
+        Err(e) => report
+            .out_of_range
+            .push(format!("{}: {}: {}", label, partition_str, e)),
+    }
+}
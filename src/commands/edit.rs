@@ -1,43 +1,69 @@
 use anyhow::{anyhow, Result};
 use dialoguer::{Confirm, Input, Select};
+use std::path::Path;
+use uuid::Uuid;
 
-use crate::config::DoksConfig;
-use crate::hash::hash_content;
+use crate::cli::EditArgs;
+use crate::config::{escape_description, parse_tags, unescape_description, DoksConfig, Mapping};
+use crate::hash::HashOptions;
+use crate::output::Marker;
 use crate::partition::Partition;
 
-pub fn handle(id: String) -> Result<()> {
-    let doks_file_path = DoksConfig::find_doks_file()
-        .ok_or_else(|| anyhow!("No .doks file found. Run 'doksnet new' first."))?;
+use super::Preview;
+
+pub fn handle(args: EditArgs) -> Result<()> {
+    let doks_file_path = super::find_doks_file()?;
     let mut config = DoksConfig::from_file(&doks_file_path)?;
     if config.mappings.is_empty() {
-        println!("📭 No mappings found. Use 'doksnet add' to create some first.");
+        println!(
+            "{}No mappings found. Use 'doksnet add' to create some first.",
+            Marker::Empty
+        );
         return Ok(());
     }
 
     let mapping_index = config
         .mappings
         .iter()
-        .position(|m| m.id.starts_with(&id))
-        .ok_or_else(|| anyhow!("No mapping found with ID starting with '{}'", id))?;
+        .position(|m| m.id.starts_with(&args.id))
+        .ok_or_else(|| anyhow!("No mapping found with ID starting with '{}'", args.id))?;
 
+    let hash_options = config.mappings[mapping_index].hash_options(config.hash_options);
+    let root = config.root.clone();
+    let preview = Preview {
+        root: &root,
+        chars: config.preview_chars,
+        context: args.context,
+    };
     let mapping = &mut config.mappings[mapping_index];
 
-    println!("✏️  Editing mapping: {}", mapping.id);
+    if args.has_changes() {
+        apply_changes(mapping, &root, hash_options, args)?;
+        config.to_file(&doks_file_path)?;
+        println!("{}Successfully updated mapping!", Marker::Ok);
+        return Ok(());
+    }
+
+    println!("{}Editing mapping: {}", Marker::Edit, mapping.id);
     println!("Current values:");
-    println!("📄 Documentation: {}", mapping.doc_partition);
-    println!("💻 Code: {}", mapping.code_partition);
+    println!("{}Documentation: {}", Marker::Doc, mapping.doc_partition);
+    println!("{}Code: {}", Marker::Code, mapping.code_partition);
     if let Some(desc) = &mapping.description {
-        println!("📝 Description: {}", desc);
+        println!("{}Description: {}", Marker::Note, desc);
     } else {
-        println!("📝 Description: (none)");
+        println!("{}Description: (none)", Marker::Note);
     }
+    println!("{}Tags: {}", Marker::Pin, format_tags(&mapping.tags));
     println!();
 
     let options = vec![
         "Documentation partition",
         "Code partition",
         "Description",
+        "Tags",
         "Both documentation and code partitions",
+        "Regenerate ID",
+        "Set custom ID",
         "Cancel",
     ];
 
@@ -48,28 +74,111 @@ pub fn handle(id: String) -> Result<()> {
         .interact()?;
 
     match selection {
-        0 => edit_doc_partition(mapping)?,
-        1 => edit_code_partition(mapping)?,
+        0 => edit_doc_partition(mapping, hash_options, &preview)?,
+        1 => edit_code_partition(mapping, hash_options, &preview)?,
         2 => edit_description(mapping)?,
-        3 => {
-            edit_doc_partition(mapping)?;
-            edit_code_partition(mapping)?;
-        }
+        3 => edit_tags(mapping)?,
         4 => {
-            println!("❌ Edit cancelled");
+            edit_doc_partition(mapping, hash_options, &preview)?;
+            edit_code_partition(mapping, hash_options, &preview)?;
+        }
+        5 => {
+            let new_id = Uuid::new_v4().to_string();
+            config.rename_mapping(mapping_index, &new_id)?;
+            println!("{}New ID: {}", Marker::Ok, new_id);
+        }
+        6 => {
+            let new_id: String = Input::new()
+                .with_prompt("New ID")
+                .with_initial_text(&config.mappings[mapping_index].id)
+                .interact_text()?;
+            config.rename_mapping(mapping_index, &new_id)?;
+            println!("{}New ID: {}", Marker::Ok, new_id.trim());
+        }
+        7 => {
+            println!("{}Edit cancelled", Marker::Fail);
             return Ok(());
         }
         _ => unreachable!(),
     }
 
     config.to_file(&doks_file_path)?;
-    println!("✅ Successfully updated mapping!");
+    println!("{}Successfully updated mapping!", Marker::Ok);
 
     Ok(())
 }
 
-fn edit_doc_partition(mapping: &mut crate::config::Mapping) -> Result<()> {
-    println!("\n📄 Editing documentation partition");
+/// Applies the changes given as flags, validating partitions before
+/// anything is modified.
+fn apply_changes(
+    mapping: &mut Mapping,
+    root: &Path,
+    hash_options: HashOptions,
+    args: EditArgs,
+) -> Result<()> {
+    let doc = args
+        .doc
+        .map(|partition| extract(root, &partition, "documentation").map(|c| (partition, c)))
+        .transpose()?;
+    let code = args
+        .code
+        .map(|partition| extract(root, &partition, "code").map(|c| (partition, c)))
+        .transpose()?;
+
+    println!("{}Editing mapping: {}", Marker::Edit, mapping.id);
+    if let Some((partition, content)) = doc {
+        println!("{}Documentation: {}", Marker::Doc, partition);
+        mapping.doc_partition = partition;
+        mapping.rehash_doc(&content, hash_options);
+        mapping.touch();
+    }
+    if let Some((partition, content)) = code {
+        println!("{}Code: {}", Marker::Code, partition);
+        mapping.code_partition = partition;
+        mapping.rehash_code(&content, mapping.code_hash_options(hash_options));
+        mapping.touch();
+    }
+    if args.clear_description {
+        println!("{}Description: (none)", Marker::Note);
+        mapping.description = None;
+        mapping.touch();
+    } else if let Some(description) = args.description {
+        let description = description.trim().to_string();
+        println!("{}Description: {}", Marker::Note, description);
+        mapping.description = Some(description).filter(|d| !d.is_empty());
+        mapping.touch();
+    }
+    if args.clear_tags || !args.tags.is_empty() {
+        mapping.tags = parse_tags(&args.tags.join(","))?;
+        println!("{}Tags: {}", Marker::Pin, format_tags(&mapping.tags));
+        mapping.touch();
+    }
+    if args.enable || args.disable {
+        mapping.enabled = args.enable;
+        let status = if mapping.enabled {
+            "enabled"
+        } else {
+            "disabled"
+        };
+        println!("{}Status: {}", Marker::Pin, status);
+        mapping.touch();
+    }
+
+    Ok(())
+}
+
+pub fn extract(root: &Path, partition_str: &str, content_type: &str) -> Result<String> {
+    Partition::parse(partition_str)?
+        .extract_content_in(root)
+        .map_err(|e| anyhow!("Failed to extract {} content: {}", content_type, e))
+}
+
+fn edit_doc_partition(
+    mapping: &mut crate::config::Mapping,
+    hash_options: HashOptions,
+    preview: &Preview,
+) -> Result<()> {
+    println!("\n{}Editing documentation partition", Marker::Doc);
     println!("Current value: {}", mapping.doc_partition);
 
     let new_partition: String = Input::new()
@@ -80,15 +189,12 @@ fn edit_doc_partition(mapping: &mut crate::config::Mapping) -> Result<()> {
     if new_partition != mapping.doc_partition {
         let partition = Partition::parse(&new_partition)?;
         let content = partition
-            .extract_content()
+            .extract_content_in(preview.root)
             .map_err(|e| anyhow!("Failed to extract documentation content: {}", e))?;
 
-        println!("\n📄 New documentation content preview:");
+        println!("\n{}New documentation content preview:", Marker::Doc);
         println!("---");
-        println!("{}", content.chars().take(200).collect::<String>());
-        if content.len() > 200 {
-            println!("... (truncated)");
-        }
+        println!("{}", preview.render(&new_partition, &content));
         println!("---");
 
         let confirm = Confirm::new()
@@ -98,20 +204,25 @@ fn edit_doc_partition(mapping: &mut crate::config::Mapping) -> Result<()> {
 
         if confirm {
             mapping.doc_partition = new_partition;
-            mapping.doc_hash = hash_content(&content);
-            println!("✅ Documentation partition updated");
+            mapping.rehash_doc(&content, hash_options);
+            mapping.touch();
+            println!("{}Documentation partition updated", Marker::Ok);
         } else {
-            println!("❌ Documentation partition change cancelled");
+            println!("{}Documentation partition change cancelled", Marker::Fail);
         }
     } else {
-        println!("ℹ️  No changes made to documentation partition");
+        println!("{}No changes made to documentation partition", Marker::Info);
     }
 
     Ok(())
 }
 
-fn edit_code_partition(mapping: &mut crate::config::Mapping) -> Result<()> {
-    println!("\n💻 Editing code partition");
+fn edit_code_partition(
+    mapping: &mut crate::config::Mapping,
+    hash_options: HashOptions,
+    preview: &Preview,
+) -> Result<()> {
+    println!("\n{}Editing code partition", Marker::Code);
     println!("Current value: {}", mapping.code_partition);
 
     let new_partition: String = Input::new()
@@ -122,15 +233,12 @@ fn edit_code_partition(mapping: &mut crate::config::Mapping) -> Result<()> {
     if new_partition != mapping.code_partition {
         let partition = Partition::parse(&new_partition)?;
         let content = partition
-            .extract_content()
+            .extract_content_in(preview.root)
             .map_err(|e| anyhow!("Failed to extract code content: {}", e))?;
 
-        println!("\n💻 New code content preview:");
+        println!("\n{}New code content preview:", Marker::Code);
         println!("---");
-        println!("{}", content.chars().take(200).collect::<String>());
-        if content.len() > 200 {
-            println!("... (truncated)");
-        }
+        println!("{}", preview.render(&new_partition, &content));
         println!("---");
 
         let confirm = Confirm::new()
@@ -140,36 +248,39 @@ fn edit_code_partition(mapping: &mut crate::config::Mapping) -> Result<()> {
 
         if confirm {
             mapping.code_partition = new_partition;
-            mapping.code_hash = hash_content(&content);
-            println!("✅ Code partition updated");
+            mapping.rehash_code(&content, mapping.code_hash_options(hash_options));
+            mapping.touch();
+            println!("{}Code partition updated", Marker::Ok);
         } else {
-            println!("❌ Code partition change cancelled");
+            println!("{}Code partition change cancelled", Marker::Fail);
         }
     } else {
-        println!("ℹ️  No changes made to code partition");
+        println!("{}No changes made to code partition", Marker::Info);
     }
 
     Ok(())
 }
 
 fn edit_description(mapping: &mut crate::config::Mapping) -> Result<()> {
-    println!("\n📝 Editing description");
-    let current_desc = mapping.description.as_deref().unwrap_or("");
+    println!("\n{}Editing description", Marker::Note);
+    // The prompt is a single line, so line breaks are shown and typed as \n
+    let current_desc = escape_description(mapping.description.as_deref().unwrap_or(""));
     println!(
         "Current value: {}",
         if current_desc.is_empty() {
             "(none)"
         } else {
-            current_desc
+            &current_desc
         }
     );
 
     let new_description: String = Input::new()
-        .with_prompt("New description (leave empty to remove)")
+        .with_prompt("New description (\\n for a line break, leave empty to remove)")
         .with_initial_text(current_desc)
         .allow_empty(true)
         .interact_text()?;
 
+    let new_description = unescape_description(&new_description);
     let new_description = if new_description.trim().is_empty() {
         None
     } else {
@@ -178,10 +289,94 @@ fn edit_description(mapping: &mut crate::config::Mapping) -> Result<()> {
 
     if new_description != mapping.description {
         mapping.description = new_description;
-        println!("✅ Description updated");
+        mapping.touch();
+        println!("{}Description updated", Marker::Ok);
+    } else {
+        println!("{}No changes made to description", Marker::Info);
+    }
+
+    Ok(())
+}
+
+fn edit_tags(mapping: &mut Mapping) -> Result<()> {
+    println!("\n{}Editing tags", Marker::Pin);
+    println!("Current value: {}", format_tags(&mapping.tags));
+
+    let new_tags: String = Input::new()
+        .with_prompt("Tags, comma-separated (leave empty to remove all)")
+        .with_initial_text(mapping.tags.join(","))
+        .allow_empty(true)
+        .interact_text()?;
+    let new_tags = parse_tags(&new_tags)?;
+
+    if new_tags != mapping.tags {
+        mapping.tags = new_tags;
+        mapping.touch();
+        println!("{}Tags updated", Marker::Ok);
     } else {
-        println!("ℹ️  No changes made to description");
+        println!("{}No changes made to tags", Marker::Info);
     }
 
     Ok(())
 }
+
+fn format_tags(tags: &[String]) -> String {
+    if tags.is_empty() {
+        "(none)".to_string()
+    } else {
+        tags.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_apply_changes_updates_timestamp() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("main.rs"), "fn main() {}").unwrap();
+
+        let created_at = "2024-01-02T03:04:05Z".to_string();
+        let mut mapping = Mapping {
+            id: "edit-mapping".to_string(),
+            doc_partition: "README.md:1".to_string(),
+            code_partition: "main.rs:1".to_string(),
+            doc_hash: "0".repeat(64),
+            code_hash: "0".repeat(64),
+            description: None,
+            normalize_whitespace: false,
+            strip_comments: false,
+            tags: Vec::new(),
+            doc_snippet: None,
+            code_snippet: None,
+            doc_lines: Vec::new(),
+            code_lines: Vec::new(),
+            comments: Vec::new(),
+            created_at: Some(created_at.clone()),
+            updated_at: Some(created_at.clone()),
+            enabled: true,
+        };
+        let args = EditArgs {
+            id: "edit-mapping".to_string(),
+            doc: None,
+            code: None,
+            description: Some("Entry point".to_string()),
+            clear_description: false,
+            tags: vec!["cli".to_string(), "startup, cli".to_string()],
+            clear_tags: false,
+            enable: false,
+            disable: false,
+            context: 0,
+        };
+
+        apply_changes(&mut mapping, dir.path(), HashOptions::default(), args).unwrap();
+
+        assert_eq!(mapping.description.as_deref(), Some("Entry point"));
+        assert_eq!(mapping.tags, vec!["cli", "startup"]);
+        assert_eq!(mapping.created_at, Some(created_at.clone()));
+        assert!(mapping.updated_at.unwrap() > created_at);
+    }
+}
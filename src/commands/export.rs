@@ -0,0 +1,90 @@
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::cli::ExportFormat;
+use crate::config::{DoksConfig, Mapping};
+
+#[derive(Serialize)]
+struct ExportedMapping<'a> {
+    id: &'a str,
+    doc_partition: &'a str,
+    code_partition: &'a str,
+    doc_hash: &'a str,
+    code_hash: &'a str,
+    description: Option<&'a str>,
+    normalize_whitespace: bool,
+    strip_comments: bool,
+    tags: &'a [String],
+}
+
+impl<'a> From<&'a Mapping> for ExportedMapping<'a> {
+    fn from(mapping: &'a Mapping) -> Self {
+        Self {
+            id: &mapping.id,
+            doc_partition: &mapping.doc_partition,
+            code_partition: &mapping.code_partition,
+            doc_hash: &mapping.doc_hash,
+            code_hash: &mapping.code_hash,
+            description: mapping.description.as_deref(),
+            normalize_whitespace: mapping.normalize_whitespace,
+            strip_comments: mapping.strip_comments,
+            tags: &mapping.tags,
+        }
+    }
+}
+
+const CSV_HEADER: &str = "id,doc_partition,code_partition,doc_hash,code_hash,description";
+
+pub fn handle(format: ExportFormat) -> Result<()> {
+    let doks_file_path = super::find_doks_file()?;
+
+    let config = DoksConfig::from_file(&doks_file_path)?;
+    let mappings: Vec<ExportedMapping> = config.mappings.iter().map(Into::into).collect();
+
+    match format {
+        ExportFormat::Json => println!("{}", serde_json::to_string_pretty(&mappings)?),
+        ExportFormat::Csv => print!("{}", to_csv(&mappings)),
+    }
+
+    Ok(())
+}
+
+fn to_csv(mappings: &[ExportedMapping]) -> String {
+    let mut csv = format!("{}\n", CSV_HEADER);
+    for mapping in mappings {
+        let fields = [
+            mapping.id,
+            mapping.doc_partition,
+            mapping.code_partition,
+            mapping.doc_hash,
+            mapping.code_hash,
+            mapping.description.unwrap_or_default(),
+        ];
+        let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Quotes a field when it contains a delimiter, quote or line break (RFC 4180).
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_field() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a, b"), "\"a, b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field(""), "");
+    }
+}
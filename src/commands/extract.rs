@@ -0,0 +1,22 @@
+use anyhow::{anyhow, Result};
+use std::io::Write;
+
+use crate::partition::Partition;
+
+/// Prints the content `partition_str` selects, exactly as a mapping would
+/// hash it, so the partition syntax can be used from scripts. A relative
+/// path is taken relative to the working directory; no `.doks` is needed.
+pub fn handle(partition_str: String) -> Result<()> {
+    let partition = Partition::parse(&partition_str)
+        .map_err(|e| anyhow!("Invalid partition '{}': {}", partition_str, e))?;
+    let content = partition
+        .extract_content_in(&super::working_dir()?)
+        .map_err(|e| anyhow!("Cannot extract '{}': {}", partition_str, e))?;
+
+    let mut stdout = std::io::stdout().lock();
+    stdout.write_all(content.as_bytes())?;
+    if !content.is_empty() && !content.ends_with('\n') {
+        stdout.write_all(b"\n")?;
+    }
+    Ok(())
+}
@@ -0,0 +1,166 @@
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use crate::config::{parse_tags, timestamp_now, DoksConfig, Mapping};
+use crate::hash::{hash_content, HashOptions};
+use crate::output::Marker;
+use crate::partition::Partition;
+
+/// One entry of the imported JSON array, in the shape `export` writes.
+#[derive(Deserialize)]
+struct ImportedMapping {
+    id: Option<String>,
+    doc_partition: String,
+    code_partition: String,
+    /// Computed from the current content when missing.
+    doc_hash: Option<String>,
+    code_hash: Option<String>,
+    description: Option<String>,
+    /// Hashing flags the supplied hashes were computed with.
+    #[serde(default)]
+    normalize_whitespace: bool,
+    #[serde(default)]
+    strip_comments: bool,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+pub fn handle(file: PathBuf, overwrite: bool) -> Result<()> {
+    let file = super::resolve_path(file);
+    let doks_file_path = super::find_doks_file()?;
+
+    let mut config = DoksConfig::from_file(&doks_file_path)?;
+
+    let json = std::fs::read_to_string(&file)
+        .map_err(|e| anyhow!("Cannot read '{}': {}", file.display(), e))?;
+    let imported: Vec<ImportedMapping> = serde_json::from_str(&json)
+        .map_err(|e| anyhow!("Invalid import file '{}': {}", file.display(), e))?;
+
+    // Validate everything before touching .doks, so a bad entry imports nothing
+    let mut mappings = Vec::new();
+    for (index, entry) in imported.into_iter().enumerate() {
+        let mapping = to_mapping(entry, &config.root, config.hash_options)
+            .map_err(|e| anyhow!("Mapping #{} in '{}': {}", index + 1, file.display(), e))?;
+        mappings.push(mapping);
+    }
+
+    let mut added = 0;
+    let mut replaced = 0;
+    let mut skipped = Vec::new();
+    for mapping in mappings {
+        match config.mappings.iter().position(|m| m.id == mapping.id) {
+            Some(index) if overwrite => {
+                let comments = std::mem::take(&mut config.mappings[index].comments);
+                config.mappings[index] = Mapping {
+                    comments,
+                    ..mapping
+                };
+                replaced += 1;
+            }
+            Some(_) => skipped.push(mapping.id),
+            None => {
+                config.add_mapping(mapping);
+                added += 1;
+            }
+        }
+    }
+
+    if added + replaced > 0 {
+        config.to_file(&doks_file_path)?;
+    }
+
+    println!(
+        "{}Imported {} mapping(s) from {}",
+        Marker::Ok,
+        added + replaced,
+        file.display()
+    );
+    if replaced > 0 {
+        println!("   {}Overwritten: {}", Marker::Rehash, replaced);
+    }
+    if !skipped.is_empty() {
+        println!(
+            "   {}Skipped (ID already exists): {}",
+            Marker::Skip,
+            skipped.len()
+        );
+        for id in &skipped {
+            println!("      {}{}", Marker::Bullet, id);
+        }
+        println!(
+            "{}Tip: Use 'doksnet import --overwrite' to replace existing mappings",
+            Marker::Tip
+        );
+    }
+    println!("{}Total mappings: {}", Marker::Stats, config.mappings.len());
+
+    Ok(())
+}
+
+fn to_mapping(entry: ImportedMapping, root: &Path, hash_options: HashOptions) -> Result<Mapping> {
+    let id = match entry.id.map(|id| id.trim().to_string()) {
+        Some(id) if id.contains('|') => return Err(anyhow!("ID cannot contain '|'")),
+        Some(id) if !id.is_empty() => id,
+        _ => Uuid::new_v4().to_string(),
+    };
+    let now = timestamp_now();
+
+    let mut mapping = Mapping {
+        id,
+        doc_partition: entry.doc_partition,
+        code_partition: entry.code_partition,
+        doc_hash: String::new(),
+        code_hash: String::new(),
+        description: entry
+            .description
+            .map(|d| d.trim().to_string())
+            .filter(|d| !d.is_empty()),
+        normalize_whitespace: entry.normalize_whitespace,
+        strip_comments: entry.strip_comments,
+        tags: parse_tags(&entry.tags.join(","))?,
+        doc_snippet: None,
+        code_snippet: None,
+        doc_lines: Vec::new(),
+        code_lines: Vec::new(),
+        comments: Vec::new(),
+        created_at: Some(now.clone()),
+        updated_at: Some(now),
+        enabled: true,
+    };
+    mapping.doc_hash = resolve_hash(
+        entry.doc_hash,
+        root,
+        &mapping.doc_partition,
+        mapping.hash_options(hash_options),
+    )?;
+    mapping.code_hash = resolve_hash(
+        entry.code_hash,
+        root,
+        &mapping.code_partition,
+        mapping.code_hash_options(hash_options),
+    )?;
+    Ok(mapping)
+}
+
+/// Checks that the partition parses, hashing its current content when no
+/// hash was supplied.
+fn resolve_hash(
+    hash: Option<String>,
+    root: &Path,
+    partition_str: &str,
+    hash_options: HashOptions,
+) -> Result<String> {
+    let partition = Partition::parse(partition_str)
+        .map_err(|e| anyhow!("Invalid partition '{}': {}", partition_str, e))?;
+    match hash {
+        Some(hash) => Ok(hash),
+        None => {
+            let content = partition
+                .extract_content_in(root)
+                .map_err(|e| anyhow!("Failed to extract '{}': {}", partition_str, e))?;
+            Ok(hash_content(&content, hash_options))
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::cli::OutputFormat;
+use crate::commands::test;
+use crate::config::DoksConfig;
+use crate::output::Marker;
+
+#[derive(Serialize)]
+struct ListedMapping<'a> {
+    id: &'a str,
+    doc_partition: &'a str,
+    code_partition: &'a str,
+    description: Option<&'a str>,
+    tags: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<&'static str>,
+}
+
+pub fn handle(format: OutputFormat, with_status: bool) -> Result<()> {
+    let doks_file_path = super::find_doks_file()?;
+
+    let config = DoksConfig::from_file(&doks_file_path)?;
+
+    // Extracting every partition is the expensive part, so only do it on request
+    let statuses: Vec<Option<&'static str>> = if with_status {
+        test::check_all(&config)
+            .iter()
+            .map(|errors| Some(if errors.is_empty() { "pass" } else { "fail" }))
+            .collect()
+    } else {
+        vec![None; config.mappings.len()]
+    };
+
+    match format {
+        OutputFormat::Text => print_table(&config, &statuses),
+        OutputFormat::Json => {
+            let mappings: Vec<ListedMapping> = config
+                .mappings
+                .iter()
+                .zip(statuses)
+                .map(|(m, status)| ListedMapping {
+                    id: &m.id,
+                    doc_partition: &m.doc_partition,
+                    code_partition: &m.code_partition,
+                    description: m.description.as_deref(),
+                    tags: &m.tags,
+                    status,
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&mappings)?);
+        }
+    }
+
+    Ok(())
+}
+
+fn print_table(config: &DoksConfig, statuses: &[Option<&'static str>]) {
+    if config.mappings.is_empty() {
+        println!(
+            "{}No mappings found. Use 'doksnet add' to create some first.",
+            Marker::Empty
+        );
+        return;
+    }
+
+    println!(
+        "{}{} documentation-code mappings",
+        Marker::List,
+        config.mappings.len()
+    );
+    println!();
+
+    let with_status = statuses.iter().any(Option::is_some);
+    let with_tags = config.mappings.iter().any(|m| !m.tags.is_empty());
+    let rows: Vec<Vec<String>> = config
+        .mappings
+        .iter()
+        .zip(statuses)
+        .map(|(m, status)| {
+            let mut row = vec![
+                m.short_id(),
+                m.doc_partition.clone(),
+                m.code_partition.clone(),
+                first_line(m.description.as_deref().unwrap_or_default()),
+            ];
+            if with_tags {
+                row.push(m.tags.join(","));
+            }
+            if let Some(status) = status {
+                row.insert(1, status.to_uppercase());
+            }
+            row
+        })
+        .collect();
+
+    let mut headers = vec!["ID", "Documentation", "Code", "Description"];
+    if with_tags {
+        headers.push("Tags");
+    }
+    if with_status {
+        headers.insert(1, "Status");
+    }
+    let headers: Vec<String> = headers.into_iter().map(String::from).collect();
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    print_row(&headers, &widths);
+    print_row(
+        &widths.iter().map(|w| "-".repeat(*w)).collect::<Vec<_>>(),
+        &widths,
+    );
+    for row in &rows {
+        print_row(row, &widths);
+    }
+}
+
+/// The first line of a multi-line description, marked as cut short, so the
+/// table keeps one row per mapping.
+fn first_line(description: &str) -> String {
+    match description.split_once('\n') {
+        Some((first, _)) => format!("{} ...", first.trim_end()),
+        None => description.to_string(),
+    }
+}
+
+fn print_row(cells: &[String], widths: &[usize]) {
+    let line: Vec<String> = cells
+        .iter()
+        .zip(widths)
+        .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+        .collect();
+    println!("{}", line.join("  ").trim_end());
+}
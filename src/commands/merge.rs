@@ -0,0 +1,124 @@
+use anyhow::{anyhow, Result};
+use std::path::{Path, PathBuf};
+
+use crate::config::DoksConfig;
+use crate::output::Marker;
+use crate::partition::Partition;
+
+/// Appends the mappings of another `.doks` file, e.g. when two repositories
+/// are combined into one. With `prefix`, the other file's partitions are
+/// rewritten to point under that directory. Mappings whose id is already
+/// taken are left out and reported.
+pub fn handle(other: PathBuf, prefix: Option<String>) -> Result<()> {
+    let other = super::resolve_path(other);
+    let doks_file_path = super::find_doks_file()?;
+
+    let mut config = DoksConfig::from_file(&doks_file_path)?;
+    let other_config = DoksConfig::from_file(&other)
+        .map_err(|e| anyhow!("Cannot load '{}': {}", other.display(), e))?;
+
+    // Stored hashes are only comparable when they were computed the same way
+    let ours = config.hash_options;
+    let theirs = other_config.hash_options;
+    if ours.algorithm != theirs.algorithm
+        || ours.normalize_line_endings != theirs.normalize_line_endings
+    {
+        return Err(anyhow!(
+            "'{}' hashes with different settings than .doks (hash_algo={}, normalize_line_endings={} vs hash_algo={}, normalize_line_endings={})",
+            other.display(),
+            theirs.algorithm,
+            theirs.normalize_line_endings,
+            ours.algorithm,
+            ours.normalize_line_endings
+        ));
+    }
+
+    // Rewrite everything before touching .doks, so a bad partition merges nothing
+    let mut mappings = Vec::new();
+    for mut mapping in other_config.mappings {
+        if let Some(prefix) = &prefix {
+            mapping.doc_partition = prefix_partition(&mapping.doc_partition, prefix)?;
+            mapping.code_partition = prefix_partition(&mapping.code_partition, prefix)?;
+        }
+        mappings.push(mapping);
+    }
+
+    let mut added = 0;
+    let mut collisions = Vec::new();
+    for mapping in mappings {
+        if config.mappings.iter().any(|m| m.id == mapping.id) {
+            collisions.push(mapping.id);
+        } else {
+            config.add_mapping(mapping);
+            added += 1;
+        }
+    }
+
+    if added > 0 {
+        config.to_file(&doks_file_path)?;
+    }
+
+    println!(
+        "{}Merged {} mapping(s) from {}",
+        Marker::Ok,
+        added,
+        other.display()
+    );
+    if let Some(prefix) = &prefix {
+        println!("   {}Paths prefixed with: {}", Marker::Pin, prefix);
+    }
+    if !collisions.is_empty() {
+        println!(
+            "   {}Skipped (ID already exists): {}",
+            Marker::Skip,
+            collisions.len()
+        );
+        for id in &collisions {
+            println!("      {}{}", Marker::Bullet, id);
+        }
+        println!(
+            "{}Tip: Use 'doksnet edit <id>' to give the existing mappings new IDs, then merge again",
+            Marker::Tip
+        );
+    }
+    println!("{}Total mappings: {}", Marker::Stats, config.mappings.len());
+
+    Ok(())
+}
+
+/// The partition with `prefix` joined onto its file path. Absolute paths and
+/// paths starting with an environment variable are left alone, since they
+/// don't depend on where `.doks` lives.
+fn prefix_partition(partition_str: &str, prefix: &str) -> Result<String> {
+    let mut partition = Partition::parse(partition_str)
+        .map_err(|e| anyhow!("Invalid partition '{}': {}", partition_str, e))?;
+    if Path::new(&partition.file_path).is_absolute() || partition.file_path.starts_with('$') {
+        return Ok(partition_str.to_string());
+    }
+
+    let prefix = prefix.trim_end_matches('/');
+    partition.file_path = format!("{}/{}", prefix, partition.file_path);
+    Ok(partition.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_partition() {
+        assert_eq!(
+            prefix_partition("README.md:1-5", "services/api/").unwrap(),
+            "services/api/README.md:1-5"
+        );
+        assert_eq!(
+            prefix_partition("src/lib.rs:@@auth@@", "services/api").unwrap(),
+            "services/api/src/lib.rs:@@auth@@"
+        );
+        assert_eq!(
+            prefix_partition("$OUT_DIR/gen.md:1", "services/api").unwrap(),
+            "$OUT_DIR/gen.md:1"
+        );
+        assert!(prefix_partition("src/lib.rs:x", "services/api").is_err());
+    }
+}
@@ -1,6 +1,117 @@
+use anyhow::{anyhow, Result};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::config::DoksConfig;
+use crate::output::{context_preview, preview};
+use crate::partition::Partition;
+
 pub mod add;
+pub mod add_batch;
+pub mod check;
+pub mod config;
+pub mod coverage;
+pub mod doctor;
 pub mod edit;
+pub mod export;
+pub mod extract;
+pub mod import;
+pub mod list;
+pub mod merge;
+pub mod move_mapping;
 pub mod new;
+pub mod remove;
 pub mod remove_failed;
+pub mod rename_file;
+pub mod show;
+pub mod sort;
+pub mod stats;
+pub mod sync;
 pub mod test;
 pub mod test_interactive;
+pub mod undo;
+pub mod validate;
+pub mod watch;
+
+static DOKS_FILE: OnceLock<PathBuf> = OnceLock::new();
+static WORKING_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Records the `--cwd` and `--file` overrides, once, at startup.
+pub fn init(doks_file: Option<PathBuf>, cwd: Option<PathBuf>) {
+    if let Some(dir) = cwd {
+        let _ = WORKING_DIR.set(dir);
+    }
+    if let Some(path) = doks_file {
+        let _ = DOKS_FILE.set(resolve_path(path));
+    }
+}
+
+/// The `--cwd` directory when one was given, otherwise the current directory.
+/// Commands use this instead of changing the process's directory.
+pub fn working_dir() -> Result<PathBuf> {
+    match WORKING_DIR.get() {
+        Some(dir) if dir.is_dir() => Ok(dir.clone()),
+        Some(dir) => Err(anyhow!("--cwd {} is not a directory", dir.display())),
+        None => Ok(std::env::current_dir()?),
+    }
+}
+
+/// A path given on the command line, taken relative to `--cwd` if set.
+pub fn resolve_path(path: PathBuf) -> PathBuf {
+    match WORKING_DIR.get() {
+        Some(dir) => dir.join(path),
+        None => path,
+    }
+}
+
+/// The `--file` path when one was given, otherwise the nearest `.doks` found
+/// searching upward from the working directory.
+pub fn find_doks_file() -> Result<PathBuf> {
+    match (locate_doks_file()?, DOKS_FILE.get()) {
+        (Some(path), _) => Ok(path),
+        (None, Some(path)) => Err(anyhow!(
+            "{} not found. Run 'doksnet new --file {}' first.",
+            path.display(),
+            path.display()
+        )),
+        (None, None) => Err(anyhow!("No .doks file found. Run 'doksnet new' first.")),
+    }
+}
+
+/// Like [`find_doks_file`], but a missing `.doks` is `None` rather than an
+/// error, for commands that can treat it as nothing to do.
+pub fn locate_doks_file() -> Result<Option<PathBuf>> {
+    match DOKS_FILE.get() {
+        Some(path) => Ok(path.exists().then(|| path.clone())),
+        None => Ok(DoksConfig::find_doks_file_from(&working_dir()?)),
+    }
+}
+
+/// The `--file` override, if any.
+pub fn doks_file_override() -> Option<&'static PathBuf> {
+    DOKS_FILE.get()
+}
+
+/// How partition content is previewed before it's hashed.
+pub struct Preview<'a> {
+    pub root: &'a Path,
+    /// Characters shown (`preview_chars=`); 0 shows everything.
+    pub chars: usize,
+    /// Lines of the file shown around the partition (`--context`).
+    pub context: usize,
+}
+
+impl Preview<'_> {
+    /// The preview of `content`, extracted from `partition_str`. With no
+    /// context that's the first `chars` characters; otherwise the whole
+    /// partition, numbered, between the lines of the file around it.
+    pub fn render(&self, partition_str: &str, content: &str) -> String {
+        if self.context == 0 {
+            return preview(content, self.chars);
+        }
+        Partition::parse(partition_str)
+            .and_then(|partition| partition.extract_with_context_in(self.root, self.context))
+            .map(|lines| context_preview(&lines))
+            .unwrap_or_else(|_| preview(content, self.chars))
+    }
+}
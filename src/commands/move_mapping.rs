@@ -0,0 +1,61 @@
+use anyhow::{anyhow, Result};
+
+use crate::config::DoksConfig;
+use crate::output::Marker;
+
+/// Points a mapping at new partitions, e.g. after moving a function to
+/// another file, and re-hashes them from their current content.
+pub fn handle(id: String, to_doc: Option<String>, to_code: Option<String>) -> Result<()> {
+    let doks_file_path = super::find_doks_file()?;
+    let mut config = DoksConfig::from_file(&doks_file_path)?;
+
+    let mapping_index = config
+        .mappings
+        .iter()
+        .position(|m| m.id.starts_with(&id))
+        .ok_or_else(|| anyhow!("No mapping found with ID starting with '{}'", id))?;
+
+    // Read both targets before touching the mapping, so a bad one saves nothing
+    let doc = to_doc
+        .map(|partition| {
+            super::edit::extract(&config.root, &partition, "documentation").map(|c| (partition, c))
+        })
+        .transpose()?;
+    let code = to_code
+        .map(|partition| {
+            super::edit::extract(&config.root, &partition, "code").map(|c| (partition, c))
+        })
+        .transpose()?;
+
+    let hash_options = config.mappings[mapping_index].hash_options(config.hash_options);
+    let mapping = &mut config.mappings[mapping_index];
+
+    println!("{}Moving mapping: {}", Marker::Edit, mapping.id);
+    if let Some((partition, content)) = doc {
+        println!(
+            "{}Documentation: {} -> {}",
+            Marker::Doc,
+            mapping.doc_partition,
+            partition
+        );
+        mapping.doc_partition = partition;
+        mapping.rehash_doc(&content, hash_options);
+        mapping.touch();
+    }
+    if let Some((partition, content)) = code {
+        println!(
+            "{}Code: {} -> {}",
+            Marker::Code,
+            mapping.code_partition,
+            partition
+        );
+        mapping.code_partition = partition;
+        mapping.rehash_code(&content, mapping.code_hash_options(hash_options));
+        mapping.touch();
+    }
+
+    config.to_file(&doks_file_path)?;
+    println!("{}Mapping moved", Marker::Ok);
+
+    Ok(())
+}
@@ -1,23 +1,81 @@
 use anyhow::{anyhow, Result};
 use dialoguer::{Input, Select};
-use std::path::PathBuf;
+use glob::Pattern;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
 
 use crate::config::{DoksConfig, DOKS_FILE_NAME};
+use crate::output::Marker;
+
+/// Subdirectories scanned recursively for documentation files.
+const DOC_DIRS: [&str; 2] = ["docs", "doc"];
+
+/// Extensions of documentation files offered from the project root.
+const ROOT_DOC_EXTENSIONS: [&str; 3] = ["md", "mdx", "adoc"];
+
+/// Extensions of documentation files offered from `DOC_DIRS`.
+const DOC_DIR_EXTENSIONS: [&str; 4] = ["md", "mdx", "adoc", "rst"];
+
+pub fn handle(path: Option<PathBuf>, doc: Option<String>, doc_globs: Vec<String>) -> Result<()> {
+    let extra_patterns = doc_globs
+        .iter()
+        .map(|glob| Pattern::new(glob).map_err(|e| anyhow!("Invalid --doc-glob '{}': {}", glob, e)))
+        .collect::<Result<Vec<_>>>()?;
+
+    // `--file` names the file itself; partitions are relative to its directory
+    let (target_path, doks_file_path) = match super::doks_file_override() {
+        Some(file) => {
+            let target_path = match file.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+                _ => super::working_dir()?,
+            };
+            if file.exists() {
+                return Err(anyhow!("{} already exists", file.display()));
+            }
+            (target_path, file.clone())
+        }
+        None => {
+            let target_path = match path {
+                Some(path) => super::resolve_path(path),
+                None => super::working_dir()?,
+            };
+            let doks_file_path = target_path.join(DOKS_FILE_NAME);
+            if doks_file_path.exists() {
+                return Err(anyhow!("A .doks file already exists in this directory"));
+            }
+            (target_path, doks_file_path)
+        }
+    };
 
-pub fn handle(path: Option<PathBuf>) -> Result<()> {
-    let target_path = path.unwrap_or_else(|| std::env::current_dir().unwrap());
-    let doks_file_path = target_path.join(DOKS_FILE_NAME);
+    println!(
+        "{}Initializing new doksnet project in: {}",
+        Marker::Start,
+        target_path.display()
+    );
 
-    if doks_file_path.exists() {
-        return Err(anyhow!("A .doks file already exists in this directory"));
-    }
+    let default_doc = match doc {
+        Some(doc) => doc,
+        None => select_default_doc(&target_path, &extra_patterns)?,
+    };
+
+    let config = DoksConfig::new(default_doc.clone());
+    config.to_file(&doks_file_path)?;
 
     println!(
-        "🚀 Initializing new doksnet project in: {}",
-        target_path.display()
+        "{}Created .doks file with default documentation: {}",
+        Marker::Ok,
+        default_doc
+    );
+    println!(
+        "{}You can now use 'doksnet add' to create mappings between documentation and code",
+        Marker::Note
     );
 
-    let doc_files = find_documentation_files(&target_path)?;
+    Ok(())
+}
+
+fn select_default_doc(target_path: &PathBuf, extra_patterns: &[Pattern]) -> Result<String> {
+    let doc_files = find_documentation_files(target_path, extra_patterns)?;
 
     let default_doc = if doc_files.is_empty() {
         let input: String = Input::new()
@@ -27,10 +85,10 @@ pub fn handle(path: Option<PathBuf>) -> Result<()> {
         input
     } else if doc_files.len() == 1 {
         let doc_file = &doc_files[0];
-        println!("📄 Found documentation file: {}", doc_file);
+        println!("{}Found documentation file: {}", Marker::Doc, doc_file);
         doc_file.clone()
     } else {
-        println!("📚 Found multiple documentation files:");
+        println!("{}Found multiple documentation files:", Marker::Docs);
         let selection = Select::new()
             .with_prompt("Select the default documentation file")
             .items(&doc_files)
@@ -39,19 +97,12 @@ pub fn handle(path: Option<PathBuf>) -> Result<()> {
         doc_files[selection].clone()
     };
 
-    let config = DoksConfig::new(default_doc.clone());
-    config.to_file(&doks_file_path)?;
-
-    println!(
-        "✅ Created .doks file with default documentation: {}",
-        default_doc
-    );
-    println!("📝 You can now use 'doksnet add' to create mappings between documentation and code");
-
-    Ok(())
+    Ok(default_doc)
 }
 
-fn find_documentation_files(path: &PathBuf) -> Result<Vec<String>> {
+/// Documentation files in `path` and its `DOC_DIRS`, READMEs first. A file
+/// whose name matches one of `extra_patterns` counts as documentation too.
+fn find_documentation_files(path: &PathBuf, extra_patterns: &[Pattern]) -> Result<Vec<String>> {
     let mut doc_files = Vec::new();
 
     let doc_patterns = [
@@ -86,12 +137,29 @@ fn find_documentation_files(path: &PathBuf) -> Result<Vec<String>> {
                 }
             }
 
-            if file_name_str.ends_with(".md") && !doc_files.contains(&file_name_str.to_string()) {
+            let is_doc = has_extension(entry.path().as_path(), &ROOT_DOC_EXTENSIONS)
+                || matches_any(&file_name_str, extra_patterns);
+            if is_doc && !doc_files.contains(&file_name_str.to_string()) {
                 doc_files.push(file_name_str.to_string());
             }
         }
     }
 
+    for doc_dir in DOC_DIRS {
+        let doc_dir = path.join(doc_dir);
+        if !doc_dir.is_dir() {
+            continue;
+        }
+        for entry in WalkDir::new(&doc_dir).sort_by_file_name() {
+            let entry = entry?;
+            let is_doc = has_extension(entry.path(), &DOC_DIR_EXTENSIONS)
+                || matches_any(&entry.file_name().to_string_lossy(), extra_patterns);
+            if entry.file_type().is_file() && is_doc {
+                doc_files.push(relative_path(path, entry.path()));
+            }
+        }
+    }
+
     doc_files.sort_by(|a, b| {
         let a_is_readme = a.to_lowercase().starts_with("readme");
         let b_is_readme = b.to_lowercase().starts_with("readme");
@@ -105,3 +173,74 @@ fn find_documentation_files(path: &PathBuf) -> Result<Vec<String>> {
 
     Ok(doc_files)
 }
+
+fn has_extension(file: &Path, extensions: &[&str]) -> bool {
+    file.extension()
+        .is_some_and(|ext| extensions.iter().any(|e| ext == *e))
+}
+
+fn matches_any(file_name: &str, patterns: &[Pattern]) -> bool {
+    patterns.iter().any(|pattern| pattern.matches(file_name))
+}
+
+/// `file` relative to `root`, always with `/` separators as used in partitions.
+fn relative_path(root: &Path, file: &Path) -> String {
+    let relative = file.strip_prefix(root).unwrap_or(file);
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_find_documentation_files_in_docs_dir() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("README.md"), "# Readme").unwrap();
+        fs::create_dir_all(dir.path().join("docs/api")).unwrap();
+        fs::write(dir.path().join("docs/guide.md"), "# Guide").unwrap();
+        fs::write(dir.path().join("docs/api/index.rst"), "Index").unwrap();
+        fs::write(dir.path().join("docs/logo.png"), "").unwrap();
+        fs::create_dir(dir.path().join("doc")).unwrap();
+        fs::write(dir.path().join("doc/notes.md"), "# Notes").unwrap();
+
+        let doc_files = find_documentation_files(&dir.path().to_path_buf(), &[]).unwrap();
+
+        assert_eq!(doc_files[0], "README.md");
+        assert!(doc_files.contains(&"docs/guide.md".to_string()));
+        assert!(doc_files.contains(&"docs/api/index.rst".to_string()));
+        assert!(doc_files.contains(&"doc/notes.md".to_string()));
+        assert!(!doc_files.iter().any(|f| f.ends_with("logo.png")));
+    }
+
+    #[test]
+    fn test_find_documentation_files_with_extra_patterns() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("README.adoc"), "= Readme").unwrap();
+        fs::write(dir.path().join("intro.mdx"), "# Intro").unwrap();
+        fs::write(dir.path().join("notes.textile"), "h1. Notes").unwrap();
+        fs::create_dir(dir.path().join("docs")).unwrap();
+        fs::write(dir.path().join("docs/setup.textile"), "h1. Setup").unwrap();
+
+        let defaults = find_documentation_files(&dir.path().to_path_buf(), &[]).unwrap();
+        assert_eq!(defaults, vec!["README.adoc", "intro.mdx"]);
+
+        let patterns = [Pattern::new("*.textile").unwrap()];
+        let doc_files = find_documentation_files(&dir.path().to_path_buf(), &patterns).unwrap();
+        assert_eq!(
+            doc_files,
+            vec![
+                "README.adoc",
+                "docs/setup.textile",
+                "intro.mdx",
+                "notes.textile"
+            ]
+        );
+    }
+}
@@ -0,0 +1,51 @@
+use anyhow::{anyhow, Result};
+
+use crate::config::DoksConfig;
+use crate::output::Marker;
+
+pub fn handle(id: String) -> Result<()> {
+    let doks_file_path = super::find_doks_file()?;
+    let mut config = DoksConfig::from_file(&doks_file_path)?;
+
+    let matches: Vec<usize> = config
+        .mappings
+        .iter()
+        .enumerate()
+        .filter(|(_, m)| m.id.starts_with(&id))
+        .map(|(index, _)| index)
+        .collect();
+
+    let mapping_index = match matches.as_slice() {
+        [] => return Err(anyhow!("No mapping found with ID starting with '{}'", id)),
+        [index] => *index,
+        _ => {
+            let ids: Vec<&str> = matches
+                .iter()
+                .map(|&i| config.mappings[i].id.as_str())
+                .collect();
+            return Err(anyhow!(
+                "ID prefix '{}' is ambiguous, it matches {} mappings: {}",
+                id,
+                matches.len(),
+                ids.join(", ")
+            ));
+        }
+    };
+
+    super::undo::record_removal(
+        &doks_file_path,
+        &[(mapping_index, &config.mappings[mapping_index])],
+    )?;
+    let mapping = config.mappings.remove(mapping_index);
+    config.to_file(&doks_file_path)?;
+
+    println!("{}Removed mapping {}", Marker::Remove, mapping.id);
+    println!(
+        "{}Remaining mappings: {}",
+        Marker::Stats,
+        config.mappings.len()
+    );
+    println!("{}Run 'doksnet undo' to restore it", Marker::Tip);
+
+    Ok(())
+}
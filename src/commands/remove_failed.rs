@@ -1,32 +1,57 @@
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use dialoguer::Confirm;
+use std::path::Path;
 
 use crate::config::DoksConfig;
-use crate::hash::verify_hash;
+use crate::hash::{verify_hash, HashOptions};
+use crate::output::Marker;
 use crate::partition::Partition;
 
-pub fn handle() -> Result<()> {
-    let doks_file_path = DoksConfig::find_doks_file()
-        .ok_or_else(|| anyhow!("No .doks file found. Run 'doksnet new' first."))?;
+/// Removes every enabled mapping that fails verification, after listing them
+/// and asking for confirmation unless `yes` is set. With `count`, only the
+/// number of failed mappings is printed: nothing is removed unless `yes` is
+/// set as well.
+pub fn handle(yes: bool, count: bool) -> Result<()> {
+    let doks_file_path = super::find_doks_file()?;
 
     let mut config = DoksConfig::from_file(&doks_file_path)?;
 
-    if config.mappings.is_empty() {
-        println!("📭 No mappings found. Use 'doksnet add' to create some first.");
+    if config.mappings.is_empty() && !count {
+        println!(
+            "{}No mappings found. Use 'doksnet add' to create some first.",
+            Marker::Empty
+        );
         return Ok(());
     }
 
-    println!(
-        "🔍 Checking {} mappings for failures...",
-        config.mappings.len()
-    );
+    if !count {
+        println!(
+            "{}Checking {} mappings for failures...",
+            Marker::Search,
+            config.mappings.len()
+        );
+    }
 
     let mut failed_indices = Vec::new();
     let mut failed_details = Vec::new();
 
     for (index, mapping) in config.mappings.iter().enumerate() {
-        let doc_failed = !test_partition_validity(&mapping.doc_partition, &mapping.doc_hash);
-        let code_failed = !test_partition_validity(&mapping.code_partition, &mapping.code_hash);
+        // Disabled mappings are expected to drift until they're re-enabled
+        if !mapping.enabled {
+            continue;
+        }
+        let doc_failed = !test_partition_validity(
+            &config.root,
+            &mapping.doc_partition,
+            &mapping.doc_hash,
+            mapping.hash_options(config.hash_options),
+        );
+        let code_failed = !test_partition_validity(
+            &config.root,
+            &mapping.code_partition,
+            &mapping.code_hash,
+            mapping.code_hash_options(config.hash_options),
+        );
 
         if doc_failed || code_failed {
             let mut failure_reasons = Vec::new();
@@ -39,6 +64,7 @@ pub fn handle() -> Result<()> {
 
             failed_indices.push(index);
             failed_details.push((
+                mapping.short_id(),
                 mapping.id.clone(),
                 mapping.doc_partition.clone(),
                 mapping.code_partition.clone(),
@@ -48,62 +74,116 @@ pub fn handle() -> Result<()> {
         }
     }
 
+    if count {
+        if yes && !failed_indices.is_empty() {
+            remove_mappings(&doks_file_path, &mut config, &failed_indices)?;
+        }
+        println!("{}", failed_indices.len());
+        return Ok(());
+    }
+
     if failed_indices.is_empty() {
-        println!("✅ No failed mappings found! All mappings are up to date.");
+        println!(
+            "{}No failed mappings found! All mappings are up to date.",
+            Marker::Ok
+        );
         return Ok(());
     }
 
-    println!("\n🚨 Found {} failed mapping(s):", failed_indices.len());
-    for (id, doc_partition, code_partition, description, reasons) in &failed_details {
-        println!("   📍 ID: {} ({}...)", &id[..8], id);
-        println!("      📄 Doc: {}", doc_partition);
-        println!("      💻 Code: {}", code_partition);
+    println!(
+        "\n{}Found {} failed mapping(s):",
+        Marker::Alert,
+        failed_indices.len()
+    );
+    for (short_id, id, doc_partition, code_partition, description, reasons) in &failed_details {
+        println!("   {}ID: {} ({}...)", Marker::Pin, short_id, id);
+        println!("      {}Doc: {}", Marker::Doc, doc_partition);
+        println!("      {}Code: {}", Marker::Code, code_partition);
         if let Some(desc) = description {
-            println!("      📝 Description: {}", desc);
+            println!("      {}Description: {}", Marker::Note, desc);
         }
-        println!("      ❌ Failed: {}", reasons.join(", "));
+        println!("      {}Failed: {}", Marker::Fail, reasons.join(", "));
         println!();
     }
 
-    println!("💡 These mappings have content that no longer matches their stored hashes.");
+    println!(
+        "{}These mappings have content that no longer matches their stored hashes.",
+        Marker::Tip
+    );
 
-    let confirm = Confirm::new()
-        .with_prompt(format!(
-            "Remove all {} failed mapping(s)?",
-            failed_indices.len()
-        ))
-        .default(false)
-        .interact()?;
+    let confirm = yes
+        || Confirm::new()
+            .with_prompt(format!(
+                "Remove all {} failed mapping(s)?",
+                failed_indices.len()
+            ))
+            .default(false)
+            .interact()?;
 
     if confirm {
-        for &index in failed_indices.iter().rev() {
-            config.mappings.remove(index);
-        }
-
-        config.to_file(&doks_file_path)?;
+        remove_mappings(&doks_file_path, &mut config, &failed_indices)?;
 
         println!(
-            "✅ Successfully removed {} failed mapping(s)",
+            "{}Successfully removed {} failed mapping(s)",
+            Marker::Ok,
             failed_indices.len()
         );
-        println!("📊 Remaining mappings: {}", config.mappings.len());
+        println!("{}Run 'doksnet undo' to restore them", Marker::Tip);
+        println!(
+            "{}Remaining mappings: {}",
+            Marker::Stats,
+            config.mappings.len()
+        );
 
         if config.mappings.is_empty() {
-            println!("💡 No mappings remain. Use 'doksnet add' to create new ones.");
+            println!(
+                "{}No mappings remain. Use 'doksnet add' to create new ones.",
+                Marker::Tip
+            );
         }
     } else {
-        println!("❌ Removal cancelled. Failed mappings remain.");
-        println!("💡 Tip: Use 'doksnet edit <id>' to fix individual mappings");
-        println!("💡 Tip: Use 'doksnet test-interactive' for guided fixing");
+        println!("{}Removal cancelled. Failed mappings remain.", Marker::Fail);
+        println!(
+            "{}Tip: Use 'doksnet edit <id>' to fix individual mappings",
+            Marker::Tip
+        );
+        println!(
+            "{}Tip: Use 'doksnet test-interactive' for guided fixing",
+            Marker::Tip
+        );
     }
 
     Ok(())
 }
 
-fn test_partition_validity(partition_str: &str, expected_hash: &str) -> bool {
+/// Removes the mappings at `indices` (in ascending order) and saves `.doks`,
+/// recording them for `undo` first.
+fn remove_mappings(
+    doks_file_path: &Path,
+    config: &mut DoksConfig,
+    indices: &[usize],
+) -> Result<()> {
+    let removed: Vec<_> = indices
+        .iter()
+        .map(|&index| (index, &config.mappings[index]))
+        .collect();
+    super::undo::record_removal(doks_file_path, &removed)?;
+    for &index in indices.iter().rev() {
+        config.mappings.remove(index);
+    }
+    config.to_file(doks_file_path)?;
+    Ok(())
+}
+
+fn test_partition_validity(
+    root: &Path,
+    partition_str: &str,
+    expected_hash: &str,
+    hash_options: HashOptions,
+) -> bool {
     match Partition::parse(partition_str) {
-        Ok(partition) => match partition.extract_content() {
-            Ok(content) => verify_hash(&content, expected_hash),
+        Ok(partition) => match partition.extract_content_in(root) {
+            Ok(content) => verify_hash(&content, expected_hash, hash_options),
             Err(_) => false,
         },
         Err(_) => false,
@@ -0,0 +1,112 @@
+use anyhow::{anyhow, Result};
+use std::path::Path;
+
+use crate::config::DoksConfig;
+use crate::output::Marker;
+use crate::partition::{ContentCache, Partition};
+use crate::verify::verify_mapping;
+
+/// Points every partition into `from` at `to` instead, after the file was
+/// renamed, or renames it too with `move_file`. Both paths are relative to
+/// the directory containing `.doks`, like partition paths.
+pub fn handle(from: String, to: String, move_file: bool) -> Result<()> {
+    let doks_file_path = super::find_doks_file()?;
+    let mut config = DoksConfig::from_file(&doks_file_path)?;
+
+    let mut updated = Vec::new();
+    for (index, mapping) in config.mappings.iter_mut().enumerate() {
+        let doc = rename_in_partition(&mapping.doc_partition, &from, &to);
+        let code = rename_in_partition(&mapping.code_partition, &from, &to);
+        if doc.is_none() && code.is_none() {
+            continue;
+        }
+        if let Some(partition) = doc {
+            mapping.doc_partition = partition;
+        }
+        if let Some(partition) = code {
+            mapping.code_partition = partition;
+        }
+        mapping.touch();
+        updated.push(index);
+    }
+
+    if updated.is_empty() {
+        return Err(anyhow!("No partitions reference '{}'", from));
+    }
+
+    // Move the file before saving, so a failed move leaves .doks as it was
+    if move_file {
+        let source = config.root.join(&from);
+        let target = config.root.join(&to);
+        if target.exists() {
+            return Err(anyhow!("'{}' already exists", to));
+        }
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::rename(&source, &target)
+            .map_err(|e| anyhow!("Cannot move '{}' to '{}': {}", from, to, e))?;
+        println!("{}Moved {} -> {}", Marker::Ok, from, to);
+    }
+
+    config.to_file(&doks_file_path)?;
+    println!(
+        "{}Updated {} mapping(s) referencing {}",
+        Marker::Ok,
+        updated.len(),
+        from
+    );
+
+    let cache = ContentCache::new(&config.root);
+    let failing: Vec<_> = updated
+        .iter()
+        .map(|&index| verify_mapping(&config.mappings[index], config.hash_options, &cache))
+        .filter(|result| !result.passed)
+        .collect();
+    if !failing.is_empty() {
+        println!(
+            "\n{}{} updated mapping(s) don't match their stored hashes:",
+            Marker::Warn,
+            failing.len()
+        );
+        for result in &failing {
+            println!("   {}{}", Marker::Bullet, result.id);
+            for error in &result.errors {
+                println!("      {}", error);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The partition pointing at `to`, when it currently points at `from`.
+fn rename_in_partition(partition_str: &str, from: &str, to: &str) -> Option<String> {
+    let mut partition = Partition::parse(partition_str).ok()?;
+    if Path::new(&partition.file_path) != Path::new(from) {
+        return None;
+    }
+    partition.file_path = to.to_string();
+    Some(partition.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rename_in_partition() {
+        assert_eq!(
+            rename_in_partition("src/old.rs:10-20@5-30", "src/old.rs", "src/new.rs"),
+            Some("src/new.rs:10-20@5-30".to_string())
+        );
+        assert_eq!(
+            rename_in_partition("src/old.rs", "src/old.rs", "lib/new.rs"),
+            Some("lib/new.rs".to_string())
+        );
+        assert_eq!(
+            rename_in_partition("src/old.rs.bak:1", "src/old.rs", "src/new.rs"),
+            None
+        );
+    }
+}
@@ -0,0 +1,83 @@
+use anyhow::{anyhow, Result};
+use std::path::Path;
+
+use crate::config::DoksConfig;
+use crate::error::DoksError;
+use crate::hash::{check_hash, HashOptions};
+use crate::output::Marker;
+use crate::partition::Partition;
+
+use super::Preview;
+
+pub fn handle(id: String, context: usize) -> Result<()> {
+    let doks_file_path = super::find_doks_file()?;
+
+    let config = DoksConfig::from_file(&doks_file_path)?;
+
+    let mapping = config
+        .mappings
+        .iter()
+        .find(|m| m.id.starts_with(&id))
+        .ok_or_else(|| anyhow!("No mapping found with ID starting with '{}'", id))?;
+
+    println!("{}Mapping: {}", Marker::Inspect, mapping.id);
+    if let Some(desc) = &mapping.description {
+        println!("{}Description: {}", Marker::Note, desc);
+    } else {
+        println!("{}Description: (none)", Marker::Note);
+    }
+
+    println!("\n{}Documentation: {}", Marker::Doc, mapping.doc_partition);
+    show_partition(
+        &config.root,
+        &mapping.doc_partition,
+        &mapping.doc_hash,
+        mapping.hash_options(config.hash_options),
+        context,
+    );
+
+    println!("\n{}Code: {}", Marker::Code, mapping.code_partition);
+    show_partition(
+        &config.root,
+        &mapping.code_partition,
+        &mapping.code_hash,
+        mapping.code_hash_options(config.hash_options),
+        context,
+    );
+
+    Ok(())
+}
+
+fn show_partition(
+    root: &Path,
+    partition_str: &str,
+    expected_hash: &str,
+    hash_options: HashOptions,
+    context: usize,
+) {
+    let content = Partition::parse(partition_str).and_then(|p| p.extract_content_in(root));
+
+    match content {
+        Ok(content) => {
+            match check_hash(&content, expected_hash, hash_options) {
+                Ok(()) => println!("   {}Matches stored hash", Marker::Ok),
+                Err(e @ DoksError::MalformedHash(_)) => {
+                    println!("   {}The {}", Marker::Fail, e)
+                }
+                Err(_) => println!(
+                    "   {}Content has changed since the hash was stored",
+                    Marker::Fail
+                ),
+            }
+            println!("---");
+            let preview = Preview {
+                root,
+                chars: 0,
+                context,
+            };
+            println!("{}", preview.render(partition_str, &content));
+            println!("---");
+        }
+        Err(e) => println!("   {}Could not extract content: {}", Marker::Warn, e),
+    }
+}
@@ -0,0 +1,26 @@
+use anyhow::Result;
+
+use crate::config::DoksConfig;
+use crate::output::Marker;
+
+/// Turns on `sort_mappings` and rewrites `.doks` in canonical order, so
+/// later writes don't reorder the file depending on how mappings were added.
+pub fn handle() -> Result<()> {
+    let doks_file_path = super::find_doks_file()?;
+    let mut config = DoksConfig::from_file(&doks_file_path)?;
+
+    config.sort_mappings = true;
+    config.to_file(&doks_file_path)?;
+
+    println!(
+        "{}Sorted {} mapping(s) by documentation partition, then code partition",
+        Marker::Ok,
+        config.mappings.len()
+    );
+    println!(
+        "{}sort_mappings=true added to the header; later writes keep this order",
+        Marker::Pin
+    );
+
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use anyhow::Result;
+use std::collections::HashMap;
+
+use crate::config::{DoksConfig, Mapping};
+use crate::output::Marker;
+use crate::partition::Partition;
+
+/// Mappings listed under "Least recently updated".
+const OLDEST_SHOWN: usize = 5;
+
+pub fn handle() -> Result<()> {
+    let doks_file_path = super::find_doks_file()?;
+
+    let config = DoksConfig::from_file(&doks_file_path)?;
+
+    if config.mappings.is_empty() {
+        println!(
+            "{}No mappings found. Use 'doksnet add' to create some first.",
+            Marker::Empty
+        );
+        return Ok(());
+    }
+
+    let mut doc_files: HashMap<String, usize> = HashMap::new();
+    let mut code_files: HashMap<String, usize> = HashMap::new();
+    let mut tags: HashMap<String, usize> = HashMap::new();
+    let mut invalid = 0;
+
+    for mapping in &config.mappings {
+        for tag in &mapping.tags {
+            *tags.entry(tag.clone()).or_default() += 1;
+        }
+        for (partition_str, counts) in [
+            (&mapping.doc_partition, &mut doc_files),
+            (&mapping.code_partition, &mut code_files),
+        ] {
+            match Partition::parse(partition_str) {
+                Ok(partition) => *counts.entry(partition.file_path).or_default() += 1,
+                Err(_) => invalid += 1,
+            }
+        }
+    }
+
+    println!("{}Mapping Statistics:", Marker::Stats);
+    println!("   Total mappings: {}", config.mappings.len());
+
+    println!("\n{}Documentation files:", Marker::Doc);
+    print_counts(doc_files);
+
+    println!("\n{}Code files:", Marker::Code);
+    print_counts(code_files);
+
+    if !tags.is_empty() {
+        println!("\n{}Tags:", Marker::Pin);
+        print_counts(tags);
+    }
+
+    let oldest = oldest_mappings(&config.mappings, OLDEST_SHOWN);
+    if !oldest.is_empty() {
+        println!("\n{}Least recently updated:", Marker::List);
+        for mapping in oldest {
+            let timestamp = mapping.updated_at.as_ref().or(mapping.created_at.as_ref());
+            println!("   {}  {}", timestamp.unwrap(), mapping.id);
+        }
+    }
+
+    if invalid > 0 {
+        println!(
+            "\n{}Unparseable partitions: {} (run 'doksnet test' for details)",
+            Marker::Warn,
+            invalid
+        );
+    }
+
+    Ok(())
+}
+
+/// Up to `limit` mappings with timestamps, least recently updated first.
+fn oldest_mappings(mappings: &[Mapping], limit: usize) -> Vec<&Mapping> {
+    let mut dated: Vec<_> = mappings
+        .iter()
+        .filter_map(|mapping| mapping.last_updated().map(|time| (time, mapping)))
+        .collect();
+    dated.sort_by_key(|(time, _)| *time);
+    dated
+        .into_iter()
+        .take(limit)
+        .map(|(_, mapping)| mapping)
+        .collect()
+}
+
+/// Prints `count  name` rows, e.g. per file, most-referenced first.
+fn print_counts(counts: HashMap<String, usize>) {
+    let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+    counts.sort_by(|(a_file, a_count), (b_file, b_count)| {
+        b_count.cmp(a_count).then_with(|| a_file.cmp(b_file))
+    });
+
+    let width = counts
+        .first()
+        .map(|(_, count)| count.to_string().len())
+        .unwrap_or(1);
+    for (file, count) in counts {
+        println!("   {:>width$}  {}", count, file, width = width);
+    }
+}
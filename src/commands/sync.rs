@@ -0,0 +1,148 @@
+use anyhow::{anyhow, Result};
+use std::path::Path;
+use std::process;
+
+use crate::config::DoksConfig;
+use crate::output::Marker;
+use crate::partition::Partition;
+
+pub fn handle(check: bool) -> Result<()> {
+    let doks_file_path = super::find_doks_file()?;
+
+    let mut config = DoksConfig::from_file(&doks_file_path)?;
+
+    if config.mappings.is_empty() {
+        println!(
+            "{}No mappings found. Use 'doksnet add' to create some first.",
+            Marker::Empty
+        );
+        return Ok(());
+    }
+
+    if check {
+        println!(
+            "{}Checking {} mappings for drift (nothing will be written)",
+            Marker::Search,
+            config.mappings.len()
+        );
+    } else {
+        println!(
+            "{}Re-hashing {} mappings",
+            Marker::Sync,
+            config.mappings.len()
+        );
+    }
+
+    let defaults = config.hash_options;
+    let root = config.root.clone();
+    let mut changed_hashes = 0;
+    let mut updated_mappings = 0;
+    let mut recorded_line_digests = false;
+    let mut skipped = Vec::new();
+    let mut disabled = 0;
+
+    for mapping in config.mappings.iter_mut() {
+        // Keep the hashes of disabled mappings for when they're re-enabled
+        if !mapping.enabled {
+            disabled += 1;
+            continue;
+        }
+        let doc_content = extract(&root, &mapping.doc_partition, "documentation");
+        let code_content = extract(&root, &mapping.code_partition, "code");
+
+        let (doc_content, code_content) = match (doc_content, code_content) {
+            (Ok(doc), Ok(code)) => (doc, code),
+            (doc, code) => {
+                let errors: Vec<String> = [doc.err(), code.err()]
+                    .into_iter()
+                    .flatten()
+                    .map(|e| e.to_string())
+                    .collect();
+                skipped.push((mapping.id.clone(), errors));
+                continue;
+            }
+        };
+
+        let hash_options = mapping.hash_options(defaults);
+        let previous = mapping.clone();
+        mapping.rehash_doc(&doc_content, hash_options);
+        mapping.rehash_code(&code_content, mapping.code_hash_options(defaults));
+
+        let mut changed = false;
+        for (old, new) in [
+            (&previous.doc_hash, &mapping.doc_hash),
+            (&previous.code_hash, &mapping.code_hash),
+        ] {
+            if old != new {
+                changed_hashes += 1;
+                changed = true;
+            }
+        }
+        // Mappings written before line digests existed pick them up here
+        if previous.doc_lines != mapping.doc_lines || previous.code_lines != mapping.code_lines {
+            recorded_line_digests = true;
+        }
+        if changed {
+            updated_mappings += 1;
+            let action = if check { "Would update" } else { "Updated" };
+            println!("   {}{}: {}", Marker::Rehash, action, mapping.id);
+        }
+    }
+
+    if (updated_mappings > 0 || recorded_line_digests) && !check {
+        config.to_file(&doks_file_path)?;
+    }
+
+    println!();
+    println!("{}Sync Summary:", Marker::Stats);
+    println!(
+        "   {}Hashes changed: {} (across {} mapping(s))",
+        Marker::Rehash,
+        changed_hashes,
+        updated_mappings
+    );
+    println!(
+        "   {}Unchanged: {}",
+        Marker::Ok,
+        config.mappings.len() - updated_mappings - skipped.len() - disabled
+    );
+    if disabled > 0 {
+        println!("   {}Disabled: {}", Marker::Skip, disabled);
+    }
+
+    if !skipped.is_empty() {
+        println!("   {}Skipped: {}", Marker::Warn, skipped.len());
+        println!(
+            "\n{}Skipped Mappings (could not extract content):",
+            Marker::Alert
+        );
+        for (id, errors) in &skipped {
+            println!("   {}", id);
+            for error in errors {
+                println!("      {}{}", Marker::Bullet, error);
+            }
+        }
+        println!(
+            "\n{}Tip: Use 'doksnet edit <id>' to fix broken partitions",
+            Marker::Tip
+        );
+    }
+
+    if check && updated_mappings > 0 {
+        println!(
+            "\n{}Run 'doksnet sync' to accept these changes",
+            Marker::Tip
+        );
+        process::exit(1);
+    }
+
+    Ok(())
+}
+
+fn extract(root: &Path, partition_str: &str, content_type: &str) -> Result<String> {
+    let partition = Partition::parse(partition_str)
+        .map_err(|e| anyhow!("Failed to parse {} partition: {}", content_type, e))?;
+    partition
+        .extract_content_in(root)
+        .map_err(|e| anyhow!("Failed to extract {} content: {}", content_type, e))
+}
@@ -1,128 +1,1438 @@
 use anyhow::{anyhow, Result};
-use std::process;
+use indicatif::ProgressBar;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::io::IsTerminal;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
 
-use crate::config::DoksConfig;
-use crate::hash::{hash_content, verify_hash};
-use crate::partition::Partition;
+use crate::cli::{GroupBy, TestArgs, TestFormat};
+use crate::commands::check;
+use crate::config::{parse_tags, DoksConfig, Mapping};
+use crate::error::DoksError;
+use crate::hash::{check_hash, hash_content};
+use crate::output::{unified_diff, Marker};
+use crate::partition::{ContentCache, Partition};
+use crate::verify::{
+    verify_all, verify_all_with_progress, verify_mapping, verify_mapping_in, MappingResult,
+    SideCaches,
+};
 
-pub fn handle() -> Result<()> {
-    let doks_file_path = DoksConfig::find_doks_file()
-        .ok_or_else(|| anyhow!("No .doks file found. Run 'doksnet new' first."))?;
+/// Whether every mapping verified; `main` turns `Failed` into exit code 1
+/// and `Invalid` into exit code 2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Passed,
+    Failed,
+    /// `.doks` is missing or unreadable, or a mapping in it can't be checked,
+    /// e.g. its partition doesn't parse or its file is missing.
+    Invalid,
+}
+
+/// How mappings whose documentation and code content are identical, i.e.
+/// code pasted verbatim into the docs, are treated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdenticalCheck {
+    Off,
+    Warn,
+    /// Report them as failures.
+    Deny,
+}
+
+impl IdenticalCheck {
+    /// The check selected by `--warn-identical` and `--deny-identical`.
+    pub fn from_flags(warn: bool, deny: bool) -> Self {
+        match (warn, deny) {
+            (_, true) => IdenticalCheck::Deny,
+            (true, false) => IdenticalCheck::Warn,
+            (false, false) => IdenticalCheck::Off,
+        }
+    }
+}
+
+const IDENTICAL_CONTENT: &str = "Documentation and code content are identical";
+
+/// How `run` checks the mappings and reports on them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunOptions {
+    pub format: TestFormat,
+    /// Only print the summary and failure details.
+    pub quiet: bool,
+    /// Add the current content of failing partitions to the text report.
+    pub verbose: bool,
+    /// Stop at the first failing mapping.
+    pub fail_fast: bool,
+    pub identical: IdenticalCheck,
+    /// Section the text report's failure details, e.g. by file.
+    pub group_by: Option<GroupBy>,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        Self {
+            format: TestFormat::Text,
+            quiet: false,
+            verbose: false,
+            fail_fast: false,
+            identical: IdenticalCheck::Off,
+            group_by: None,
+        }
+    }
+}
+
+impl RunOptions {
+    /// The options selected by `test`'s flags.
+    pub fn from_args(args: &TestArgs) -> Self {
+        Self {
+            format: args.format,
+            quiet: args.quiet,
+            verbose: args.verbose,
+            fail_fast: args.fail_fast,
+            identical: IdenticalCheck::from_flags(args.warn_identical, args.deny_identical),
+            group_by: args.group_by,
+        }
+    }
+}
+
+/// Mappings left out of a run, counted in the summary.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Skipped {
+    /// Matched an `--exclude` prefix.
+    pub excluded: usize,
+    /// Disabled in `.doks`.
+    pub disabled: usize,
+}
+
+impl Skipped {
+    fn total(&self) -> usize {
+        self.excluded + self.disabled
+    }
+}
+
+/// Runs with more mappings than this show a progress bar while verifying.
+const PROGRESS_THRESHOLD: usize = 50;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Stands in for the `.doks` file name when it's read with `--stdin`.
+const STDIN_NAME: &str = "<stdin>";
+
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    results: Vec<JsonResult<'a>>,
+    summary: JsonSummary,
+}
+
+#[derive(Serialize)]
+struct JsonResult<'a> {
+    id: &'a str,
+    doc_partition: &'a str,
+    code_partition: &'a str,
+    status: &'static str,
+    errors: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct JsonSummary {
+    total: usize,
+    passed: usize,
+    failed: usize,
+    errors: usize,
+    skipped: usize,
+    #[serde(skip_serializing_if = "is_zero")]
+    disabled: usize,
+}
+
+fn is_zero(count: &usize) -> bool {
+    *count == 0
+}
+
+pub fn handle(args: &TestArgs) -> Result<Outcome> {
+    let Some((mut config, doks_file_path, skipped)) = load_config(args)? else {
+        // Keep stdout parseable for the machine-readable formats
+        let notice = format!("{}No .doks file found, nothing to verify", Marker::Info);
+        match args.format {
+            TestFormat::Text => println!("{}", notice),
+            TestFormat::Json
+            | TestFormat::Github
+            | TestFormat::Sarif
+            | TestFormat::Junit
+            | TestFormat::Markdown => {
+                eprintln!("{}", notice)
+            }
+        }
+        return Ok(Outcome::Passed);
+    };
+    if args.repair {
+        repair_moved_files(&mut config, &doks_file_path, args.format)?;
+    }
+
+    run(
+        &config,
+        &doks_file_path,
+        RunOptions::from_args(args),
+        skipped,
+    )
+}
+
+/// For `--repair`: points each partition whose file is missing at the one
+/// file elsewhere in the tree with the same name, and saves that to `.doks`
+/// when the mapping then passes. Nothing else is repaired, so a mapping whose
+/// content drifted as well keeps failing.
+fn repair_moved_files(
+    config: &mut DoksConfig,
+    doks_file_path: &Path,
+    format: TestFormat,
+) -> Result<()> {
+    let cache = ContentCache::new(&config.root);
+    let mut repairs = Vec::new();
+    for (index, mapping) in config.mappings.iter().enumerate() {
+        let doc = moved_partition(&config.root, &mapping.doc_partition);
+        let code = moved_partition(&config.root, &mapping.code_partition);
+        if doc.is_none() && code.is_none() {
+            continue;
+        }
+        let mut repaired = mapping.clone();
+        repaired.doc_partition = doc.unwrap_or(repaired.doc_partition);
+        repaired.code_partition = code.unwrap_or(repaired.code_partition);
+        if verify_mapping(&repaired, config.hash_options, &cache).passed {
+            repaired.touch();
+            repairs.push((index, repaired));
+        }
+    }
+    if repairs.is_empty() {
+        return Ok(());
+    }
+
+    // Save into the whole file, not just the mappings selected for this run
+    let mut saved = DoksConfig::from_file(doks_file_path)?;
+    for (index, repaired) in repairs {
+        let original = &config.mappings[index];
+        for (from, to) in [
+            (&original.doc_partition, &repaired.doc_partition),
+            (&original.code_partition, &repaired.code_partition),
+        ] {
+            if from != to {
+                let report = format!(
+                    "{}Repaired {}: {} -> {}",
+                    Marker::Fix,
+                    repaired.short_id(),
+                    from,
+                    to
+                );
+                match format {
+                    TestFormat::Text => println!("{}", report),
+                    _ => eprintln!("{}", report),
+                }
+            }
+        }
+        if let Some(mapping) = saved.mappings.iter_mut().find(|m| m.id == repaired.id) {
+            mapping.doc_partition = repaired.doc_partition.clone();
+            mapping.code_partition = repaired.code_partition.clone();
+            mapping.updated_at = repaired.updated_at.clone();
+        }
+        config.mappings[index] = repaired;
+    }
+    saved.to_file(doks_file_path)?;
+    Ok(())
+}
+
+/// `partition_str` pointing at the file under `root` with the same name as
+/// its missing one, when there's exactly one, e.g. after it was moved to
+/// another directory.
+fn moved_partition(root: &Path, partition_str: &str) -> Option<String> {
+    let mut partition = Partition::parse(partition_str).ok()?;
+    let Err(DoksError::FileNotFound { path, suggestions }) = partition.locate_in(root) else {
+        return None;
+    };
+    // Suggestions put files with the same name first, so any second one
+    // with that name is among them too
+    let name = Path::new(&path).file_name()?;
+    let mut same_name = suggestions
+        .into_iter()
+        .filter(|suggestion| Path::new(suggestion).file_name() == Some(name));
+    let moved = same_name.next()?;
+    if same_name.next().is_some() {
+        return None;
+    }
+    partition.file_path = moved;
+    Some(partition.to_string())
+}
+
+/// Verifies every mapping without printing anything, telling content drift
+/// (`Failed`) apart from a broken configuration or a mapping that can't be
+/// checked (`Invalid`). With `--allow-missing`, a missing `.doks` passes.
+pub fn status(args: &TestArgs) -> Outcome {
+    let config = match load_config(args) {
+        Ok(Some((config, _, _))) => config,
+        Ok(None) => return Outcome::Passed,
+        Err(_) => return Outcome::Invalid,
+    };
+
+    let malformed = config.mappings.iter().any(|m| {
+        Partition::parse(&m.doc_partition).is_err() || Partition::parse(&m.code_partition).is_err()
+    });
+    if malformed {
+        return Outcome::Invalid;
+    }
+
+    let results = if args.fail_fast {
+        check_until_failure(&config, &ProgressBar::hidden())
+    } else {
+        verify_all(&config, &config.root)
+    };
+    if results.iter().any(|result| result.broken) {
+        Outcome::Invalid
+    } else if results.iter().all(|result| result.passed) {
+        Outcome::Passed
+    } else {
+        Outcome::Failed
+    }
+}
+
+/// Loads `.doks`, or reads it from stdin with `--stdin`, narrowed to the
+/// run's `--only` and `--tag` selection, with disabled mappings and
+/// `--exclude` left out and `--doc-base` and `--code-base` applied, along
+/// with where it was read from and what was left out of the selection.
+/// `None` means there is no `.doks` and `--allow-missing` was given.
+fn load_config(args: &TestArgs) -> Result<Option<(DoksConfig, PathBuf, Skipped)>> {
+    let (mut config, doks_file_path) = if args.stdin {
+        read_stdin_config()?
+    } else {
+        match find_config(args)? {
+            Some(found) => found,
+            None => return Ok(None),
+        }
+    };
+    config.doc_base = resolve_base(&args.doc_base, "--doc-base")?;
+    config.code_base = resolve_base(&args.code_base, "--code-base")?;
+    config.clamp_ranges = args.clamp;
+    config.parallelism = args.parallel.map(NonZeroUsize::get);
+    select_mappings(&mut config, &args.only)?;
+    select_tagged(&mut config, &parse_tags(&args.tags.join(","))?)?;
+    let skipped = Skipped {
+        disabled: drop_disabled(&mut config),
+        excluded: exclude_mappings(&mut config, &args.exclude),
+    };
+    Ok(Some((config, doks_file_path, skipped)))
+}
+
+/// Parses `.doks` content piped to stdin, with partitions resolved from the
+/// working directory. The path returned stands in for the file in reports.
+fn read_stdin_config() -> Result<(DoksConfig, PathBuf)> {
+    if let Some(path) = super::doks_file_override() {
+        return Err(anyhow!(
+            "--stdin can't be combined with --file {}",
+            path.display()
+        ));
+    }
+    let content = std::io::read_to_string(std::io::stdin())
+        .map_err(|e| anyhow!("Cannot read .doks from stdin: {}", e))?;
+    let mut config =
+        DoksConfig::parse(&content).map_err(|e| anyhow!("Invalid .doks on stdin: {}", e))?;
+    config.root = super::working_dir()?;
+    let doks_file_path = config.root.join(STDIN_NAME);
+    Ok((config, doks_file_path))
+}
+
+/// The `.doks` file found the usual way, or `None` when there is none and
+/// `--allow-missing` was given.
+fn find_config(args: &TestArgs) -> Result<Option<(DoksConfig, PathBuf)>> {
+    let doks_file_path = if args.allow_missing {
+        match super::locate_doks_file()? {
+            Some(path) => path,
+            None => return Ok(None),
+        }
+    } else {
+        super::find_doks_file()?
+    };
 
     let config = DoksConfig::from_file(&doks_file_path)?;
+    Ok(Some((config, doks_file_path)))
+}
 
-    if config.mappings.is_empty() {
-        println!("📭 No mappings found. Use 'doksnet add' to create some first.");
+fn resolve_base(dir: &Option<PathBuf>, flag: &str) -> Result<Option<PathBuf>> {
+    let Some(dir) = dir else {
+        return Ok(None);
+    };
+    let dir = super::resolve_path(dir.clone());
+    if !dir.is_dir() {
+        return Err(anyhow!("{} {} is not a directory", flag, dir.display()));
+    }
+    Ok(Some(dir))
+}
+
+/// Keeps only the mappings whose id starts with one of `prefixes`, matching
+/// ids like `edit` does. Every prefix has to match a mapping. An empty
+/// `prefixes` keeps everything.
+fn select_mappings(config: &mut DoksConfig, prefixes: &[String]) -> Result<()> {
+    if prefixes.is_empty() {
         return Ok(());
     }
+    if let Some(unmatched) = prefixes
+        .iter()
+        .find(|prefix| !config.mappings.iter().any(|m| m.id.starts_with(*prefix)))
+    {
+        return Err(anyhow!(
+            "No mapping found with ID starting with '{}'",
+            unmatched
+        ));
+    }
+    config
+        .mappings
+        .retain(|mapping| prefixes.iter().any(|prefix| mapping.id.starts_with(prefix)));
+    Ok(())
+}
+
+/// Keeps only the mappings carrying at least one of `tags`, like `--only`
+/// does for ids. Every tag has to be on some mapping. An empty `tags` keeps
+/// everything.
+fn select_tagged(config: &mut DoksConfig, tags: &[String]) -> Result<()> {
+    if tags.is_empty() {
+        return Ok(());
+    }
+    if let Some(unmatched) = tags
+        .iter()
+        .find(|tag| !config.mappings.iter().any(|m| m.tags.contains(tag)))
+    {
+        return Err(anyhow!("No mapping is tagged '{}'", unmatched));
+    }
+    config
+        .mappings
+        .retain(|mapping| tags.iter().any(|tag| mapping.tags.contains(tag)));
+    Ok(())
+}
+
+/// Drops disabled mappings, returning how many were dropped.
+pub fn drop_disabled(config: &mut DoksConfig) -> usize {
+    let before = config.mappings.len();
+    config.mappings.retain(|mapping| mapping.enabled);
+    before - config.mappings.len()
+}
+
+/// Drops the mappings whose id starts with any of `prefixes`, returning how
+/// many were dropped.
+fn exclude_mappings(config: &mut DoksConfig, prefixes: &[String]) -> usize {
+    let before = config.mappings.len();
+    config
+        .mappings
+        .retain(|mapping| !prefixes.iter().any(|prefix| mapping.id.starts_with(prefix)));
+    before - config.mappings.len()
+}
+
+/// Verifies and reports on every mapping in `config`, loaded from
+/// `doks_file_path`, as `options` say. `skipped` counts the mappings left
+/// out of `config`, reported in the summary.
+pub fn run(
+    config: &DoksConfig,
+    doks_file_path: &Path,
+    options: RunOptions,
+    skipped: Skipped,
+) -> Result<Outcome> {
+    let progress = if should_show_progress(
+        options.format,
+        options.quiet,
+        config.mappings.len(),
+        std::io::stdout().is_terminal(),
+    ) {
+        ProgressBar::new(config.mappings.len() as u64)
+    } else {
+        ProgressBar::hidden()
+    };
+    let mut results = if options.fail_fast {
+        check_until_failure(config, &progress)
+    } else {
+        verify_all_with_progress(config, &config.root, |_| progress.inc(1))
+    };
+    progress.finish_and_clear();
+
+    let mut warned = Vec::new();
+    if options.identical != IdenticalCheck::Off {
+        for index in find_identical(config, results.len()) {
+            if options.identical == IdenticalCheck::Deny {
+                results[index].passed = false;
+                results[index].errors.push(IDENTICAL_CONTENT.to_string());
+            } else {
+                warned.push(index);
+            }
+        }
+    }
 
-    println!(
-        "🧪 Testing {} documentation-code mappings",
-        config.mappings.len()
-    );
-    println!("📄 Default documentation file: {}", config.default_doc);
-    println!();
+    match options.format {
+        TestFormat::Text => run_text(config, results, &warned, options, skipped),
+        TestFormat::Json => run_json(config, results, &warned, skipped),
+        TestFormat::Github => run_github(config, doks_file_path, results, &warned),
+        TestFormat::Sarif => run_sarif(config, doks_file_path, results, &warned),
+        TestFormat::Junit => run_junit(config, results, &warned),
+        TestFormat::Markdown => run_markdown(config, results, &warned, skipped),
+    }
+}
 
-    let mut failed_mappings = Vec::new();
+fn run_text(
+    config: &DoksConfig,
+    results: Vec<MappingResult>,
+    warned: &[usize],
+    options: RunOptions,
+    skipped: Skipped,
+) -> Result<Outcome> {
+    let RunOptions {
+        quiet,
+        verbose,
+        group_by,
+        ..
+    } = options;
+    if config.mappings.is_empty() {
+        if skipped.total() > 0 {
+            println!(
+                "{}All {} mappings were excluded or disabled",
+                Marker::Skip,
+                skipped.total()
+            );
+        } else {
+            println!(
+                "{}No mappings found. Use 'doksnet add' to create some first.",
+                Marker::Empty
+            );
+        }
+        return Ok(Outcome::Passed);
+    }
+
+    if !quiet {
+        println!(
+            "{}Testing {} documentation-code mappings",
+            Marker::Test,
+            config.mappings.len()
+        );
+        println!(
+            "{}Default documentation file: {}",
+            Marker::Doc,
+            config.default_doc
+        );
+        println!();
+    }
+
+    let mut failed_mappings: Vec<Failure> = Vec::new();
+    let mut clamped_mappings = Vec::new();
     let mut success_count = 0;
+    let mut broken_count = 0;
+    let checked = results.len();
 
-    for (index, mapping) in config.mappings.iter().enumerate() {
+    for (index, (mapping, result)) in config.mappings.iter().zip(results).enumerate() {
         let mapping_num = index + 1;
+        let passed = result.errors.is_empty();
+        let broken = result.broken;
+        let error_details = result.errors;
+        if !result.warnings.is_empty() {
+            clamped_mappings.push((mapping, result.warnings));
+        }
+
+        if passed {
+            success_count += 1;
+        } else {
+            if broken {
+                broken_count += 1;
+            }
+            failed_mappings.push((mapping_num, mapping, error_details));
+        }
+
+        if quiet {
+            continue;
+        }
+
         println!(
-            "🔍 Testing mapping {}/{}: {}",
+            "{}Testing mapping {}/{}: {}",
+            Marker::Search,
             mapping_num,
             config.mappings.len(),
             mapping.id
         );
 
         if let Some(desc) = &mapping.description {
-            println!("   📝 Description: {}", desc);
+            println!("   {}Description: {}", Marker::Note, desc);
         }
 
-        println!("   📄 Doc: {}", mapping.doc_partition);
-        println!("   💻 Code: {}", mapping.code_partition);
-
-        let doc_result = test_partition(&mapping.doc_partition, &mapping.doc_hash, "documentation");
+        println!("   {}Doc: {}", Marker::Doc, mapping.doc_partition);
+        println!("   {}Code: {}", Marker::Code, mapping.code_partition);
 
-        let code_result = test_partition(&mapping.code_partition, &mapping.code_hash, "code");
-
-        match (doc_result, code_result) {
-            (Ok(()), Ok(())) => {
-                println!("   ✅ PASS");
-                success_count += 1;
-            }
-            (doc_err, code_err) => {
-                println!("   ❌ FAIL");
-
-                let mut error_details = Vec::new();
-                if let Err(e) = doc_err {
-                    error_details.push(format!("Documentation: {}", e));
-                }
-                if let Err(e) = code_err {
-                    error_details.push(format!("Code: {}", e));
-                }
-
-                failed_mappings.push((mapping_num, mapping.id.clone(), error_details));
-            }
+        if passed {
+            println!("   {}PASS", Marker::Ok);
+        } else if broken {
+            println!("   {}ERROR", Marker::Warn);
+        } else {
+            println!("   {}FAIL", Marker::Fail);
         }
 
         println!();
     }
 
-    println!("📊 Test Results Summary:");
+    if checked < config.mappings.len() {
+        println!(
+            "{}Stopped at the first failure ({}/{} mappings checked)",
+            Marker::Warn,
+            checked,
+            config.mappings.len()
+        );
+    }
+
+    println!("{}Test Results Summary:", Marker::Stats);
     if success_count > 0 {
-        println!("   ✅ Passed: {}/{}", success_count, config.mappings.len());
+        println!(
+            "   {}Passed: {}/{}",
+            Marker::Ok,
+            success_count,
+            config.mappings.len()
+        );
     }
-    if !failed_mappings.is_empty() {
+    if failed_mappings.len() > broken_count {
         println!(
-            "   ❌ Failed: {}/{}",
-            failed_mappings.len(),
+            "   {}Failed: {}/{}",
+            Marker::Fail,
+            failed_mappings.len() - broken_count,
             config.mappings.len()
         );
     }
+    if broken_count > 0 {
+        println!(
+            "   {}Errors: {}/{}",
+            Marker::Warn,
+            broken_count,
+            config.mappings.len()
+        );
+    }
+    if skipped.excluded > 0 {
+        println!(
+            "   {}Skipped: {} (excluded)",
+            Marker::Skip,
+            skipped.excluded
+        );
+    }
+    if skipped.disabled > 0 {
+        println!(
+            "   {}Skipped: {} (disabled)",
+            Marker::Skip,
+            skipped.disabled
+        );
+    }
+
+    if !clamped_mappings.is_empty() {
+        println!(
+            "\n{}Line ranges clamped to the end of their file:",
+            Marker::Warn
+        );
+        for (mapping, warnings) in clamped_mappings {
+            println!("   {}{}", Marker::Bullet, mapping.id);
+            for warning in warnings {
+                println!("      {}", warning);
+            }
+        }
+    }
+
+    if !warned.is_empty() {
+        println!("\n{}{}:", Marker::Warn, IDENTICAL_CONTENT);
+        for &index in warned {
+            let mapping = &config.mappings[index];
+            println!(
+                "   {}{} ({} = {})",
+                Marker::Bullet,
+                mapping.id,
+                mapping.doc_partition,
+                mapping.code_partition
+            );
+        }
+    }
 
     if !failed_mappings.is_empty() {
-        println!("\n🚨 Failed Mappings Details:");
-        for (mapping_num, id, errors) in failed_mappings {
-            println!("   {}. {} (ID: {})", mapping_num, id, &id[..8]);
+        println!("\n{}Failed Mappings Details:", Marker::Alert);
+        let caches = SideCaches::for_config(config);
+        let print_failure = |indent: &str, (mapping_num, mapping, errors): Failure| {
+            println!(
+                "{}{}. {} (ID: {})",
+                indent,
+                mapping_num,
+                mapping.id,
+                mapping.short_id()
+            );
             for error in errors {
-                println!("      • {}", error);
+                println!("{}   {}{}", indent, Marker::Bullet, error);
             }
+            if verbose {
+                print_changed_content(config, mapping, &caches, indent);
+            }
+        };
+        match group_by {
+            None => failed_mappings
+                .into_iter()
+                .for_each(|failure| print_failure("   ", failure)),
+            Some(GroupBy::File) => {
+                for (file, failures) in group_by_file(failed_mappings) {
+                    println!("   {}{} ({} failing)", Marker::Pin, file, failures.len());
+                    for failure in failures {
+                        print_failure("      ", failure);
+                    }
+                }
+            }
+        }
+
+        if !quiet {
+            println!(
+                "\n{}Tip: Use 'doksnet edit <id>' to fix broken mappings",
+                Marker::Tip
+            );
         }
 
-        println!("\n💡 Tip: Use 'doksnet edit <id>' to fix broken mappings");
+        return Ok(Outcome::Failed);
+    }
+
+    if !quiet {
+        println!("\n{}All mappings are up to date!", Marker::Celebrate);
+    }
+
+    Ok(Outcome::Passed)
+}
+
+/// A failing mapping in the text report: its 1-based position, the mapping
+/// and its errors.
+type Failure<'a> = (usize, &'a Mapping, Vec<String>);
 
-        process::exit(1);
+/// Sorts failures under the file of their first failing partition, the
+/// documentation side unless only the code side failed. Files are in path
+/// order, failures in `.doks` order.
+fn group_by_file(failures: Vec<Failure>) -> BTreeMap<String, Vec<Failure>> {
+    let mut groups: BTreeMap<String, Vec<Failure>> = BTreeMap::new();
+    for failure in failures {
+        let (_, mapping, errors) = &failure;
+        let code_only = errors.iter().all(|error| error.starts_with("Code: "));
+        let partition_str = if code_only {
+            &mapping.code_partition
+        } else {
+            &mapping.doc_partition
+        };
+        let file = Partition::parse(partition_str)
+            .map(|partition| partition.file_path)
+            .unwrap_or_else(|_| partition_str.clone());
+        groups.entry(file).or_default().push(failure);
+    }
+    groups
+}
+
+fn run_json(
+    config: &DoksConfig,
+    results: Vec<MappingResult>,
+    warned: &[usize],
+    skipped: Skipped,
+) -> Result<Outcome> {
+    let results: Vec<JsonResult> = config
+        .mappings
+        .iter()
+        .zip(results)
+        .enumerate()
+        .map(|(index, (mapping, result))| {
+            let status = status_of(&result);
+            let mut warnings = result.warnings;
+            if warned.contains(&index) {
+                warnings.push(IDENTICAL_CONTENT.to_string());
+            }
+            JsonResult {
+                id: &mapping.id,
+                doc_partition: &mapping.doc_partition,
+                code_partition: &mapping.code_partition,
+                status,
+                errors: result.errors,
+                warnings,
+            }
+        })
+        .collect();
+
+    let count = |status| results.iter().filter(|r| r.status == status).count();
+    let (passed, failed, errors) = (count("pass"), count("fail"), count("error"));
+    let report = JsonReport {
+        summary: JsonSummary {
+            total: results.len(),
+            passed,
+            failed,
+            errors,
+            skipped: skipped.total(),
+            disabled: skipped.disabled,
+        },
+        results,
+    };
+
+    println!("{}", serde_json::to_string_pretty(&report)?);
+
+    Ok(if passed < report.results.len() {
+        Outcome::Failed
     } else {
-        println!("\n🎉 All mappings are up to date!");
+        Outcome::Passed
+    })
+}
+
+/// `pass`, `fail` for content that drifted, or `error` for a mapping that
+/// couldn't be checked, e.g. because a file is missing.
+fn status_of(result: &MappingResult) -> &'static str {
+    if result.errors.is_empty() {
+        "pass"
+    } else if result.broken {
+        "error"
+    } else {
+        "fail"
     }
+}
 
-    Ok(())
+/// A problem found by the run, located at a line of a file, for the formats
+/// that annotate files.
+struct Annotation {
+    level: &'static str,
+    rule: Rule,
+    /// Relative to the working directory when it's inside it.
+    path: String,
+    line: usize,
+    message: String,
 }
 
-fn test_partition(partition_str: &str, expected_hash: &str, content_type: &str) -> Result<()> {
-    let partition = Partition::parse(partition_str).map_err(|e| {
-        anyhow!(
-            "Failed to parse {} partition '{}': {}",
-            content_type,
-            partition_str,
-            e
-        )
-    })?;
+/// The kinds of problem reported by the annotating formats, as SARIF rules.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Rule {
+    ContentDrift,
+    BrokenPartition,
+    IdenticalContent,
+    ClampedRange,
+}
 
-    let content = partition
-        .extract_content()
-        .map_err(|e| anyhow!("Failed to extract {} content: {}", content_type, e))?;
+impl Rule {
+    const ALL: [Rule; 4] = [
+        Rule::ContentDrift,
+        Rule::BrokenPartition,
+        Rule::IdenticalContent,
+        Rule::ClampedRange,
+    ];
 
-    if !verify_hash(&content, expected_hash) {
-        let current_hash = hash_content(&content);
-        return Err(anyhow!(
-            "{} content has changed (expected: {}..., actual: {}...)",
-            content_type,
-            &expected_hash[..8],
-            &current_hash[..8]
+    fn id(self) -> &'static str {
+        match self {
+            Rule::ContentDrift => "doksnet/content-drift",
+            Rule::BrokenPartition => "doksnet/broken-partition",
+            Rule::IdenticalContent => "doksnet/identical-content",
+            Rule::ClampedRange => "doksnet/clamped-range",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Rule::ContentDrift => "Mapped content no longer matches its stored hash",
+            Rule::BrokenPartition => "A partition can't be parsed, read or extracted",
+            Rule::IdenticalContent => IDENTICAL_CONTENT,
+            Rule::ClampedRange => "A line range was cut short at the end of its file",
+        }
+    }
+}
+
+/// An error for each broken side of a failing mapping, at the first line its
+/// partition covers, plus the run's warnings. Problems with the mapping
+/// itself rather than either file are reported against line 1 of `.doks`.
+fn annotations(
+    config: &DoksConfig,
+    doks_file_path: &Path,
+    results: &[MappingResult],
+    warned: &[usize],
+) -> Result<Vec<Annotation>> {
+    let caches = SideCaches::for_config(config);
+    let cwd = super::working_dir()?;
+    let relative = |path: &Path| {
+        path.strip_prefix(&cwd)
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    };
+    let doks_file = relative(doks_file_path);
+    let on_doks_file = |level, rule, message| Annotation {
+        level,
+        rule,
+        path: doks_file.clone(),
+        line: 1,
+        message,
+    };
+
+    let mut annotations = Vec::new();
+    for (index, (mapping, result)) in config.mappings.iter().zip(results).enumerate() {
+        let errors = &result.errors;
+        if !errors.is_empty() {
+            for failure in check::check_mapping(config, doks_file_path, mapping, &caches) {
+                annotations.push(Annotation {
+                    level: "error",
+                    rule: if failure.drifted {
+                        Rule::ContentDrift
+                    } else {
+                        Rule::BrokenPartition
+                    },
+                    path: relative(&failure.path),
+                    line: failure.line,
+                    message: failure.message,
+                });
+            }
+        }
+
+        for warning in &result.warnings {
+            let message = format!("{} (mapping {})", warning, mapping.id);
+            annotations.push(on_doks_file("warning", Rule::ClampedRange, message));
+        }
+
+        let identical = format!("{} (mapping {})", IDENTICAL_CONTENT, mapping.id);
+        if errors.iter().any(|error| error == IDENTICAL_CONTENT) {
+            annotations.push(on_doks_file("error", Rule::IdenticalContent, identical));
+        } else if warned.contains(&index) {
+            annotations.push(on_doks_file("warning", Rule::IdenticalContent, identical));
+        }
+    }
+    Ok(annotations)
+}
+
+fn outcome_of(results: &[MappingResult]) -> Outcome {
+    if results.iter().any(|result| !result.errors.is_empty()) {
+        Outcome::Failed
+    } else {
+        Outcome::Passed
+    }
+}
+
+/// Prints a GitHub Actions `::error` or `::warning` command for each
+/// annotation, so failures show up as annotations on the pull request.
+fn run_github(
+    config: &DoksConfig,
+    doks_file_path: &Path,
+    results: Vec<MappingResult>,
+    warned: &[usize],
+) -> Result<Outcome> {
+    for annotation in annotations(config, doks_file_path, &results, warned)? {
+        println!(
+            "{}",
+            github_command(
+                annotation.level,
+                &annotation.path,
+                annotation.line,
+                &annotation.message
+            )
+        );
+    }
+    Ok(outcome_of(&results))
+}
+
+/// Prints a SARIF 2.1.0 log with a result for each annotation, for code
+/// scanning dashboards that collect the output of several tools.
+fn run_sarif(
+    config: &DoksConfig,
+    doks_file_path: &Path,
+    results: Vec<MappingResult>,
+    warned: &[usize],
+) -> Result<Outcome> {
+    let sarif_results: Vec<_> = annotations(config, doks_file_path, &results, warned)?
+        .into_iter()
+        .map(|annotation| {
+            json!({
+                "ruleId": annotation.rule.id(),
+                "level": annotation.level,
+                "message": { "text": annotation.message },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": annotation.path.replace('\\', "/") },
+                        "region": { "startLine": annotation.line },
+                    },
+                }],
+            })
+        })
+        .collect();
+    let rules: Vec<_> = Rule::ALL
+        .iter()
+        .map(|rule| {
+            json!({
+                "id": rule.id(),
+                "shortDescription": { "text": rule.description() },
+            })
+        })
+        .collect();
+
+    let log = json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "doksnet",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_HOMEPAGE"),
+                    "rules": rules,
+                },
+            },
+            "results": sarif_results,
+        }],
+    });
+    println!("{}", serde_json::to_string_pretty(&log)?);
+
+    Ok(outcome_of(&results))
+}
+
+/// Prints a JUnit XML `<testsuite>` with a `<testcase>` per mapping, named
+/// by its id and classed by its documentation file. A failing mapping has a
+/// `<failure>` listing its errors, or an `<error>` when it couldn't be
+/// checked; warnings go to `<system-out>`.
+fn run_junit(
+    config: &DoksConfig,
+    results: Vec<MappingResult>,
+    warned: &[usize],
+) -> Result<Outcome> {
+    let count = |status| results.iter().filter(|r| status_of(r) == status).count();
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuite name=\"doksnet\" tests=\"{}\" failures=\"{}\" errors=\"{}\">\n",
+        results.len(),
+        count("fail"),
+        count("error")
+    ));
+
+    for (index, (mapping, result)) in config.mappings.iter().zip(&results).enumerate() {
+        let classname = Partition::parse(&mapping.doc_partition)
+            .map(|partition| partition.file_path)
+            .unwrap_or_else(|_| mapping.doc_partition.clone());
+        let mut warnings = result.warnings.clone();
+        if warned.contains(&index) {
+            warnings.push(IDENTICAL_CONTENT.to_string());
+        }
+
+        xml.push_str(&format!(
+            "  <testcase name=\"{}\" classname=\"{}\"",
+            xml_escape(&mapping.id),
+            xml_escape(&classname)
+        ));
+        if result.errors.is_empty() && warnings.is_empty() {
+            xml.push_str("/>\n");
+            continue;
+        }
+        xml.push_str(">\n");
+        if let Some(first) = result.errors.first() {
+            let element = if result.broken { "error" } else { "failure" };
+            xml.push_str(&format!(
+                "    <{0} message=\"{1}\">{2}</{0}>\n",
+                element,
+                xml_escape(first),
+                xml_escape(&result.errors.join("\n"))
+            ));
+        }
+        if !warnings.is_empty() {
+            xml.push_str(&format!(
+                "    <system-out>{}</system-out>\n",
+                xml_escape(&warnings.join("\n"))
+            ));
+        }
+        xml.push_str("  </testcase>\n");
+    }
+    xml.push_str("</testsuite>");
+    println!("{}", xml);
+
+    Ok(outcome_of(&results))
+}
+
+/// Prints a Markdown table with a row per mapping and a summary line below
+/// it, e.g. for a pull request comment. The status is ✅, ❌ for drifted
+/// content or ⚠️ for a mapping that couldn't be checked, and the message
+/// column lists a failing mapping's errors, or a passing one's warnings.
+fn run_markdown(
+    config: &DoksConfig,
+    results: Vec<MappingResult>,
+    warned: &[usize],
+    skipped: Skipped,
+) -> Result<Outcome> {
+    println!("| Status | ID | Doc | Code | Message |");
+    println!("| --- | --- | --- | --- | --- |");
+    for (index, (mapping, result)) in config.mappings.iter().zip(&results).enumerate() {
+        let mut messages = if result.errors.is_empty() {
+            result.warnings.clone()
+        } else {
+            result.errors.clone()
+        };
+        if result.errors.is_empty() && warned.contains(&index) {
+            messages.push(IDENTICAL_CONTENT.to_string());
+        }
+        println!(
+            "| {} | `{}` | `{}` | `{}` | {} |",
+            match status_of(result) {
+                "pass" => "✅",
+                "error" => "⚠️",
+                _ => "❌",
+            },
+            mapping.id,
+            markdown_cell(&mapping.doc_partition),
+            markdown_cell(&mapping.code_partition),
+            markdown_cell(&messages.join("; "))
+        );
+    }
+
+    let count = |status| results.iter().filter(|r| status_of(r) == status).count();
+    let mut counts = format!("{} passed, {} failed", count("pass"), count("fail"));
+    if count("error") > 0 {
+        counts.push_str(&format!(", {} error(s)", count("error")));
+    }
+    let mut summary = format!("**{}** of {} mapping(s)", counts, results.len());
+    if skipped.total() > 0 {
+        summary.push_str(&format!(", {} skipped", skipped.total()));
+    }
+    println!("\n{}", summary);
+
+    Ok(outcome_of(&results))
+}
+
+/// `value` made safe for a Markdown table cell: `|` would end the cell and
+/// a line break the row.
+fn markdown_cell(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', "<br>")
+}
+
+/// `value` with the characters XML treats specially escaped, for use in
+/// both attributes and text.
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// A workflow command such as `::error file=src/lib.rs,line=3::message`, with
+/// the characters GitHub treats specially escaped.
+fn github_command(level: &str, path: &str, line: usize, message: &str) -> String {
+    let escape_data = |value: &str| {
+        value
+            .replace('%', "%25")
+            .replace('\r', "%0D")
+            .replace('\n', "%0A")
+    };
+    let escape_property = |value: &str| escape_data(value).replace(':', "%3A").replace(',', "%2C");
+
+    format!(
+        "::{} file={},line={}::{}",
+        level,
+        escape_property(path),
+        line,
+        escape_data(message)
+    )
+}
+
+/// Prints the current content of each side of `mapping` that no longer
+/// matches its stored hash, as a diff when the mapping stores a snippet of
+/// the expected content. A side that can't be extracted is shown by its
+/// snippet alone, if it has one. Lines start with `indent`, the indentation
+/// of the mapping's own line in the report.
+fn print_changed_content(
+    config: &DoksConfig,
+    mapping: &Mapping,
+    caches: &SideCaches,
+    indent: &str,
+) {
+    let sides = [
+        (
+            &mapping.doc_partition,
+            &mapping.doc_hash,
+            &mapping.doc_snippet,
+            "documentation",
+            caches.doc(),
+            mapping.hash_options(config.hash_options),
+        ),
+        (
+            &mapping.code_partition,
+            &mapping.code_hash,
+            &mapping.code_snippet,
+            "code",
+            caches.code(),
+            mapping.code_hash_options(config.hash_options),
+        ),
+    ];
+    for (partition_str, expected_hash, snippet, content_type, cache, hash_options) in sides {
+        let content = Partition::parse(partition_str)
+            .ok()
+            .and_then(|p| p.extract_content_cached(cache).ok());
+        let (heading, shown) = match (snippet, &content) {
+            (_, Some(content)) if check_hash(content, expected_hash, hash_options).is_ok() => {
+                continue
+            }
+            (Some(snippet), Some(content)) => ("Changes to", unified_diff(snippet, content)),
+            (None, Some(content)) => ("Current", content.clone()),
+            (Some(snippet), None) => ("Expected", snippet.clone()),
+            (None, None) => continue,
+        };
+
+        println!(
+            "{}   --- {} {} content ({}) ---",
+            indent, heading, content_type, partition_str
+        );
+        for line in shown.lines() {
+            println!("{}   {}", indent, line);
+        }
+        println!("{}   ---", indent);
+    }
+}
+
+/// Indices, among the first `checked` mappings, of those whose documentation
+/// and code extract to the same content under the mapping's hash options.
+fn find_identical(config: &DoksConfig, checked: usize) -> Vec<usize> {
+    let caches = SideCaches::for_config(config);
+    let extract = |partition: &str, cache: &ContentCache| {
+        Partition::parse(partition)
+            .ok()
+            .and_then(|p| p.extract_content_cached(cache).ok())
+    };
+
+    config
+        .mappings
+        .iter()
+        .take(checked)
+        .enumerate()
+        .filter(|(_, mapping)| {
+            let options = mapping.hash_options(config.hash_options);
+            match (
+                extract(&mapping.doc_partition, caches.doc()),
+                extract(&mapping.code_partition, caches.code()),
+            ) {
+                (Some(doc), Some(code)) => {
+                    hash_content(&doc, options) == hash_content(&code, options)
+                }
+                _ => false,
+            }
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Whether to draw a progress bar: only for the text report on a terminal,
+/// without `--quiet`, and for runs large enough to take a noticeable time.
+fn should_show_progress(
+    format: TestFormat,
+    quiet: bool,
+    mapping_count: usize,
+    stdout_is_terminal: bool,
+) -> bool {
+    format == TestFormat::Text && !quiet && stdout_is_terminal && mapping_count > PROGRESS_THRESHOLD
+}
+
+/// Each mapping's failures, in the same order as `config.mappings`.
+pub fn check_all(config: &DoksConfig) -> Vec<Vec<String>> {
+    verify_all(config, &config.root)
+        .into_iter()
+        .map(|result| result.errors)
+        .collect()
+}
+
+/// Verifies mappings in order, stopping after the first one that fails.
+fn check_until_failure(config: &DoksConfig, progress: &ProgressBar) -> Vec<MappingResult> {
+    let caches = SideCaches::for_config(config);
+    let mut results = Vec::new();
+    for mapping in &config.mappings {
+        let result = verify_mapping_in(mapping, config.hash_options, &caches, config.clamp_ranges);
+        progress.inc(1);
+        let passed = result.passed;
+        results.push(result);
+        if !passed {
+            break;
+        }
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Mapping;
+    use crate::hash::{hash_content, HashOptions};
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn config_with_mapping(root: &std::path::Path, code_hash: String) -> DoksConfig {
+        let doc_hash = hash_content("Content", HashOptions::default());
+        let mut config = DoksConfig::new("README.md".to_string());
+        config.root = root.to_path_buf();
+        config.add_mapping(Mapping {
+            id: "in-memory-mapping".to_string(),
+            doc_partition: "README.md:2".to_string(),
+            code_partition: "src/main.rs:1".to_string(),
+            doc_hash,
+            code_hash,
+            description: None,
+            normalize_whitespace: false,
+            strip_comments: false,
+            tags: Vec::new(),
+            doc_snippet: None,
+            code_snippet: None,
+            doc_lines: Vec::new(),
+            code_lines: Vec::new(),
+            comments: Vec::new(),
+            created_at: None,
+            updated_at: None,
+            enabled: true,
+        });
+        config
+    }
+
+    #[test]
+    fn test_run_reports_outcome() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/main.rs"), "fn main() {}").unwrap();
+
+        let code_hash = hash_content("fn main() {}", HashOptions::default());
+        let config = config_with_mapping(dir.path(), code_hash);
+        for format in [TestFormat::Text, TestFormat::Json] {
+            assert_eq!(
+                run(
+                    &config,
+                    &dir.path().join(".doks"),
+                    RunOptions {
+                        format,
+                        quiet: true,
+                        ..RunOptions::default()
+                    },
+                    Skipped::default(),
+                )
+                .unwrap(),
+                Outcome::Passed
+            );
+        }
+
+        let config = config_with_mapping(dir.path(), "0".repeat(64));
+        for format in [TestFormat::Text, TestFormat::Json] {
+            assert_eq!(
+                run(
+                    &config,
+                    &dir.path().join(".doks"),
+                    RunOptions {
+                        format,
+                        quiet: true,
+                        ..RunOptions::default()
+                    },
+                    Skipped::default(),
+                )
+                .unwrap(),
+                Outcome::Failed
+            );
+        }
+    }
+
+    #[test]
+    fn test_run_fail_fast_stops_at_first_failure() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/main.rs"), "fn main() {}").unwrap();
+
+        let mut config = config_with_mapping(dir.path(), "0".repeat(64));
+        config.mappings.push(config.mappings[0].clone());
+        config.mappings[1].id = "second-mapping".to_string();
+
+        assert_eq!(
+            check_until_failure(&config, &ProgressBar::hidden()).len(),
+            1
+        );
+        assert_eq!(check_all(&config).len(), 2);
+        assert_eq!(
+            run(
+                &config,
+                &dir.path().join(".doks"),
+                RunOptions {
+                    quiet: true,
+                    fail_fast: true,
+                    ..RunOptions::default()
+                },
+                Skipped::default(),
+            )
+            .unwrap(),
+            Outcome::Failed
+        );
+    }
+
+    #[test]
+    fn test_identical_content_warns_or_fails() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/main.rs"), "Content").unwrap();
+
+        let code_hash = hash_content("Content", HashOptions::default());
+        let config = config_with_mapping(dir.path(), code_hash);
+        assert_eq!(find_identical(&config, 1), vec![0]);
+        assert!(find_identical(&config, 0).is_empty());
+
+        let outcome = |check| {
+            run(
+                &config,
+                &dir.path().join(".doks"),
+                RunOptions {
+                    format: TestFormat::Json,
+                    quiet: true,
+                    identical: check,
+                    ..RunOptions::default()
+                },
+                Skipped::default(),
+            )
+            .unwrap()
+        };
+        assert_eq!(outcome(IdenticalCheck::Warn), Outcome::Passed);
+        assert_eq!(outcome(IdenticalCheck::Deny), Outcome::Failed);
+    }
+
+    #[test]
+    fn test_github_command_escapes_special_characters() {
+        assert_eq!(
+            github_command("error", "src/lib.rs", 3, "code content has changed"),
+            "::error file=src/lib.rs,line=3::code content has changed"
+        );
+        assert_eq!(
+            github_command("warning", "docs/a,b:c.md", 1, "100% done\nnext"),
+            "::warning file=docs/a%2Cb%3Ac.md,line=1::100%25 done%0Anext"
+        );
+    }
+
+    #[test]
+    fn test_markdown_cell() {
+        assert_eq!(markdown_cell("a.md:1-2"), "a.md:1-2");
+        assert_eq!(markdown_cell("x | y\nz"), "x \\| y<br>z");
+    }
+
+    #[test]
+    fn test_xml_escape() {
+        assert_eq!(
+            xml_escape(r#"<a href="x">Tom & Jerry's</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&apos;s&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn test_should_show_progress() {
+        let many = PROGRESS_THRESHOLD + 1;
+        assert!(should_show_progress(TestFormat::Text, false, many, true));
+
+        assert!(!should_show_progress(TestFormat::Text, false, many, false));
+        assert!(!should_show_progress(TestFormat::Text, true, many, true));
+        assert!(!should_show_progress(TestFormat::Json, false, many, true));
+        assert!(!should_show_progress(
+            TestFormat::Text,
+            false,
+            PROGRESS_THRESHOLD,
+            true
         ));
     }
 
-    Ok(())
+    #[test]
+    fn test_run_without_mappings_passes() {
+        let config = DoksConfig::new("README.md".to_string());
+        assert_eq!(
+            run(
+                &config,
+                Path::new(".doks"),
+                RunOptions::default(),
+                Skipped::default(),
+            )
+            .unwrap(),
+            Outcome::Passed
+        );
+    }
 }
@@ -1,88 +1,136 @@
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use dialoguer::{Confirm, Select};
+use std::path::Path;
 
-use crate::config::DoksConfig;
-use crate::hash::{hash_content, verify_hash};
+use crate::config::{DoksConfig, Mapping};
+use crate::git;
+use crate::hash::{check_hash, HashOptions};
+use crate::output::{unified_diff, Marker};
 use crate::partition::Partition;
 
+/// Lines of current content shown for each changed partition.
+const PREVIEW_LINES: usize = 20;
+
 pub fn handle() -> Result<()> {
-    let doks_file_path = DoksConfig::find_doks_file()
-        .ok_or_else(|| anyhow!("No .doks file found. Run 'doksnet new' first."))?;
+    let doks_file_path = super::find_doks_file()?;
 
     let mut config = DoksConfig::from_file(&doks_file_path)?;
 
     if config.mappings.is_empty() {
-        println!("📭 No mappings found. Use 'doksnet add' to create some first.");
+        println!(
+            "{}No mappings found. Use 'doksnet add' to create some first.",
+            Marker::Empty
+        );
         return Ok(());
     }
 
+    // Disabled mappings stay in `config`, which is saved after fixes
+    let enabled: Vec<&Mapping> = config.mappings.iter().filter(|m| m.enabled).collect();
+    let disabled_count = config.mappings.len() - enabled.len();
+    if enabled.is_empty() {
+        println!(
+            "{}All {} mappings are disabled",
+            Marker::Skip,
+            disabled_count
+        );
+        return Ok(());
+    }
+
+    println!(
+        "{}Interactive Testing Mode - {} mappings",
+        Marker::Test,
+        enabled.len()
+    );
     println!(
-        "🧪 Interactive Testing Mode - {} mappings",
-        config.mappings.len()
+        "{}Default documentation file: {}",
+        Marker::Doc,
+        config.default_doc
     );
-    println!("📄 Default documentation file: {}", config.default_doc);
     println!();
 
     let mut failed_mappings = Vec::new();
     let mut passed_count = 0;
     let mut modified = false;
 
-    for (index, mapping) in config.mappings.iter().enumerate() {
+    for (index, mapping) in enabled.iter().enumerate() {
         let mapping_num = index + 1;
         println!(
-            "🔍 Testing mapping {}/{}: {}",
+            "{}Testing mapping {}/{}: {}",
+            Marker::Search,
             mapping_num,
-            config.mappings.len(),
-            &mapping.id[..8]
+            enabled.len(),
+            mapping.short_id()
         );
 
         if let Some(desc) = &mapping.description {
-            println!("   📝 Description: {}", desc);
+            println!("   {}Description: {}", Marker::Note, desc);
         }
 
-        println!("   📄 Doc: {}", mapping.doc_partition);
-        println!("   💻 Code: {}", mapping.code_partition);
+        println!("   {}Doc: {}", Marker::Doc, mapping.doc_partition);
+        println!("   {}Code: {}", Marker::Code, mapping.code_partition);
 
-        let doc_result =
-            test_partition_detailed(&mapping.doc_partition, &mapping.doc_hash, "documentation");
-        let code_result =
-            test_partition_detailed(&mapping.code_partition, &mapping.code_hash, "code");
+        let doc_result = test_partition_detailed(
+            &config.root,
+            &mapping.doc_partition,
+            &mapping.doc_hash,
+            "documentation",
+            mapping.hash_options(config.hash_options),
+        );
+        let code_result = test_partition_detailed(
+            &config.root,
+            &mapping.code_partition,
+            &mapping.code_hash,
+            "code",
+            mapping.code_hash_options(config.hash_options),
+        );
 
         match (doc_result, code_result) {
             (Ok(_), Ok(_)) => {
-                println!("   ✅ PASS");
+                println!("   {}PASS", Marker::Ok);
                 passed_count += 1;
             }
             (doc_result, code_result) => {
-                println!("   ❌ FAIL");
-                failed_mappings.push((index, mapping.clone(), doc_result, code_result));
+                println!("   {}FAIL", Marker::Fail);
+                failed_mappings.push(((*mapping).clone(), doc_result, code_result));
             }
         }
 
         println!();
     }
 
-    println!("📊 Test Results Summary:");
+    println!("{}Test Results Summary:", Marker::Stats);
     if passed_count > 0 {
-        println!("   ✅ Passed: {}/{}", passed_count, config.mappings.len());
+        println!(
+            "   {}Passed: {}/{}",
+            Marker::Ok,
+            passed_count,
+            enabled.len()
+        );
     }
     if !failed_mappings.is_empty() {
         println!(
-            "   ❌ Failed: {}/{}",
+            "   {}Failed: {}/{}",
+            Marker::Fail,
             failed_mappings.len(),
-            config.mappings.len()
+            enabled.len()
         );
     }
+    if disabled_count > 0 {
+        println!("   {}Skipped: {} (disabled)", Marker::Skip, disabled_count);
+    }
     println!();
 
     if failed_mappings.is_empty() {
-        println!("🎉 All mappings are up to date!");
+        println!("{}All mappings are up to date!", Marker::Celebrate);
         return Ok(());
     }
 
-    println!("🛠️  Let's fix the failed mappings...");
+    println!("{}Let's fix the failed mappings...", Marker::Fix);
+
+    // Used to diff against the content the stored hashes were taken from
+    let last_commit = git::last_commit(&doks_file_path);
 
-    for (_original_index, mapping, doc_result, code_result) in failed_mappings {
+    for (mapping, doc_result, code_result) in failed_mappings {
         let current_index = config.mappings.iter().position(|m| m.id == mapping.id);
 
         if current_index.is_none() {
@@ -91,17 +139,25 @@ pub fn handle() -> Result<()> {
         let current_index = current_index.unwrap();
 
         println!(
-            "\n🚨 Failed mapping: {} ({}...)",
+            "\n{}Failed mapping: {} ({}...)",
+            Marker::Alert,
             mapping.id,
-            &mapping.id[..8]
+            mapping.short_id()
         );
         if let Some(desc) = &mapping.description {
-            println!("📝 Description: {}", desc);
+            println!("{}Description: {}", Marker::Note, desc);
         }
-        println!("📄 Doc: {}", mapping.doc_partition);
-        println!("💻 Code: {}", mapping.code_partition);
-
-        show_changes(&mapping, &doc_result, &code_result)?;
+        println!("{}Doc: {}", Marker::Doc, mapping.doc_partition);
+        println!("{}Code: {}", Marker::Code, mapping.code_partition);
+
+        show_changes(
+            &config.root,
+            last_commit.as_deref(),
+            &mapping,
+            config.hash_options,
+            &doc_result,
+            &code_result,
+        );
 
         let options = vec![
             "Update hashes (accept current content)",
@@ -119,23 +175,30 @@ pub fn handle() -> Result<()> {
         match action {
             0 => {
                 if let Err(ref _e) = doc_result {
-                    if let Some(content) = extract_content_if_possible(&mapping.doc_partition) {
-                        config.mappings[current_index].doc_hash = hash_content(&content);
-                        println!("✅ Updated documentation hash");
+                    if let Some(content) =
+                        extract_content_if_possible(&config.root, &mapping.doc_partition)
+                    {
+                        config.mappings[current_index]
+                            .rehash_doc(&content, mapping.hash_options(config.hash_options));
+                        println!("{}Updated documentation hash", Marker::Ok);
                     }
                 }
                 if let Err(ref _e) = code_result {
-                    if let Some(content) = extract_content_if_possible(&mapping.code_partition) {
-                        config.mappings[current_index].code_hash = hash_content(&content);
-                        println!("✅ Updated code hash");
+                    if let Some(content) =
+                        extract_content_if_possible(&config.root, &mapping.code_partition)
+                    {
+                        config.mappings[current_index]
+                            .rehash_code(&content, mapping.code_hash_options(config.hash_options));
+                        println!("{}Updated code hash", Marker::Ok);
                     }
                 }
                 modified = true;
             }
             1 => {
                 println!(
-                    "💡 Use 'doksnet edit {}' to edit this mapping",
-                    &mapping.id[..8]
+                    "{}Use 'doksnet edit {}' to edit this mapping",
+                    Marker::Tip,
+                    mapping.short_id()
                 );
             }
             2 => {
@@ -146,12 +209,12 @@ pub fn handle() -> Result<()> {
 
                 if confirm {
                     config.mappings.remove(current_index);
-                    println!("✅ Mapping removed");
+                    println!("{}Mapping removed", Marker::Ok);
                     modified = true;
                 }
             }
             3 => {
-                println!("⏭️  Skipped");
+                println!("{}Skipped", Marker::Skip);
             }
             _ => unreachable!(),
         }
@@ -159,80 +222,151 @@ pub fn handle() -> Result<()> {
 
     if modified {
         config.to_file(&doks_file_path)?;
-        println!("\n💾 Changes saved to .doks file");
+        println!("\n{}Changes saved to .doks file", Marker::Save);
     }
 
-    println!("\n🏁 Interactive testing complete!");
+    println!("\n{}Interactive testing complete!", Marker::Finish);
 
     Ok(())
 }
 
 fn test_partition_detailed(
+    root: &Path,
     partition_str: &str,
     expected_hash: &str,
     content_type: &str,
+    hash_options: HashOptions,
 ) -> Result<(), String> {
     let partition = match Partition::parse(partition_str) {
         Ok(p) => p,
         Err(e) => return Err(format!("Failed to parse {} partition: {}", content_type, e)),
     };
 
-    let content = match partition.extract_content() {
+    let content = match partition.extract_content_in(root) {
         Ok(c) => c,
         Err(e) => return Err(format!("Failed to extract {} content: {}", content_type, e)),
     };
 
-    if !verify_hash(&content, expected_hash) {
-        let current_hash = hash_content(&content);
-        return Err(format!(
-            "{} content has changed (expected: {}..., actual: {}...)",
-            content_type,
-            &expected_hash[..8],
-            &current_hash[..8]
-        ));
-    }
-
-    Ok(())
+    check_hash(&content, expected_hash, hash_options)
+        .map_err(|mismatch| format!("{} {}", content_type, mismatch))
 }
 
 fn show_changes(
-    mapping: &crate::config::Mapping,
+    root: &Path,
+    last_commit: Option<&str>,
+    mapping: &Mapping,
+    defaults: HashOptions,
     doc_result: &Result<(), String>,
     code_result: &Result<(), String>,
-) -> Result<()> {
-    println!("\n📋 Changes detected:");
+) {
+    println!("\n{}Changes detected:", Marker::List);
 
     if doc_result.is_err() {
-        println!("\n📄 Documentation content has changed:");
-        if let Some(content) = extract_content_if_possible(&mapping.doc_partition) {
-            println!("--- Current content ---");
-            println!("{}", content.chars().take(300).collect::<String>());
-            if content.len() > 300 {
-                println!("... (truncated)");
-            }
-        } else {
-            println!("⚠️  Could not extract current documentation content");
-        }
+        println!("\n{}Documentation content has changed:", Marker::Doc);
+        show_partition_changes(
+            root,
+            last_commit,
+            &mapping.doc_partition,
+            &mapping.doc_hash,
+            mapping.doc_snippet.as_deref(),
+            mapping.hash_options(defaults),
+            "documentation",
+        );
     }
 
     if code_result.is_err() {
-        println!("\n💻 Code content has changed:");
-        if let Some(content) = extract_content_if_possible(&mapping.code_partition) {
-            println!("--- Current content ---");
-            println!("{}", content.chars().take(300).collect::<String>());
-            if content.len() > 300 {
-                println!("... (truncated)");
-            }
-        } else {
-            println!("⚠️  Could not extract current code content");
-        }
+        println!("\n{}Code content has changed:", Marker::Code);
+        show_partition_changes(
+            root,
+            last_commit,
+            &mapping.code_partition,
+            &mapping.code_hash,
+            mapping.code_snippet.as_deref(),
+            mapping.code_hash_options(defaults),
+            "code",
+        );
     }
+}
 
-    Ok(())
+/// Prints a diff against the mapping's stored snippet, or the committed
+/// content when git can recover it, falling back to a numbered preview of the
+/// current content.
+fn show_partition_changes(
+    root: &Path,
+    last_commit: Option<&str>,
+    partition_str: &str,
+    expected_hash: &str,
+    snippet: Option<&str>,
+    hash_options: HashOptions,
+    content_type: &str,
+) {
+    let stored = snippet.map(str::to_string).or_else(|| {
+        last_commit.and_then(|commit| {
+            git::recover_partition(root, commit, partition_str, expected_hash, hash_options)
+        })
+    });
+    let current = extract_content_if_possible(root, partition_str);
+
+    if let (Some(stored), Some(current)) = (&stored, &current) {
+        print!("{}", unified_diff(stored, current));
+    } else if let Some(lines) = extract_numbered_if_possible(root, partition_str) {
+        println!("--- Current content ---");
+        print!("{}", numbered_preview(&lines, PREVIEW_LINES));
+    } else {
+        println!(
+            "{}Could not extract current {} content",
+            Marker::Warn,
+            content_type
+        );
+    }
 }
 
-fn extract_content_if_possible(partition_str: &str) -> Option<String> {
+fn extract_content_if_possible(root: &Path, partition_str: &str) -> Option<String> {
     Partition::parse(partition_str)
         .ok()
-        .and_then(|p| p.extract_content().ok())
+        .and_then(|p| p.extract_content_in(root).ok())
+}
+
+fn extract_numbered_if_possible(root: &Path, partition_str: &str) -> Option<Vec<(usize, String)>> {
+    Partition::parse(partition_str)
+        .ok()
+        .and_then(|p| p.extract_numbered_lines_in(root).ok())
+}
+
+/// Renders up to `max_lines` lines prefixed with their line number in the file.
+fn numbered_preview(lines: &[(usize, String)], max_lines: usize) -> String {
+    let width = lines
+        .iter()
+        .take(max_lines)
+        .map(|(number, _)| number.to_string().len())
+        .max()
+        .unwrap_or(1);
+
+    let mut preview = String::new();
+    for (number, line) in lines.iter().take(max_lines) {
+        preview.push_str(&format!("{:>width$} | {}\n", number, line, width = width));
+    }
+    if lines.len() > max_lines {
+        preview.push_str(&format!("... ({} more lines)\n", lines.len() - max_lines));
+    }
+    preview
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_numbered_preview() {
+        let lines: Vec<(usize, String)> = (9..=12).map(|n| (n, format!("line {}", n))).collect();
+
+        assert_eq!(
+            numbered_preview(&lines, 10),
+            " 9 | line 9\n10 | line 10\n11 | line 11\n12 | line 12\n"
+        );
+        assert_eq!(
+            numbered_preview(&lines, 2),
+            " 9 | line 9\n10 | line 10\n... (2 more lines)\n"
+        );
+    }
 }
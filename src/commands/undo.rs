@@ -0,0 +1,156 @@
+use anyhow::{anyhow, Result};
+use std::path::{Path, PathBuf};
+
+use crate::config::{DoksConfig, Mapping};
+use crate::output::Marker;
+
+const UNDO_HEADER: &str =
+    "# doksnet undo log: mappings removed by the last 'remove' or 'remove-failed'.\n\
+# Run 'doksnet undo' to put them back.\n";
+
+/// The undo log kept next to `doks_file_path`, e.g. `.doks.undo`.
+pub fn undo_log_path(doks_file_path: &Path) -> PathBuf {
+    let mut file_name = doks_file_path
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_default();
+    file_name.push(".undo");
+    doks_file_path.with_file_name(file_name)
+}
+
+/// Records the mappings about to be removed, with their positions in `.doks`,
+/// so that `undo` can restore them. Replaces the previous log: only the last
+/// removal can be undone.
+pub fn record_removal(doks_file_path: &Path, removed: &[(usize, &Mapping)]) -> Result<()> {
+    let mut log = UNDO_HEADER.to_string();
+    for (index, mapping) in removed {
+        log.push_str(&format!("@{}\n", index));
+        for comment in &mapping.comments {
+            log.push_str(comment);
+            log.push('\n');
+        }
+        log.push_str(&mapping.to_line());
+        log.push('\n');
+    }
+
+    let log_path = undo_log_path(doks_file_path);
+    std::fs::write(&log_path, log)
+        .map_err(|e| anyhow!("Cannot write undo log '{}': {}", log_path.display(), e))
+}
+
+/// Puts the mappings removed by the last `remove` or `remove-failed` back
+/// where they were, then deletes the undo log.
+pub fn handle() -> Result<()> {
+    let doks_file_path = super::find_doks_file()?;
+    let log_path = undo_log_path(&doks_file_path);
+    if !log_path.exists() {
+        return Err(anyhow!(
+            "Nothing to undo: no removal has been recorded since the last undo"
+        ));
+    }
+
+    let mut config = DoksConfig::from_file(&doks_file_path)?;
+    let removed = parse_log(&std::fs::read_to_string(&log_path)?)
+        .map_err(|e| anyhow!("Invalid undo log '{}': {}", log_path.display(), e))?;
+
+    let mut restored = Vec::new();
+    let mut collisions = Vec::new();
+    for (index, mapping) in removed {
+        if config.mappings.iter().any(|m| m.id == mapping.id) {
+            collisions.push(mapping.id);
+            continue;
+        }
+        let index = index.min(config.mappings.len());
+        restored.push(mapping.id.clone());
+        config.mappings.insert(index, mapping);
+    }
+
+    if !restored.is_empty() {
+        config.to_file(&doks_file_path)?;
+    }
+    std::fs::remove_file(&log_path)?;
+
+    println!("{}Restored {} mapping(s)", Marker::Ok, restored.len());
+    for id in &restored {
+        println!("   {}{}", Marker::Bullet, id);
+    }
+    if !collisions.is_empty() {
+        println!(
+            "   {}Skipped (ID already exists): {}",
+            Marker::Skip,
+            collisions.len()
+        );
+        for id in &collisions {
+            println!("      {}{}", Marker::Bullet, id);
+        }
+    }
+    println!("{}Total mappings: {}", Marker::Stats, config.mappings.len());
+
+    Ok(())
+}
+
+/// The `(position, mapping)` entries of an undo log, in the order written.
+fn parse_log(log: &str) -> Result<Vec<(usize, Mapping)>> {
+    let mut entries = Vec::new();
+    let mut position = None;
+    let mut comments = Vec::new();
+    for line in log.lines() {
+        if let Some(index) = line.strip_prefix('@') {
+            position = Some(
+                index
+                    .parse::<usize>()
+                    .map_err(|_| anyhow!("invalid position '{}'", index))?,
+            );
+            comments.clear();
+        } else if let Some(index) = position {
+            if line.is_empty() || line.starts_with('#') {
+                comments.push(line.to_string());
+                continue;
+            }
+            let mut mapping = Mapping::from_line(line)?;
+            mapping.comments = std::mem::take(&mut comments);
+            entries.push((index, mapping));
+            position = None;
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undo_log_path() {
+        assert_eq!(
+            undo_log_path(Path::new("docs/.doks")),
+            PathBuf::from("docs/.doks.undo")
+        );
+        assert_eq!(
+            undo_log_path(Path::new("mappings.doks")),
+            PathBuf::from("mappings.doks.undo")
+        );
+    }
+
+    #[test]
+    fn test_parse_log() {
+        let log = format!(
+            "{}@2\n# Auth\n\nauth|README.md:3|src/auth.rs:1|aaa|bbb|Login\n@5\nmain|README.md:9|src/main.rs|ccc|ddd|\n",
+            UNDO_HEADER
+        );
+        let entries = parse_log(&log).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].0, 2);
+        assert_eq!(entries[0].1.id, "auth");
+        assert_eq!(
+            entries[0].1.comments,
+            vec!["# Auth".to_string(), String::new()]
+        );
+        assert_eq!(entries[0].1.description.as_deref(), Some("Login"));
+        assert_eq!(entries[1].0, 5);
+        assert_eq!(entries[1].1.code_partition, "src/main.rs");
+
+        assert!(parse_log("@x\nid|a|b|c|d|\n").is_err());
+    }
+}
@@ -0,0 +1,66 @@
+use anyhow::{anyhow, Result};
+use std::path::Path;
+use std::process;
+
+use crate::config::DoksConfig;
+use crate::output::Marker;
+use crate::partition::Partition;
+
+pub fn handle() -> Result<()> {
+    let doks_file_path = super::find_doks_file()?;
+
+    let config = DoksConfig::from_file(&doks_file_path)?;
+
+    if config.mappings.is_empty() {
+        println!(
+            "{}No mappings found. Use 'doksnet add' to create some first.",
+            Marker::Empty
+        );
+        return Ok(());
+    }
+
+    println!(
+        "{}Validating {} mappings",
+        Marker::Search,
+        config.mappings.len()
+    );
+
+    let mut problems = Vec::new();
+    for mapping in &config.mappings {
+        for (partition_str, content_type) in [
+            (&mapping.doc_partition, "Documentation"),
+            (&mapping.code_partition, "Code"),
+        ] {
+            if let Err(e) = validate_partition(&config.root, partition_str) {
+                problems.push((mapping.id.as_str(), format!("{}: {}", content_type, e)));
+            }
+        }
+    }
+
+    if problems.is_empty() {
+        println!("{}All partitions are valid", Marker::Ok);
+        return Ok(());
+    }
+
+    println!("\n{}Found {} problem(s):", Marker::Alert, problems.len());
+    for (id, problem) in &problems {
+        println!("   {}{}", Marker::Pin, id);
+        println!("      {}{}", Marker::Bullet, problem);
+    }
+
+    process::exit(1);
+}
+
+/// Checks that a partition parses and that its file exists, without reading
+/// it. A glob has to match exactly one file.
+fn validate_partition(root: &Path, partition_str: &str) -> Result<()> {
+    let partition = Partition::parse(partition_str)
+        .map_err(|e| anyhow!("Malformed partition '{}': {}", partition_str, e))?;
+
+    let path = partition.locate_in(root)?;
+    if !path.is_file() {
+        return Err(anyhow!("File not found: {}", partition.expanded_path()?));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,214 @@
+use anyhow::Result;
+use notify::event::ModifyKind;
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+use crate::commands::test::{self, RunOptions, Skipped};
+use crate::config::DoksConfig;
+use crate::output::Marker;
+use crate::partition::Partition;
+
+/// How long the files must stay quiet before a batch of changes is tested,
+/// so that an editor's save (often several writes) triggers a single run.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+pub fn handle() -> Result<()> {
+    let doks_file_path = super::find_doks_file()?;
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        // Reading files to test them produces access events; only react to edits
+        if let Some(event) = event.ok().filter(|event| is_edit(&event.kind)) {
+            for path in event.paths {
+                // The receiver is gone only while shutting down
+                let _ = tx.send(path);
+            }
+        }
+    })?;
+
+    let mut watched_dirs = BTreeSet::new();
+    let mut watched_files = run_once(&doks_file_path)?;
+
+    loop {
+        // Watch directories rather than files: editors often save by
+        // replacing the file, which would end a watch on the file itself
+        let dirs: BTreeSet<PathBuf> = watched_files
+            .iter()
+            .filter_map(|file| file.parent().map(Path::to_path_buf))
+            .filter(|dir| dir.is_dir())
+            .collect();
+        for dir in watched_dirs.difference(&dirs) {
+            let _ = watcher.unwatch(dir);
+        }
+        for dir in dirs.difference(&watched_dirs) {
+            watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        }
+        watched_dirs = dirs;
+
+        println!(
+            "\n{}Watching {} file(s) for changes (Ctrl-C to stop)",
+            Marker::Inspect,
+            watched_files.len()
+        );
+
+        let changed = loop {
+            let Some(batch) = collect_batch(&rx, DEBOUNCE) else {
+                return Ok(());
+            };
+            let changed: Vec<PathBuf> = batch
+                .into_iter()
+                .filter(|path| watched_files.contains(path))
+                .collect();
+            if !changed.is_empty() {
+                break changed;
+            }
+        };
+
+        println!();
+        for path in &changed {
+            println!("{}Changed: {}", Marker::Sync, path.display());
+        }
+        watched_files = run_once(&doks_file_path)?;
+    }
+}
+
+fn is_edit(kind: &EventKind) -> bool {
+    match kind {
+        EventKind::Modify(ModifyKind::Metadata(_)) => false,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_) => true,
+        _ => false,
+    }
+}
+
+/// Reloads `.doks` and tests every mapping, returning the files to watch
+/// next. A `.doks` file that fails to load is reported and watched until fixed.
+fn run_once(doks_file_path: &Path) -> Result<BTreeSet<PathBuf>> {
+    match DoksConfig::from_file(doks_file_path) {
+        Ok(mut config) => {
+            let skipped = Skipped {
+                disabled: test::drop_disabled(&mut config),
+                ..Skipped::default()
+            };
+            let options = RunOptions {
+                quiet: true,
+                ..RunOptions::default()
+            };
+            test::run(&config, doks_file_path, options, skipped)?;
+            Ok(watched_paths(doks_file_path, &config))
+        }
+        Err(e) => {
+            println!("{}Failed to load .doks: {}", Marker::Fail, e);
+            Ok(BTreeSet::from([doks_file_path.to_path_buf()]))
+        }
+    }
+}
+
+/// The `.doks` file plus every file a mapping points into.
+fn watched_paths(doks_file_path: &Path, config: &DoksConfig) -> BTreeSet<PathBuf> {
+    let mut paths = BTreeSet::from([doks_file_path.to_path_buf()]);
+    for mapping in &config.mappings {
+        for partition_str in [&mapping.doc_partition, &mapping.code_partition] {
+            let Ok(partition) = Partition::parse(partition_str) else {
+                continue;
+            };
+            if let Ok(path) = partition.locate_in(&config.root) {
+                paths.insert(path);
+            } else if let Ok(file_path) = partition.expanded_path() {
+                paths.insert(config.root.join(file_path));
+            }
+        }
+    }
+    paths
+}
+
+/// Blocks until a path arrives, then keeps collecting until none has arrived
+/// for `quiet_period`. Returns `None` once the sender is gone.
+fn collect_batch(rx: &Receiver<PathBuf>, quiet_period: Duration) -> Option<BTreeSet<PathBuf>> {
+    let mut batch = BTreeSet::from([rx.recv().ok()?]);
+    loop {
+        match rx.recv_timeout(quiet_period) {
+            Ok(path) => {
+                batch.insert(path);
+            }
+            Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => {
+                return Some(batch)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Mapping;
+    use std::thread;
+
+    #[test]
+    fn test_collect_batch_debounces() {
+        let (tx, rx) = mpsc::channel();
+        let sender = thread::spawn(move || {
+            for name in ["a.rs", "b.rs", "a.rs"] {
+                tx.send(PathBuf::from(name)).unwrap();
+                thread::sleep(Duration::from_millis(10));
+            }
+            thread::sleep(Duration::from_millis(300));
+            tx.send(PathBuf::from("c.rs")).unwrap();
+        });
+
+        let first = collect_batch(&rx, Duration::from_millis(100)).unwrap();
+        assert_eq!(
+            first,
+            BTreeSet::from([PathBuf::from("a.rs"), PathBuf::from("b.rs")])
+        );
+        let second = collect_batch(&rx, Duration::from_millis(100)).unwrap();
+        assert_eq!(second, BTreeSet::from([PathBuf::from("c.rs")]));
+
+        sender.join().unwrap();
+        assert_eq!(collect_batch(&rx, Duration::from_millis(100)), None);
+    }
+
+    #[test]
+    fn test_watched_paths() {
+        let mut config = DoksConfig::new("README.md".to_string());
+        config.root = PathBuf::from("/project");
+        for (doc, code) in [
+            ("README.md:1", "src/main.rs:1-3"),
+            ("README.md:2", "src/lib.rs:@@auth@@"),
+            ("README.md:x", "src/main.rs"),
+        ] {
+            config.add_mapping(Mapping {
+                id: format!("{}-{}", doc, code),
+                doc_partition: doc.to_string(),
+                code_partition: code.to_string(),
+                doc_hash: String::new(),
+                code_hash: String::new(),
+                description: None,
+                normalize_whitespace: false,
+                strip_comments: false,
+                tags: Vec::new(),
+                doc_snippet: None,
+                code_snippet: None,
+                doc_lines: Vec::new(),
+                code_lines: Vec::new(),
+                comments: Vec::new(),
+                created_at: None,
+                updated_at: None,
+                enabled: true,
+            });
+        }
+
+        let doks = Path::new("/project/.doks");
+        assert_eq!(
+            watched_paths(doks, &config),
+            BTreeSet::from([
+                PathBuf::from("/project/.doks"),
+                PathBuf::from("/project/README.md"),
+                PathBuf::from("/project/src/lib.rs"),
+                PathBuf::from("/project/src/main.rs"),
+            ])
+        );
+    }
+}
@@ -0,0 +1,271 @@
+//! Comment stripping for mappings with `strip_comments=true`, so that edits
+//! touching only comments don't change the hash of a code partition.
+
+use std::path::Path;
+
+/// How comments are written in a source file, chosen by its extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommentSyntax {
+    /// `//` and nested `/* */`; `'` starts a char literal or a lifetime.
+    Rust,
+    /// `//` and `/* */`; `'` delimits strings like `"` does.
+    CStyle,
+    /// `#` to the end of the line.
+    Hash,
+}
+
+/// Extensions of languages with `//` and `/* */` comments, besides Rust.
+const C_STYLE_EXTENSIONS: [&str; 15] = [
+    "c", "cc", "cpp", "cs", "go", "h", "hpp", "java", "js", "jsx", "kt", "scala", "swift", "ts",
+    "tsx",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Code,
+    /// Inside a block comment, nested `depth` deep.
+    Block(usize),
+    /// Inside a string opened with `quote`; `hashes` is set for Rust raw
+    /// strings, which end at `"` followed by that many `#`.
+    Str {
+        quote: char,
+        hashes: Option<usize>,
+    },
+}
+
+impl CommentSyntax {
+    /// The syntax for `file_path`: Rust for `.rs`, C-style for the usual
+    /// C-family extensions, `#` for everything else.
+    pub fn for_path(file_path: &str) -> Self {
+        let extension = Path::new(file_path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        match extension.as_str() {
+            "rs" => CommentSyntax::Rust,
+            extension if C_STYLE_EXTENSIONS.contains(&extension) => CommentSyntax::CStyle,
+            _ => CommentSyntax::Hash,
+        }
+    }
+
+    /// `content` without its comments. Lines that held nothing but a comment
+    /// are dropped, and whitespace before a trailing comment is trimmed, so
+    /// adding, removing or rewording comments leaves the result unchanged.
+    /// Comment markers inside string and char literals are kept.
+    pub fn strip(self, content: &str) -> String {
+        let mut state = State::Code;
+        let mut lines = Vec::new();
+        for line in content.split('\n') {
+            let (kept, had_comment) = self.strip_line(line, &mut state);
+            if !had_comment {
+                lines.push(kept);
+            } else if !kept.trim().is_empty() {
+                lines.push(kept.trim_end().to_string());
+            }
+        }
+        lines.join("\n")
+    }
+
+    /// Strips one line, carrying block comments and multi-line strings over
+    /// to the next through `state`. Also returns whether a comment was seen.
+    fn strip_line(self, line: &str, state: &mut State) -> (String, bool) {
+        let chars: Vec<char> = line.chars().collect();
+        let mut kept = String::new();
+        let mut had_comment = false;
+        let mut i = 0;
+
+        while i < chars.len() {
+            let c = chars[i];
+            let next = chars.get(i + 1).copied();
+            match *state {
+                State::Block(depth) => {
+                    had_comment = true;
+                    if c == '*' && next == Some('/') {
+                        *state = if depth == 1 {
+                            State::Code
+                        } else {
+                            State::Block(depth - 1)
+                        };
+                        i += 2;
+                    } else if self == CommentSyntax::Rust && c == '/' && next == Some('*') {
+                        *state = State::Block(depth + 1);
+                        i += 2;
+                    } else {
+                        i += 1;
+                    }
+                }
+                State::Str { quote, hashes } => {
+                    kept.push(c);
+                    i += 1;
+                    if c == '\\' && hashes.is_none() {
+                        if let Some(escaped) = next {
+                            kept.push(escaped);
+                            i += 1;
+                        }
+                    } else if c == quote {
+                        let closing = hashes.unwrap_or(0);
+                        let closed = chars
+                            .get(i..i + closing)
+                            .is_some_and(|rest| rest.iter().all(|&c| c == '#'));
+                        if closed {
+                            kept.extend(&chars[i..i + closing]);
+                            i += closing;
+                            *state = State::Code;
+                        }
+                    }
+                }
+                State::Code => {
+                    if self == CommentSyntax::Hash {
+                        if c == '#' {
+                            had_comment = true;
+                            break;
+                        }
+                    } else if c == '/' && next == Some('/') {
+                        had_comment = true;
+                        break;
+                    } else if c == '/' && next == Some('*') {
+                        had_comment = true;
+                        *state = State::Block(1);
+                        i += 2;
+                        continue;
+                    }
+
+                    if let Some(hashes) = self.raw_string_start(&chars, i) {
+                        // `r`, the hashes and the opening quote
+                        kept.extend(&chars[i..i + hashes + 2]);
+                        i += hashes + 2;
+                        *state = State::Str {
+                            quote: '"',
+                            hashes: Some(hashes),
+                        };
+                    } else if c == '\'' && self == CommentSyntax::Rust {
+                        let end = char_literal_end(&chars, i).unwrap_or(i + 1);
+                        kept.extend(&chars[i..end]);
+                        i = end;
+                    } else {
+                        kept.push(c);
+                        i += 1;
+                        if c == '"' || c == '\'' {
+                            *state = State::Str {
+                                quote: c,
+                                hashes: None,
+                            };
+                        }
+                    }
+                }
+            }
+        }
+
+        // Single quotes outside Rust don't span lines; an apostrophe in
+        // unquoted text shouldn't hide the comments below it
+        if matches!(*state, State::Str { quote: '\'', .. }) {
+            *state = State::Code;
+        }
+        (kept, had_comment)
+    }
+
+    /// The number of `#` when a Rust raw string (`r"`, `r#"`, ...) starts at
+    /// `i`, i.e. `r` not preceded by an identifier character (other than the
+    /// `b` of `br"`).
+    fn raw_string_start(self, chars: &[char], i: usize) -> Option<usize> {
+        if self != CommentSyntax::Rust || chars[i] != 'r' {
+            return None;
+        }
+        let is_ident = |c: &char| c.is_alphanumeric() || *c == '_';
+        let prefix_start = match i.checked_sub(1).map(|j| chars[j]) {
+            Some('b') => i - 1,
+            _ => i,
+        };
+        if prefix_start > 0 && is_ident(&chars[prefix_start - 1]) {
+            return None;
+        }
+        let hashes = chars[i + 1..].iter().take_while(|&&c| c == '#').count();
+        (chars.get(i + 1 + hashes) == Some(&'"')).then_some(hashes)
+    }
+}
+
+/// The index just past the Rust char literal starting at `i`, or `None` when
+/// the `'` starts a lifetime or label instead.
+fn char_literal_end(chars: &[char], i: usize) -> Option<usize> {
+    match chars.get(i + 1) {
+        Some('\\') => chars[i + 2..]
+            .iter()
+            .skip(1)
+            .position(|&c| c == '\'')
+            .map(|offset| i + 4 + offset),
+        Some(_) if chars.get(i + 2) == Some(&'\'') => Some(i + 3),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_for_path() {
+        assert_eq!(CommentSyntax::for_path("src/lib.rs"), CommentSyntax::Rust);
+        assert_eq!(CommentSyntax::for_path("web/app.TS"), CommentSyntax::CStyle);
+        assert_eq!(CommentSyntax::for_path("setup.py"), CommentSyntax::Hash);
+        assert_eq!(CommentSyntax::for_path("Makefile"), CommentSyntax::Hash);
+    }
+
+    #[test]
+    fn test_strip_rust_comments() {
+        let code = "/// Adds one.\n\
+            fn add(x: u32) -> u32 {\n    \
+                // bump\n    \
+                x + 1 // the answer\n\
+                /* gone\n   still gone */}\n";
+        assert_eq!(
+            CommentSyntax::Rust.strip(code),
+            "fn add(x: u32) -> u32 {\n    x + 1\n}\n"
+        );
+
+        // Nested block comments end with the outermost `*/`
+        assert_eq!(CommentSyntax::Rust.strip("a /* x /* y */ z */ b"), "a  b");
+    }
+
+    #[test]
+    fn test_strip_rust_keeps_literals() {
+        let code = "let url = \"http://example.com\"; // site";
+        assert_eq!(
+            CommentSyntax::Rust.strip(code),
+            "let url = \"http://example.com\";"
+        );
+
+        let code = "let s = r#\"/* \"quoted\" */\"#; let q = '\"'; // x";
+        assert_eq!(
+            CommentSyntax::Rust.strip(code),
+            "let s = r#\"/* \"quoted\" */\"#; let q = '\"';"
+        );
+
+        let code = "fn f<'a>(s: &'a str) -> char { '/' } // slash";
+        assert_eq!(
+            CommentSyntax::Rust.strip(code),
+            "fn f<'a>(s: &'a str) -> char { '/' }"
+        );
+
+        let code = "let s = \"escaped \\\" // quote\"; let c = '\\''; // x";
+        assert_eq!(
+            CommentSyntax::Rust.strip(code),
+            "let s = \"escaped \\\" // quote\"; let c = '\\'';"
+        );
+    }
+
+    #[test]
+    fn test_strip_hash_comments() {
+        let code = "#!/bin/sh\n# Greet\necho \"#1\" 'and #2' # done\n";
+        assert_eq!(CommentSyntax::Hash.strip(code), "echo \"#1\" 'and #2'\n");
+    }
+
+    #[test]
+    fn test_strip_c_style_single_quotes() {
+        let code = "const s = '// not a comment'; // comment";
+        assert_eq!(
+            CommentSyntax::CStyle.strip(code),
+            "const s = '// not a comment';"
+        );
+    }
+}
@@ -1,12 +1,63 @@
-use anyhow::{anyhow, Result};
-use std::path::Path;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use crate::comments::CommentSyntax;
+use crate::error::{DoksError, Result};
+use crate::hash::{hash_content, line_digests, HashOptions};
+use crate::partition::{Addressing, Partition};
 
 pub const DOKS_FILE_NAME: &str = ".doks";
 
+/// Characters of content shown when previewing a partition.
+pub const DEFAULT_PREVIEW_CHARS: usize = 200;
+
+/// Largest content, in bytes, stored inline as an expected snippet, so that
+/// `add --store-snippet` on a big partition doesn't bloat `.doks`.
+pub const MAX_SNIPPET_BYTES: usize = 4096;
+
+/// Header fields of a `.doks` file, in the order they're written.
+pub const HEADER_KEYS: [&str; 5] = [
+    "default_doc",
+    "hash_algo",
+    "normalize_line_endings",
+    "preview_chars",
+    "sort_mappings",
+];
+
+const HEADER_COMMENT: &str = "# .doks - Mapping doks to code";
+const FORMAT_COMMENT: &str =
+    "# Format: id|doc_partition|code_partition|doc_hash|code_hash|description";
+
 #[derive(Debug, Clone)]
 pub struct DoksConfig {
     pub default_doc: String,
+    pub hash_options: HashOptions,
+    /// Characters shown in content previews (`preview_chars=` header); 0
+    /// shows the whole content.
+    pub preview_chars: usize,
+    /// Write mappings in canonical order (`sort_mappings=true` header) so
+    /// that `.doks` doesn't churn with the order mappings were added in.
+    pub sort_mappings: bool,
     pub mappings: Vec<Mapping>,
+    /// User comment lines following the last mapping.
+    pub trailing_comments: Vec<String>,
+    /// Directory that partition paths are relative to: the one containing
+    /// the `.doks` file when loaded with `from_file`.
+    pub root: PathBuf,
+    /// Resolves documentation partitions from here instead of `root`, for a
+    /// single run (`test --doc-base`). Never saved.
+    pub doc_base: Option<PathBuf>,
+    /// Resolves code partitions from here instead of `root`, e.g. a build
+    /// output directory (`test --code-base`). Never saved.
+    pub code_base: Option<PathBuf>,
+    /// Cuts line ranges that end past the end of their file short instead of
+    /// failing them, for a single run (`test --clamp`). Never saved.
+    pub clamp_ranges: bool,
+    /// How many mappings are verified at once, for a single run
+    /// (`test --parallel`). `None` uses one thread per CPU. Never saved.
+    pub parallelism: Option<usize>,
 }
 
 #[derive(Debug, Clone)]
@@ -17,109 +68,523 @@ pub struct Mapping {
     pub doc_hash: String,
     pub code_hash: String,
     pub description: Option<String>,
+    /// Hash both sides with whitespace normalization (see `HashOptions`).
+    pub normalize_whitespace: bool,
+    /// Strip comments from the code before hashing it (`strip_comments=true`),
+    /// in the syntax its file extension implies (see `CommentSyntax`).
+    pub strip_comments: bool,
+    /// Groups the mapping belongs to, e.g. a feature area (`tags=auth,api`),
+    /// for running `test --tag` on just one of them.
+    pub tags: Vec<String>,
+    /// The content each side was hashed from, stored inline
+    /// (`doc_snippet=`, `code_snippet=`) by `add --store-snippet` so that
+    /// drift can be shown as a diff even where the files or their history
+    /// aren't available. Kept in step with the hashes when re-hashing.
+    pub doc_snippet: Option<String>,
+    pub code_snippet: Option<String>,
+    /// Per-line digests of multi-line content (see `line_digests`), used to
+    /// report which lines changed. Empty when the content is a single line
+    /// or the digests were never recorded.
+    pub doc_lines: Vec<String>,
+    pub code_lines: Vec<String>,
+    /// User comment and blank lines written directly above this mapping,
+    /// kept so that rewriting `.doks` doesn't drop them.
+    pub comments: Vec<String>,
+    /// RFC 3339 time the mapping was added, and last re-hashed or edited.
+    /// `None` for mappings written before timestamps were recorded.
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    /// Disabled mappings are kept in `.doks`, hashes and all, but skipped
+    /// when verifying (`enabled=false`).
+    pub enabled: bool,
+}
+
+/// Where a partition points, for ordering mappings: the file, then the line
+/// (or byte offset) it starts at, then the partition as written, which
+/// breaks ties between anchors, headings and ranges counted from the end.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PartitionKey {
+    file_path: String,
+    start: usize,
+    partition: String,
+}
+
+impl PartitionKey {
+    fn of(partition_str: &str) -> Self {
+        let (file_path, start) = match Partition::parse(partition_str) {
+            Ok(partition) => {
+                let start = match &partition.addressing {
+                    Addressing::Lines(segments) => match segments.first() {
+                        None => 0,
+                        // Counted from the end, so after any numbered line
+                        Some(segment) if segment.from_end => usize::MAX,
+                        Some(segment) => segment.start_line,
+                    },
+                    Addressing::Bytes { start, .. } => *start,
+                    Addressing::Anchor { .. } | Addressing::Heading { .. } => 0,
+                };
+                (partition.file_path, start)
+            }
+            Err(_) => (partition_str.to_string(), 0),
+        };
+        Self {
+            file_path,
+            start,
+            partition: partition_str.to_string(),
+        }
+    }
+}
+
+/// The current UTC time in RFC 3339 format, to the second.
+pub fn timestamp_now() -> String {
+    OffsetDateTime::now_utc()
+        .replace_nanosecond(0)
+        .unwrap()
+        .format(&Rfc3339)
+        .unwrap()
+}
+
+impl Mapping {
+    /// The first 8 characters of the id, as commands show it; shorter,
+    /// hand-written ids are shown in full.
+    pub fn short_id(&self) -> String {
+        self.id.chars().take(8).collect()
+    }
+
+    /// Hash options for this mapping, layered over the file-wide defaults.
+    pub fn hash_options(&self, defaults: HashOptions) -> HashOptions {
+        HashOptions {
+            normalize_whitespace: self.normalize_whitespace,
+            ..defaults
+        }
+    }
+
+    /// Hash options for the code partition: `hash_options`, plus comment
+    /// stripping for the code file's language when `strip_comments` is set.
+    pub fn code_hash_options(&self, defaults: HashOptions) -> HashOptions {
+        let strip_comments = self.strip_comments.then(|| {
+            let file_path = Partition::parse(&self.code_partition)
+                .map(|partition| partition.file_path)
+                .unwrap_or_else(|_| self.code_partition.clone());
+            CommentSyntax::for_path(&file_path)
+        });
+        HashOptions {
+            strip_comments,
+            ..self.hash_options(defaults)
+        }
+    }
+
+    /// The mapping's line in a `.doks` file, without its comments.
+    pub fn to_line(&self) -> String {
+        let mut line = format!(
+            "{}|{}|{}|{}|{}",
+            self.id, self.doc_partition, self.code_partition, self.doc_hash, self.code_hash,
+        );
+        let mut extras = Vec::new();
+        if self.normalize_whitespace {
+            extras.push("normalize_whitespace=true".to_string());
+        }
+        if self.strip_comments {
+            extras.push("strip_comments=true".to_string());
+        }
+        if !self.tags.is_empty() {
+            extras.push(format!("tags={}", self.tags.join(",")));
+        }
+        if !self.enabled {
+            extras.push("enabled=false".to_string());
+        }
+        if !self.doc_lines.is_empty() {
+            extras.push(format!("doc_lines={}", self.doc_lines.join(",")));
+        }
+        if !self.code_lines.is_empty() {
+            extras.push(format!("code_lines={}", self.code_lines.join(",")));
+        }
+        if let Some(created_at) = &self.created_at {
+            extras.push(format!("created_at={}", created_at));
+        }
+        if let Some(updated_at) = &self.updated_at {
+            extras.push(format!("updated_at={}", updated_at));
+        }
+        if let Some(snippet) = &self.doc_snippet {
+            extras.push(format!("doc_snippet={}", escape_field(snippet)));
+        }
+        if let Some(snippet) = &self.code_snippet {
+            extras.push(format!("code_snippet={}", escape_field(snippet)));
+        }
+        // The description field is only needed when it, or a
+        // key=value field after it, has something to say
+        if self.description.is_some() || !extras.is_empty() {
+            let description = self.description.as_deref().unwrap_or("");
+            line.push_str(&format!("|{}", escape_field(description)));
+        }
+        for extra in extras {
+            line.push('|');
+            line.push_str(&extra);
+        }
+        line
+    }
+
+    /// Parses a mapping line as written by `to_line`:
+    /// `id|doc_partition|code_partition|doc_hash|code_hash|description`,
+    /// followed by optional `key=value` fields. Comments are left empty.
+    pub fn from_line(line: &str) -> Result<Self> {
+        let parts = split_fields(line);
+        if parts.len() < 5 {
+            return Err(DoksError::InvalidConfig(format!(
+                "Invalid mapping line: {} (expected at least 5 parts)",
+                line
+            )));
+        }
+
+        let description = if parts.len() > 5 && !parts[5].trim().is_empty() {
+            Some(unescape_description(parts[5].trim()))
+        } else {
+            None
+        };
+
+        let mut mapping = Mapping {
+            id: parts[0].trim().to_string(),
+            doc_partition: parts[1].trim().to_string(),
+            code_partition: parts[2].trim().to_string(),
+            doc_hash: parts[3].trim().to_string(),
+            code_hash: parts[4].trim().to_string(),
+            description,
+            normalize_whitespace: false,
+            strip_comments: false,
+            tags: Vec::new(),
+            doc_snippet: None,
+            code_snippet: None,
+            doc_lines: Vec::new(),
+            code_lines: Vec::new(),
+            comments: Vec::new(),
+            created_at: None,
+            updated_at: None,
+            enabled: true,
+        };
+
+        // Optional trailing key=value fields
+        for field in parts.iter().skip(6) {
+            let field = field.trim();
+            if field.is_empty() {
+                continue;
+            }
+            match field.split_once('=') {
+                Some(("normalize_whitespace", value)) => {
+                    mapping.normalize_whitespace = value.parse().map_err(|_| {
+                        DoksError::InvalidConfig(format!(
+                            "Invalid normalize_whitespace value: {}",
+                            value
+                        ))
+                    })?;
+                }
+                Some(("strip_comments", value)) => {
+                    mapping.strip_comments = value.parse().map_err(|_| {
+                        DoksError::InvalidConfig(format!("Invalid strip_comments value: {}", value))
+                    })?;
+                }
+                Some(("tags", value)) => mapping.tags = parse_tags(value)?,
+                Some(("enabled", value)) => {
+                    mapping.enabled = value.parse().map_err(|_| {
+                        DoksError::InvalidConfig(format!("Invalid enabled value: {}", value))
+                    })?;
+                }
+                Some(("doc_lines", value)) => mapping.doc_lines = split_digests(value),
+                Some(("code_lines", value)) => mapping.code_lines = split_digests(value),
+                Some(("created_at", value)) => {
+                    mapping.created_at = Some(parse_timestamp(value, &mapping.id)?)
+                }
+                Some(("updated_at", value)) => {
+                    mapping.updated_at = Some(parse_timestamp(value, &mapping.id)?)
+                }
+                Some(("doc_snippet", value)) => {
+                    mapping.doc_snippet = Some(unescape_description(value))
+                }
+                Some(("code_snippet", value)) => {
+                    mapping.code_snippet = Some(unescape_description(value))
+                }
+                _ => {
+                    return Err(DoksError::InvalidConfig(format!(
+                        "Unknown field '{}' in mapping {}",
+                        field, mapping.id
+                    )))
+                }
+            }
+        }
+
+        Ok(mapping)
+    }
+
+    /// Canonical position of the mapping: by documentation file and the line
+    /// its partition starts on, then likewise for code, then by id. Comments
+    /// above a mapping move with it.
+    pub fn sort_key(&self) -> (PartitionKey, PartitionKey, String) {
+        (
+            PartitionKey::of(&self.doc_partition),
+            PartitionKey::of(&self.code_partition),
+            self.id.clone(),
+        )
+    }
+
+    /// Records the hash and line digests of the current documentation content,
+    /// and its snippet if the mapping stores one. The mapping is only marked
+    /// as updated when any of them changed.
+    pub fn rehash_doc(&mut self, content: &str, options: HashOptions) {
+        let changed = rehash_side(
+            &mut self.doc_hash,
+            &mut self.doc_lines,
+            &mut self.doc_snippet,
+            content,
+            options,
+        );
+        if changed {
+            self.touch();
+        }
+    }
+
+    /// Records the hash and line digests of the current code content, and
+    /// its snippet if the mapping stores one. The mapping is only marked as
+    /// updated when any of them changed.
+    pub fn rehash_code(&mut self, content: &str, options: HashOptions) {
+        let changed = rehash_side(
+            &mut self.code_hash,
+            &mut self.code_lines,
+            &mut self.code_snippet,
+            content,
+            options,
+        );
+        if changed {
+            self.touch();
+        }
+    }
+
+    /// Marks the mapping as updated now.
+    pub fn touch(&mut self) {
+        self.updated_at = Some(timestamp_now());
+    }
+
+    /// When the mapping was last updated, falling back to when it was added.
+    /// `None` for mappings without timestamps.
+    pub fn last_updated(&self) -> Option<OffsetDateTime> {
+        let timestamp = self.updated_at.as_deref().or(self.created_at.as_deref())?;
+        OffsetDateTime::parse(timestamp, &Rfc3339).ok()
+    }
 }
 
 impl DoksConfig {
     pub fn new(default_doc: String) -> Self {
         Self {
             default_doc,
+            hash_options: HashOptions::default(),
+            preview_chars: DEFAULT_PREVIEW_CHARS,
+            sort_mappings: false,
             mappings: Vec::new(),
+            trailing_comments: Vec::new(),
+            root: PathBuf::new(),
+            doc_base: None,
+            code_base: None,
+            clamp_ranges: false,
+            parallelism: None,
         }
     }
 
+    /// Directory documentation partitions resolve from.
+    pub fn doc_root(&self) -> &Path {
+        self.doc_base.as_deref().unwrap_or(&self.root)
+    }
+
+    /// Directory code partitions resolve from.
+    pub fn code_root(&self) -> &Path {
+        self.code_base.as_deref().unwrap_or(&self.root)
+    }
+
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let content = std::fs::read_to_string(path)?;
-        Self::parse(&content)
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| DoksError::io(path.display().to_string(), e))?;
+        let mut config = Self::parse(&content)?;
+        config.root = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        Ok(config)
+    }
+
+    /// Like `from_file`, but loads mappings with duplicate ids instead of
+    /// rejecting them, so that they can be reported.
+    pub fn from_file_allowing_duplicates<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| DoksError::io(path.display().to_string(), e))?;
+        let mut config = Self::parse_allowing_duplicates(&content)?;
+        config.root = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        Ok(config)
     }
 
     pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
-        let content = self.to_string();
-        std::fs::write(path, content)?;
-        Ok(())
+        let path = path.as_ref();
+        std::fs::write(path, self.to_string())
+            .map_err(|e| DoksError::io(path.display().to_string(), e))
     }
 
     pub fn parse(content: &str) -> Result<Self> {
-        let mut default_doc = String::new();
-        let mut mappings = Vec::new();
+        let config = Self::parse_allowing_duplicates(content)?;
+
+        let duplicates = duplicate_ids(&config.mappings);
+        if !duplicates.is_empty() {
+            return Err(DoksError::InvalidConfig(format!(
+                "Duplicate mapping id(s) in .doks file: {}",
+                duplicates.join(", ")
+            )));
+        }
+
+        Ok(config)
+    }
+
+    fn parse_allowing_duplicates(content: &str) -> Result<Self> {
+        let mut config = Self::new(String::new());
+        let mut mappings: Vec<Mapping> = Vec::new();
+        let mut comments = Vec::new();
 
         for line in content.lines() {
             let line = line.trim();
 
-            if line.is_empty() || line.starts_with('#') {
+            if line.starts_with(HEADER_COMMENT) || line.starts_with(FORMAT_COMMENT) {
                 continue;
             }
-
-            if line.starts_with("default_doc=") {
-                default_doc = line.strip_prefix("default_doc=").unwrap().to_string();
-            } else if line.contains('|') {
-                // Parse mapping line: id|doc_partition|code_partition|doc_hash|code_hash|description
-                let parts: Vec<&str> = line.split('|').collect();
-                if parts.len() < 5 {
-                    return Err(anyhow!(
-                        "Invalid mapping line: {} (expected at least 5 parts)",
-                        line
-                    ));
+            if line.starts_with('#') {
+                comments.push(line.to_string());
+                continue;
+            }
+            if line.is_empty() {
+                // Blank lines only matter once they separate user content
+                if !comments.is_empty() || !mappings.is_empty() {
+                    comments.push(String::new());
                 }
+                continue;
+            }
 
-                let description = if parts.len() > 5 && !parts[5].trim().is_empty() {
-                    Some(parts[5].trim().to_string())
-                } else {
-                    None
-                };
-
-                mappings.push(Mapping {
-                    id: parts[0].trim().to_string(),
-                    doc_partition: parts[1].trim().to_string(),
-                    code_partition: parts[2].trim().to_string(),
-                    doc_hash: parts[3].trim().to_string(),
-                    code_hash: parts[4].trim().to_string(),
-                    description,
-                });
+            if line.starts_with("version=") {
+                // Written by early releases; the compact format is unversioned
+                continue;
+            }
+            let header = line
+                .split_once('=')
+                .filter(|(key, _)| HEADER_KEYS.contains(key));
+            if let Some((key, value)) = header {
+                config.set_header(key, value)?;
+            } else if line.contains('|') {
+                let mut mapping = Mapping::from_line(line)?;
+                mapping.comments = std::mem::take(&mut comments);
+                mappings.push(mapping);
             }
         }
 
-        if default_doc.is_empty() {
-            return Err(anyhow!("Missing default_doc in .doks file"));
+        if config.default_doc.is_empty() {
+            return Err(DoksError::InvalidConfig(
+                "Missing default_doc in .doks file".to_string(),
+            ));
         }
 
-        Ok(Self {
-            default_doc,
-            mappings,
+        while comments.last().is_some_and(|line| line.is_empty()) {
+            comments.pop();
+        }
+
+        config.mappings = mappings;
+        config.trailing_comments = comments;
+        Ok(config)
+    }
+
+    /// The value of header field `key` (one of [`HEADER_KEYS`]) as it would
+    /// be written in `.doks`, defaults included.
+    pub fn header(&self, key: &str) -> Result<String> {
+        Ok(match key {
+            "default_doc" => self.default_doc.clone(),
+            "hash_algo" => self.hash_options.algorithm.to_string(),
+            "normalize_line_endings" => self.hash_options.normalize_line_endings.to_string(),
+            "preview_chars" => self.preview_chars.to_string(),
+            "sort_mappings" => self.sort_mappings.to_string(),
+            _ => return Err(unknown_header(key)),
         })
     }
 
+    /// Sets header field `key` (one of [`HEADER_KEYS`]) from its text in
+    /// `.doks`, rejecting values that file couldn't be parsed with, such as
+    /// an empty `default_doc` or one spanning several lines.
+    pub fn set_header(&mut self, key: &str, value: &str) -> Result<()> {
+        let value = value.trim();
+        let invalid = || DoksError::InvalidConfig(format!("Invalid {} value: {}", key, value));
+        match key {
+            "default_doc" if value.is_empty() => {
+                return Err(DoksError::InvalidConfig(
+                    "default_doc can't be empty".to_string(),
+                ))
+            }
+            "default_doc" if value.contains(['\n', '\r']) => {
+                return Err(DoksError::InvalidConfig(
+                    "default_doc can't contain line breaks".to_string(),
+                ))
+            }
+            "default_doc" => self.default_doc = value.to_string(),
+            "hash_algo" => self.hash_options.algorithm = value.parse()?,
+            "normalize_line_endings" => {
+                self.hash_options.normalize_line_endings = value.parse().map_err(|_| invalid())?
+            }
+            "preview_chars" => self.preview_chars = value.parse().map_err(|_| invalid())?,
+            "sort_mappings" => self.sort_mappings = value.parse().map_err(|_| invalid())?,
+            _ => return Err(unknown_header(key)),
+        }
+        Ok(())
+    }
+
     #[allow(clippy::inherent_to_string)]
     pub fn to_string(&self) -> String {
         let mut content = String::new();
 
-        content.push_str("# .doks - Mapping doks to code \n");
+        content.push_str(HEADER_COMMENT);
+        content.push_str(" \n");
         content.push_str(&format!("default_doc={}\n", self.default_doc));
+        content.push_str(&format!("hash_algo={}\n", self.hash_options.algorithm));
+        if !self.hash_options.normalize_line_endings {
+            content.push_str("normalize_line_endings=false\n");
+        }
+        if self.preview_chars != DEFAULT_PREVIEW_CHARS {
+            content.push_str(&format!("preview_chars={}\n", self.preview_chars));
+        }
+        if self.sort_mappings {
+            content.push_str("sort_mappings=true\n");
+        }
         content.push('\n');
 
         if !self.mappings.is_empty() {
-            content.push_str(
-                "# Format: id|doc_partition|code_partition|doc_hash|code_hash|description\n",
-            );
-
-            for mapping in &self.mappings {
-                let description = mapping.description.as_deref().unwrap_or("");
-                content.push_str(&format!(
-                    "{}|{}|{}|{}|{}|{}\n",
-                    mapping.id,
-                    mapping.doc_partition,
-                    mapping.code_partition,
-                    mapping.doc_hash,
-                    mapping.code_hash,
-                    description
-                ));
+            content.push_str(FORMAT_COMMENT);
+            content.push('\n');
+
+            let mut mappings: Vec<&Mapping> = self.mappings.iter().collect();
+            if self.sort_mappings {
+                mappings.sort_by_cached_key(|mapping| mapping.sort_key());
+            }
+            for mapping in mappings {
+                for comment in &mapping.comments {
+                    content.push_str(comment);
+                    content.push('\n');
+                }
+                content.push_str(&mapping.to_line());
+                content.push('\n');
             }
         }
 
+        for comment in &self.trailing_comments {
+            content.push_str(comment);
+            content.push('\n');
+        }
+
         content
     }
 
     pub fn find_doks_file() -> Option<std::path::PathBuf> {
-        let mut current = std::env::current_dir().ok()?;
+        Self::find_doks_file_from(&std::env::current_dir().ok()?)
+    }
+
+    /// The nearest `.doks` file, searching upward from `start`.
+    pub fn find_doks_file_from(start: &Path) -> Option<std::path::PathBuf> {
+        let mut current = start.to_path_buf();
         loop {
             let doks_path = current.join(DOKS_FILE_NAME);
             if doks_path.exists() {
@@ -136,15 +601,193 @@ impl DoksConfig {
         self.mappings.push(mapping);
     }
 
-    #[allow(dead_code)]
+    /// Changes the id of the mapping at `index`, refusing ids that are empty,
+    /// contain the `|` delimiter, or belong to another mapping.
+    pub fn rename_mapping(&mut self, index: usize, new_id: &str) -> Result<()> {
+        let new_id = new_id.trim();
+        if new_id.is_empty() {
+            return Err(DoksError::InvalidConfig(
+                "Mapping id cannot be empty".to_string(),
+            ));
+        }
+        if new_id.contains('|') {
+            return Err(DoksError::InvalidConfig(
+                "Mapping id cannot contain '|'".to_string(),
+            ));
+        }
+        if self
+            .mappings
+            .iter()
+            .enumerate()
+            .any(|(i, m)| i != index && m.id == new_id)
+        {
+            return Err(DoksError::InvalidConfig(format!(
+                "A mapping with id '{}' already exists",
+                new_id
+            )));
+        }
+
+        self.mappings[index].id = new_id.to_string();
+        self.mappings[index].touch();
+        Ok(())
+    }
+
     pub fn find_mapping_by_id(&mut self, id: &str) -> Option<&mut Mapping> {
         self.mappings.iter_mut().find(|m| m.id == id)
     }
 }
 
+/// Returns each id that appears more than once, in order of first repetition.
+pub fn duplicate_ids(mappings: &[Mapping]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut duplicates = Vec::new();
+    for mapping in mappings {
+        if !seen.insert(mapping.id.as_str()) && !duplicates.contains(&mapping.id) {
+            duplicates.push(mapping.id.clone());
+        }
+    }
+    duplicates
+}
+
+/// Checks that a `created_at`/`updated_at` value is an RFC 3339 timestamp.
+fn parse_timestamp(value: &str, id: &str) -> Result<String> {
+    OffsetDateTime::parse(value, &Rfc3339).map_err(|_| {
+        DoksError::InvalidConfig(format!(
+            "Invalid timestamp '{}' in mapping {} (expected RFC 3339)",
+            value, id
+        ))
+    })?;
+    Ok(value.to_string())
+}
+
+fn split_digests(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|digest| !digest.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// The tags in a comma-separated list, trimmed, without empty entries or
+/// repeats. Tags can't contain whitespace, `|` or `=`, which would break the
+/// mapping line.
+pub fn parse_tags(value: &str) -> Result<Vec<String>> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in value
+        .split(',')
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+    {
+        if tag.contains(|c: char| c.is_whitespace() || c == '|' || c == '=') {
+            return Err(DoksError::InvalidConfig(format!(
+                "Invalid tag '{}': tags can't contain whitespace, '|' or '='",
+                tag
+            )));
+        }
+        if !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    Ok(tags)
+}
+
+/// Stores the hash, line digests and, if one is kept, snippet of `content`
+/// for one side of a mapping. Returns whether any of them changed.
+fn rehash_side(
+    hash: &mut String,
+    lines: &mut Vec<String>,
+    stored_snippet: &mut Option<String>,
+    content: &str,
+    options: HashOptions,
+) -> bool {
+    let new_hash = hash_content(content, options);
+    let new_lines = line_digests(content, options);
+    let new_snippet = stored_snippet.as_ref().and_then(|_| snippet(content));
+    let changed = *hash != new_hash || *lines != new_lines || *stored_snippet != new_snippet;
+    *hash = new_hash;
+    *lines = new_lines;
+    *stored_snippet = new_snippet;
+    changed
+}
+
+fn unknown_header(key: &str) -> DoksError {
+    DoksError::InvalidConfig(format!(
+        "Unknown header '{}' (expected one of {})",
+        key,
+        HEADER_KEYS.join(", ")
+    ))
+}
+
+/// Splits a mapping line on `|`, keeping `\|`-escaped delimiters inside their field.
+fn split_fields(line: &str) -> Vec<&str> {
+    let mut fields = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '|' => {
+                fields.push(&line[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    fields.push(&line[start..]);
+    fields
+}
+
+/// `content` as an expected snippet, or `None` when it's over
+/// [`MAX_SNIPPET_BYTES`].
+pub fn snippet(content: &str) -> Option<String> {
+    (content.len() <= MAX_SNIPPET_BYTES).then(|| content.to_string())
+}
+
+/// `value` escaped for a free-text field of a mapping line, where `|` would
+/// otherwise end the field.
+fn escape_field(value: &str) -> String {
+    escape_description(value).replace('|', "\\|")
+}
+
+/// `description` on a single line: backslashes are doubled and line breaks
+/// written as `\n` (and `\r`), as in `.doks` and the `edit` prompt.
+pub fn escape_description(description: &str) -> String {
+    description
+        .replace('\\', "\\\\")
+        .replace('\r', "\\r")
+        .replace('\n', "\\n")
+}
+
+/// Reverses [`escape_description`], also unescaping `\|`. Backslashes before
+/// any other character are kept as they are.
+pub fn unescape_description(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some(next @ ('\\' | '|')) => result.push(next),
+                Some('n') => result.push('\n'),
+                Some('r') => result.push('\r'),
+                Some(next) => {
+                    result.push(c);
+                    result.push(next);
+                }
+                None => result.push(c),
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::hash::{hash_content, verify_hash, HashAlgorithm};
     use std::fs;
     use tempfile::tempdir;
 
@@ -156,6 +799,17 @@ mod tests {
             doc_hash: "abc123".to_string(),
             code_hash: "def456".to_string(),
             description: Some("Test mapping".to_string()),
+            normalize_whitespace: false,
+            strip_comments: false,
+            tags: Vec::new(),
+            doc_snippet: None,
+            code_snippet: None,
+            doc_lines: Vec::new(),
+            code_lines: Vec::new(),
+            comments: Vec::new(),
+            created_at: None,
+            updated_at: None,
+            enabled: true,
         }
     }
 
@@ -236,6 +890,20 @@ mod tests {
         std::env::set_current_dir(original_dir).unwrap();
     }
 
+    #[test]
+    fn test_find_doks_file_from() {
+        let dir = tempdir().unwrap();
+        let nested = dir.path().join("a/b");
+        fs::create_dir_all(&nested).unwrap();
+        assert!(DoksConfig::find_doks_file_from(&nested).is_none());
+
+        fs::write(dir.path().join(DOKS_FILE_NAME), "default_doc=README.md\n").unwrap();
+        assert_eq!(
+            DoksConfig::find_doks_file_from(&nested),
+            Some(dir.path().join(DOKS_FILE_NAME))
+        );
+    }
+
     #[test]
     fn test_serialization_format() {
         let dir = tempdir().unwrap();
@@ -308,6 +976,505 @@ test-2|docs/api.md:5-10|src/lib.rs:1-10|fedcba|654321|
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_normalize_line_endings_header() {
+        let config = DoksConfig::parse("default_doc=README.md\n").unwrap();
+        assert!(config.hash_options.normalize_line_endings);
+        assert!(!config.to_string().contains("normalize_line_endings"));
+
+        let content = "default_doc=README.md\nnormalize_line_endings=false\n";
+        let config = DoksConfig::parse(content).unwrap();
+        assert!(!config.hash_options.normalize_line_endings);
+
+        let reparsed = DoksConfig::parse(&config.to_string()).unwrap();
+        assert!(!reparsed.hash_options.normalize_line_endings);
+
+        assert!(DoksConfig::parse("default_doc=README.md\nnormalize_line_endings=maybe").is_err());
+    }
+
+    #[test]
+    fn test_hash_algo_header() {
+        let config = DoksConfig::parse("default_doc=README.md").unwrap();
+        assert_eq!(config.hash_options.algorithm, HashAlgorithm::Blake3);
+        assert!(config.to_string().contains("hash_algo=blake3\n"));
+
+        let content = "default_doc=README.md\nhash_algo=sha256\n";
+        let config = DoksConfig::parse(content).unwrap();
+        assert_eq!(config.hash_options.algorithm, HashAlgorithm::Sha256);
+
+        let reparsed = DoksConfig::parse(&config.to_string()).unwrap();
+        assert_eq!(reparsed.hash_options.algorithm, HashAlgorithm::Sha256);
+
+        assert!(DoksConfig::parse("default_doc=README.md\nhash_algo=md5").is_err());
+    }
+
+    #[test]
+    fn test_sha256_config_verifies() {
+        let content = "line one\nline two";
+        let sha256 = HashOptions {
+            algorithm: HashAlgorithm::Sha256,
+            ..HashOptions::default()
+        };
+        let doks = format!(
+            "default_doc=README.md\nhash_algo=sha256\n\nid|README.md:1|src/main.rs:1|{}|{}|\n",
+            hash_content(content, sha256),
+            hash_content(content, sha256)
+        );
+
+        let config = DoksConfig::parse(&doks).unwrap();
+        let mapping = &config.mappings[0];
+        let options = mapping.hash_options(config.hash_options);
+        assert!(verify_hash(content, &mapping.doc_hash, options));
+        assert!(verify_hash(content, &mapping.code_hash, options));
+        assert!(!verify_hash(
+            content,
+            &mapping.doc_hash,
+            mapping.hash_options(HashOptions::default())
+        ));
+    }
+
+    #[test]
+    fn test_description_with_pipes() {
+        for description in [
+            "returns Ok | Err",
+            "a | b | c",
+            "trailing pipe |",
+            "path\\to|x",
+        ] {
+            let mut config = DoksConfig::new("README.md".to_string());
+            let mut mapping = create_test_mapping();
+            mapping.description = Some(description.to_string());
+            config.add_mapping(mapping);
+
+            let serialized = config.to_string();
+            let parsed = DoksConfig::parse(&serialized).unwrap();
+            assert_eq!(parsed.mappings[0].description.as_deref(), Some(description));
+            assert_eq!(parsed.mappings[0].code_hash, "def456");
+        }
+    }
+
+    #[test]
+    fn test_short_id() {
+        let mut mapping = create_test_mapping();
+        mapping.id = "0123456789abcdef".to_string();
+        assert_eq!(mapping.short_id(), "01234567");
+        mapping.id = "abc".to_string();
+        assert_eq!(mapping.short_id(), "abc");
+        mapping.id = "äöüßéèêëï".to_string();
+        assert_eq!(mapping.short_id(), "äöüßéèêë");
+    }
+
+    #[test]
+    fn test_snippets_round_trip() {
+        let code = "fn main() {\n    let a = b | c; // \\n\n}";
+        let mut config = DoksConfig::new("README.md".to_string());
+        let mut mapping = create_test_mapping();
+        mapping.doc_snippet = Some("# Usage".to_string());
+        mapping.code_snippet = Some(code.to_string());
+        config.add_mapping(mapping);
+
+        let serialized = config.to_string();
+        assert_eq!(serialized.lines().last().unwrap().lines().count(), 1);
+        assert!(serialized.contains("|doc_snippet=# Usage|code_snippet=fn main() {\\n"));
+
+        let parsed = DoksConfig::parse(&serialized).unwrap();
+        assert_eq!(parsed.mappings[0].doc_snippet.as_deref(), Some("# Usage"));
+        assert_eq!(parsed.mappings[0].code_snippet.as_deref(), Some(code));
+        assert_eq!(parsed.to_string(), serialized);
+
+        // Re-hashing keeps a stored snippet in step, and never adds one
+        let mut mapping = parsed.mappings[0].clone();
+        mapping.rehash_code("fn main() {}", HashOptions::default());
+        assert_eq!(mapping.code_snippet.as_deref(), Some("fn main() {}"));
+        mapping.rehash_doc(&"x".repeat(MAX_SNIPPET_BYTES + 1), HashOptions::default());
+        assert_eq!(mapping.doc_snippet, None);
+        mapping.rehash_doc("# Usage", HashOptions::default());
+        assert_eq!(mapping.doc_snippet, None);
+    }
+
+    #[test]
+    fn test_tags_round_trip() {
+        let mut config = DoksConfig::new("README.md".to_string());
+        let mut mapping = create_test_mapping();
+        mapping.tags = vec!["auth".to_string(), "api-v2".to_string()];
+        config.add_mapping(mapping);
+        let mut untagged = create_test_mapping();
+        untagged.id = "untagged".to_string();
+        config.add_mapping(untagged);
+
+        let serialized = config.to_string();
+        assert!(serialized.contains("|tags=auth,api-v2"));
+        assert_eq!(serialized.matches("tags=").count(), 1);
+
+        let parsed = DoksConfig::parse(&serialized).unwrap();
+        assert_eq!(parsed.mappings[0].tags, vec!["auth", "api-v2"]);
+        assert!(parsed.mappings[1].tags.is_empty());
+        assert_eq!(parsed.to_string(), serialized);
+
+        assert_eq!(
+            parse_tags(" billing, ,auth,billing ").unwrap(),
+            vec!["billing", "auth"]
+        );
+        assert!(parse_tags("").unwrap().is_empty());
+        assert!(parse_tags("two words").is_err());
+        assert!(parse_tags("a=b").is_err());
+    }
+
+    #[test]
+    fn test_multiline_description() {
+        let description = "Logs the user in.\nFails with | on bad input, see C:\\path\\n.";
+        let mut config = DoksConfig::new("README.md".to_string());
+        let mut mapping = create_test_mapping();
+        mapping.description = Some(description.to_string());
+        config.add_mapping(mapping);
+
+        let serialized = config.to_string();
+        let line = serialized.lines().last().unwrap();
+        assert!(line
+            .ends_with("|Logs the user in.\\nFails with \\| on bad input, see C:\\\\path\\\\n."));
+
+        let parsed = DoksConfig::parse(&serialized).unwrap();
+        assert_eq!(parsed.mappings.len(), 1);
+        assert_eq!(parsed.mappings[0].description.as_deref(), Some(description));
+
+        assert_eq!(
+            unescape_description(&escape_description("a\r\nb\\n")),
+            "a\r\nb\\n"
+        );
+        assert_eq!(unescape_description("C:\\temp"), "C:\\temp");
+    }
+
+    #[test]
+    fn test_parse_duplicate_ids() {
+        let content = r#"
+default_doc=README.md
+dup-id|README.md:1|src/main.rs:1|abc|def|First
+other-id|README.md:2|src/main.rs:2|abc|def|
+dup-id|README.md:3|src/main.rs:3|abc|def|Second
+"#;
+
+        let err = DoksConfig::parse(content).unwrap_err().to_string();
+        assert!(err.contains("Duplicate mapping id"));
+        assert!(err.contains("dup-id"));
+        assert!(!err.contains("other-id"));
+    }
+
+    #[test]
+    fn test_parse_end_to_end_test_shape() {
+        // The exact shape hand-written by tests/end_to_end_tests.rs
+        let content = r#"# .doks - Mapping doks to code 
+version=0.1.0
+default_doc=README.md
+
+# Format: id|doc_partition|code_partition|doc_hash|code_hash|description
+main-function-example|README.md:11-15|src/main.rs:3-7|abc|def|Main function documentation example"#;
+
+        let config = DoksConfig::parse(content).unwrap();
+        assert_eq!(config.default_doc, "README.md");
+        assert_eq!(config.mappings.len(), 1);
+        assert_eq!(config.mappings[0].id, "main-function-example");
+        assert_eq!(config.mappings[0].doc_partition, "README.md:11-15");
+        assert_eq!(config.mappings[0].code_partition, "src/main.rs:3-7");
+        assert_eq!(
+            config.mappings[0].description.as_deref(),
+            Some("Main function documentation example")
+        );
+        assert!(config.mappings[0].comments.is_empty());
+        assert!(config.trailing_comments.is_empty());
+    }
+
+    #[test]
+    fn test_no_description_omits_trailing_pipe() {
+        let mut config = DoksConfig::new("README.md".to_string());
+        let mut mapping = create_test_mapping();
+        mapping.description = None;
+        config.add_mapping(mapping);
+
+        let serialized = config.to_string();
+        assert!(serialized.contains("|abc123|def456\n"));
+
+        let parsed = DoksConfig::parse(&serialized).unwrap();
+        assert_eq!(parsed.mappings[0].description, None);
+        assert_eq!(parsed.mappings[0].code_hash, "def456");
+
+        // The legacy six-field form still parses to no description
+        let legacy = serialized.replace("|def456\n", "|def456|\n");
+        let parsed = DoksConfig::parse(&legacy).unwrap();
+        assert_eq!(parsed.mappings[0].description, None);
+    }
+
+    #[test]
+    fn test_preview_chars_header() {
+        let config = DoksConfig::parse("default_doc=README.md\n").unwrap();
+        assert_eq!(config.preview_chars, DEFAULT_PREVIEW_CHARS);
+        assert!(!config.to_string().contains("preview_chars"));
+
+        let config = DoksConfig::parse("default_doc=README.md\npreview_chars=0\n").unwrap();
+        assert_eq!(config.preview_chars, 0);
+        let reparsed = DoksConfig::parse(&config.to_string()).unwrap();
+        assert_eq!(reparsed.preview_chars, 0);
+
+        assert!(DoksConfig::parse("default_doc=README.md\npreview_chars=lots\n").is_err());
+    }
+
+    #[test]
+    fn test_line_digests_round_trip() {
+        let mut config = DoksConfig::new("README.md".to_string());
+        let mut mapping = create_test_mapping();
+        mapping.description = None;
+        mapping.rehash_code("fn a() {}\nfn b() {}", HashOptions::default());
+        mapping.rehash_doc("One line", HashOptions::default());
+        config.add_mapping(mapping);
+
+        let serialized = config.to_string();
+        assert!(serialized.contains("||code_lines="));
+        assert!(!serialized.contains("doc_lines="));
+
+        let parsed = DoksConfig::parse(&serialized).unwrap();
+        assert_eq!(parsed.mappings[0].code_lines, config.mappings[0].code_lines);
+        assert_eq!(parsed.mappings[0].code_lines.len(), 2);
+        assert!(parsed.mappings[0].doc_lines.is_empty());
+        assert_eq!(parsed.mappings[0].description, None);
+    }
+
+    #[test]
+    fn test_rename_mapping() {
+        let mut config = DoksConfig::new("README.md".to_string());
+        let mut first = create_test_mapping();
+        first.id = "first".to_string();
+        let mut second = create_test_mapping();
+        second.id = "second".to_string();
+        config.add_mapping(first);
+        config.add_mapping(second);
+
+        config.rename_mapping(0, " renamed ").unwrap();
+        assert_eq!(config.mappings[0].id, "renamed");
+
+        // Keeping the current id is not a collision
+        config.rename_mapping(0, "renamed").unwrap();
+
+        let err = config.rename_mapping(0, "second").unwrap_err().to_string();
+        assert!(err.contains("already exists"));
+        assert!(config.rename_mapping(0, "  ").is_err());
+        assert!(config.rename_mapping(0, "a|b").is_err());
+        assert_eq!(config.mappings[0].id, "renamed");
+
+        let parsed = DoksConfig::parse(&config.to_string()).unwrap();
+        assert_eq!(parsed.mappings[0].id, "renamed");
+    }
+
+    #[test]
+    fn test_normalize_whitespace_round_trip() {
+        let mut config = DoksConfig::new("README.md".to_string());
+        let mut mapping = create_test_mapping();
+        mapping.description = None;
+        mapping.normalize_whitespace = true;
+        config.add_mapping(mapping);
+
+        let serialized = config.to_string();
+        assert!(serialized.contains("|def456||normalize_whitespace=true"));
+
+        let parsed = DoksConfig::parse(&serialized).unwrap();
+        assert!(parsed.mappings[0].normalize_whitespace);
+        assert_eq!(parsed.mappings[0].description, None);
+        assert!(
+            parsed.mappings[0]
+                .hash_options(parsed.hash_options)
+                .normalize_whitespace
+        );
+
+        let content = "default_doc=README.md\nid|a.md|b.rs|abc|def||bogus=1\n";
+        assert!(DoksConfig::parse(content).is_err());
+    }
+
+    #[test]
+    fn test_sorted_mappings_ignore_insertion_order() {
+        let mapping = |id: &str, doc: &str, code: &str| Mapping {
+            id: id.to_string(),
+            doc_partition: doc.to_string(),
+            code_partition: code.to_string(),
+            ..create_test_mapping()
+        };
+        let mut commented = mapping("guide-mapping", "docs/guide.md:@@intro@@", "src/a.rs:1");
+        commented.comments = vec!["# Guide".to_string()];
+        let mappings = [
+            mapping("late-mapping", "README.md:20", "src/a.rs:1"),
+            mapping("b-side-mapping", "README.md:3", "src/b.rs:1"),
+            commented,
+            mapping("a-side-mapping", "README.md:3", "src/a.rs:5"),
+            mapping("tail-mapping", "README.md:-5..", "src/a.rs:1"),
+        ];
+
+        let serialize = |order: &[usize], sort_mappings: bool| {
+            let mut config = DoksConfig::new("README.md".to_string());
+            config.sort_mappings = sort_mappings;
+            for &index in order {
+                config.add_mapping(mappings[index].clone());
+            }
+            config.to_string()
+        };
+
+        let sorted = serialize(&[0, 1, 2, 3, 4], true);
+        assert_eq!(sorted, serialize(&[4, 3, 2, 1, 0], true));
+        assert_eq!(sorted, serialize(&[2, 0, 4, 1, 3], true));
+        assert!(sorted.contains("sort_mappings=true\n"));
+
+        let parsed = DoksConfig::parse(&sorted).unwrap();
+        assert!(parsed.sort_mappings);
+        let ids: Vec<&str> = parsed.mappings.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            [
+                "a-side-mapping",
+                "b-side-mapping",
+                "late-mapping",
+                "tail-mapping",
+                "guide-mapping"
+            ]
+        );
+        assert_eq!(parsed.mappings[4].comments, vec!["# Guide".to_string()]);
+
+        // Without the setting, insertion order is kept
+        assert_ne!(
+            serialize(&[0, 1, 2, 3, 4], false),
+            serialize(&[4, 3, 2, 1, 0], false)
+        );
+    }
+
+    #[test]
+    fn test_strip_comments_round_trip() {
+        let mut config = DoksConfig::new("README.md".to_string());
+        let mut mapping = create_test_mapping();
+        mapping.strip_comments = true;
+        config.add_mapping(mapping);
+
+        let serialized = config.to_string();
+        assert!(serialized.contains("|strip_comments=true"));
+
+        let parsed = DoksConfig::parse(&serialized).unwrap();
+        let mapping = &parsed.mappings[0];
+        assert!(mapping.strip_comments);
+        // Only the code side strips comments, in its file's syntax
+        assert_eq!(
+            mapping.hash_options(parsed.hash_options).strip_comments,
+            None
+        );
+        assert_eq!(
+            mapping
+                .code_hash_options(parsed.hash_options)
+                .strip_comments,
+            Some(CommentSyntax::Rust)
+        );
+    }
+
+    #[test]
+    fn test_enabled_round_trip() {
+        let mut config = DoksConfig::new("README.md".to_string());
+        config.add_mapping(create_test_mapping());
+        assert!(!config.to_string().contains("enabled="));
+
+        config.mappings[0].enabled = false;
+        let serialized = config.to_string();
+        assert!(serialized.contains("|Test mapping|enabled=false"));
+        let parsed = DoksConfig::parse(&serialized).unwrap();
+        assert!(!parsed.mappings[0].enabled);
+        assert_eq!(parsed.mappings[0].doc_hash, config.mappings[0].doc_hash);
+
+        let content = "default_doc=README.md\nid|a.md|b.rs|abc|def||enabled=maybe\n";
+        assert!(DoksConfig::parse(content).is_err());
+    }
+
+    #[test]
+    fn test_timestamps_round_trip() {
+        let mut config = DoksConfig::new("README.md".to_string());
+        let mut mapping = create_test_mapping();
+        mapping.created_at = Some("2024-01-02T03:04:05Z".to_string());
+        mapping.updated_at = Some("2024-06-07T08:09:10+02:00".to_string());
+        config.add_mapping(mapping);
+
+        let serialized = config.to_string();
+        assert!(serialized.contains(
+            "|Test mapping|created_at=2024-01-02T03:04:05Z|updated_at=2024-06-07T08:09:10+02:00"
+        ));
+
+        let parsed = DoksConfig::parse(&serialized).unwrap();
+        let mapping = &parsed.mappings[0];
+        assert_eq!(mapping.created_at.as_deref(), Some("2024-01-02T03:04:05Z"));
+        assert_eq!(
+            mapping.updated_at.as_deref(),
+            Some("2024-06-07T08:09:10+02:00")
+        );
+        assert_eq!(
+            mapping.last_updated().unwrap().unix_timestamp(),
+            1_717_740_550
+        );
+
+        // Files written before timestamps existed still load
+        let legacy = DoksConfig::parse("default_doc=README.md\nid|a.md|b.rs|abc|def\n").unwrap();
+        assert_eq!(legacy.mappings[0].created_at, None);
+        assert_eq!(legacy.mappings[0].last_updated(), None);
+
+        let content = "default_doc=README.md\nid|a.md|b.rs|abc|def||created_at=yesterday\n";
+        assert!(DoksConfig::parse(content).is_err());
+    }
+
+    #[test]
+    fn test_rehash_updates_timestamp() {
+        let mut mapping = create_test_mapping();
+        mapping.created_at = Some("2024-01-02T03:04:05Z".to_string());
+        mapping.updated_at = mapping.created_at.clone();
+
+        mapping.rehash_code("fn main() {}", HashOptions::default());
+
+        assert_eq!(mapping.created_at.as_deref(), Some("2024-01-02T03:04:05Z"));
+        let updated_at = mapping.updated_at.unwrap();
+        assert!(updated_at.as_str() > "2024-01-02T03:04:05Z");
+        assert!(OffsetDateTime::parse(&updated_at, &Rfc3339).is_ok());
+
+        mapping.updated_at = mapping.created_at.clone();
+        mapping.rehash_code("fn main() {}", HashOptions::default());
+        assert_eq!(mapping.updated_at.as_deref(), Some("2024-01-02T03:04:05Z"));
+    }
+
+    #[test]
+    fn test_comments_round_trip() {
+        let content = "# .doks - Mapping doks to code \n\
+default_doc=README.md\n\
+\n\
+# Format: id|doc_partition|code_partition|doc_hash|code_hash|description\n\
+# Authentication\n\
+auth|README.md:1|src/auth.rs:1|a|b|Login\n\
+\n\
+# Billing\n\
+# (see docs/billing.md)\n\
+billing|README.md:2|src/billing.rs:1|c|d|Invoices\n\
+plain|README.md:3|src/main.rs:1|e|f|\n\
+\n\
+# TODO: map the CLI flags\n\
+\n";
+
+        let config = DoksConfig::parse(content).unwrap();
+        assert_eq!(config.mappings[0].comments, vec!["# Authentication"]);
+        assert_eq!(
+            config.mappings[1].comments,
+            vec!["", "# Billing", "# (see docs/billing.md)"]
+        );
+        assert!(config.mappings[2].comments.is_empty());
+        assert_eq!(
+            config.trailing_comments,
+            vec!["", "# TODO: map the CLI flags"]
+        );
+
+        let serialized = config.to_string();
+        assert!(serialized.contains("# Authentication\nauth|"));
+        assert!(serialized.contains("|Login\n\n# Billing\n# (see docs/billing.md)\nbilling|"));
+        assert!(serialized.ends_with("|e|f\n\n# TODO: map the CLI flags\n"));
+        assert_eq!(serialized.matches("# Format:").count(), 1);
+
+        let reparsed = DoksConfig::parse(&serialized).unwrap();
+        assert_eq!(reparsed.to_string(), serialized);
+    }
+
     #[test]
     fn test_empty_description() {
         let mut config = DoksConfig::new("README.md".to_string());
@@ -318,6 +1485,17 @@ test-2|docs/api.md:5-10|src/lib.rs:1-10|fedcba|654321|
             doc_hash: "abc".to_string(),
             code_hash: "def".to_string(),
             description: None,
+            normalize_whitespace: false,
+            strip_comments: false,
+            tags: Vec::new(),
+            doc_snippet: None,
+            code_snippet: None,
+            doc_lines: Vec::new(),
+            code_lines: Vec::new(),
+            comments: Vec::new(),
+            created_at: None,
+            updated_at: None,
+            enabled: true,
         };
         config.add_mapping(mapping);
 
@@ -326,4 +1504,21 @@ test-2|docs/api.md:5-10|src/lib.rs:1-10|fedcba|654321|
 
         assert_eq!(parsed.mappings[0].description, None);
     }
+
+    #[test]
+    fn test_header_get_and_set() {
+        let mut config = DoksConfig::new("README.md".to_string());
+        assert_eq!(config.header("preview_chars").unwrap(), "200");
+        assert_eq!(config.header("sort_mappings").unwrap(), "false");
+
+        config.set_header("default_doc", " docs/guide.md ").unwrap();
+        config.set_header("sort_mappings", "true").unwrap();
+        assert_eq!(config.header("default_doc").unwrap(), "docs/guide.md");
+        assert!(config.sort_mappings);
+
+        assert!(config.set_header("preview_chars", "many").is_err());
+        assert!(config.set_header("hash_algo", "md5").is_err());
+        assert!(config.header("version").is_err());
+        assert_eq!(config.header("preview_chars").unwrap(), "200");
+    }
 }
@@ -0,0 +1,97 @@
+use thiserror::Error;
+
+pub type Result<T, E = DoksError> = std::result::Result<T, E>;
+
+/// Failures from parsing partitions and `.doks` files, reading mapped files
+/// and verifying their hashes. Commands convert these to `anyhow` for display.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum DoksError {
+    /// The partition string doesn't follow the partition format.
+    #[error("{0}")]
+    InvalidPartition(String),
+    /// The file a partition points at doesn't exist. `suggestions` are
+    /// existing files with a similar name, e.g. after a move or a typo.
+    #[error("File not found: {path}{}", did_you_mean(.suggestions))]
+    FileNotFound {
+        path: String,
+        suggestions: Vec<String>,
+    },
+    /// A partition's file path is a glob that matches more than one file.
+    #[error("'{pattern}' matches {} files, not one: {}", .matches.len(), .matches.join(", "))]
+    AmbiguousGlob {
+        pattern: String,
+        matches: Vec<String>,
+    },
+    /// The file exists but couldn't be read, e.g. it isn't valid UTF-8.
+    #[error("Cannot read '{path}': {message}")]
+    Io { path: String, message: String },
+    /// The file is too large to be read whole; see `DOKSNET_MAX_FILE_SIZE`.
+    #[error(
+        "'{path}' is {size} bytes, more than the {limit} bytes read whole (set DOKSNET_MAX_FILE_SIZE to raise the limit)"
+    )]
+    FileTooLarge { path: String, size: u64, limit: u64 },
+    /// A line, column or byte range reaches past the end of the content.
+    #[error("{0}")]
+    OutOfRange(String),
+    /// A partition's file path refers to an environment variable that isn't set.
+    #[error("Environment variable '{name}' used in '{path}' is not set")]
+    UnsetVariable { name: String, path: String },
+    #[error("Anchor '{0}' not found")]
+    AnchorNotFound(String),
+    #[error("Anchor '{0}' is not terminated (missing 'doksnet:end {0}')")]
+    UnterminatedAnchor(String),
+    #[error("Heading '{0}' not found")]
+    HeadingNotFound(String),
+    /// The `.doks` file is malformed or a change to it would make it so.
+    #[error("{0}")]
+    InvalidConfig(String),
+    /// A hash in `.doks` isn't a 64-character hex digest, so no content can
+    /// match it.
+    #[error("stored hash '{0}' is malformed (expected 64 hex characters)")]
+    MalformedHash(String),
+    /// Content no longer matches the hash stored for it.
+    #[error(
+        "content has changed (expected: {}..., actual: {}...)",
+        short_hash(.expected),
+        short_hash(.actual)
+    )]
+    HashMismatch { expected: String, actual: String },
+}
+
+impl DoksError {
+    pub(crate) fn io(path: impl Into<String>, error: std::io::Error) -> Self {
+        DoksError::Io {
+            path: path.into(),
+            message: error.to_string(),
+        }
+    }
+}
+
+fn did_you_mean(suggestions: &[String]) -> String {
+    if suggestions.is_empty() {
+        return String::new();
+    }
+    let quoted: Vec<String> = suggestions.iter().map(|s| format!("'{}'", s)).collect();
+    format!(" (did you mean {}?)", quoted.join(", "))
+}
+
+fn short_hash(hash: &str) -> String {
+    hash.chars().take(8).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_mismatch_display_tolerates_short_hashes() {
+        let error = DoksError::HashMismatch {
+            expected: "abc".to_string(),
+            actual: "0123456789abcdef".to_string(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "content has changed (expected: abc..., actual: 01234567...)"
+        );
+    }
+}
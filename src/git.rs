@@ -0,0 +1,121 @@
+//! Recovers previously mapped content from git history, so that failures can
+//! be shown as a diff against what the stored hash was computed from.
+
+use std::path::Path;
+use std::process::Command;
+
+use crate::hash::{verify_hash, HashOptions};
+use crate::partition::Partition;
+
+/// The last commit that touched `doks_file`, i.e. the most recent committed
+/// version of the stored hashes. `None` outside a git repository.
+pub fn last_commit(doks_file: &Path) -> Option<String> {
+    let root = doks_file.parent()?;
+    let file_name = doks_file.file_name()?;
+    let output = Command::new("git")
+        .current_dir(root)
+        .args(["log", "-1", "--format=%H", "--"])
+        .arg(file_name)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let commit = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (!commit.is_empty()).then_some(commit)
+}
+
+/// The contents of `file_path` (relative to `root`) at `commit`.
+pub fn show_file(root: &Path, commit: &str, file_path: &str) -> Option<String> {
+    let output = Command::new("git")
+        .current_dir(root)
+        .arg("show")
+        .arg(format!("{}:./{}", commit, file_path))
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
+}
+
+/// The content `partition_str` selected at `commit`, but only if it still
+/// matches `expected_hash`; otherwise it isn't what the hash was taken from.
+pub fn recover_partition(
+    root: &Path,
+    commit: &str,
+    partition_str: &str,
+    expected_hash: &str,
+    hash_options: HashOptions,
+) -> Option<String> {
+    let partition = Partition::parse(partition_str).ok()?;
+    let file = show_file(root, commit, &partition.file_path)?;
+    let content = partition.extract_from(&file).ok()?;
+    verify_hash(&content, expected_hash, hash_options).then_some(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::hash_content;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn git(dir: &Path, args: &[&str]) -> bool {
+        Command::new("git")
+            .current_dir(dir)
+            .args([
+                "-c",
+                "user.name=doksnet",
+                "-c",
+                "user.email=doksnet@example.com",
+            ])
+            .args(args)
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+    }
+
+    #[test]
+    fn test_recover_partition_from_last_commit() {
+        let dir = tempdir().unwrap();
+        if !git(dir.path(), &["init", "-q"]) {
+            eprintln!("git unavailable, skipping");
+            return;
+        }
+
+        let options = HashOptions::default();
+        let original_hash = hash_content("fn original() {}", options);
+        fs::write(dir.path().join("lib.rs"), "// header\nfn original() {}\n").unwrap();
+        fs::write(dir.path().join(".doks"), "default_doc=README.md\n").unwrap();
+        assert!(git(dir.path(), &["add", "."]));
+        assert!(git(dir.path(), &["commit", "-q", "-m", "initial"]));
+
+        fs::write(dir.path().join("lib.rs"), "// header\nfn renamed() {}\n").unwrap();
+
+        let commit = last_commit(&dir.path().join(".doks")).unwrap();
+        assert_eq!(
+            recover_partition(dir.path(), &commit, "lib.rs:2", &original_hash, options),
+            Some("fn original() {}".to_string())
+        );
+
+        // A hash the historical content doesn't match is not recovered
+        let other_hash = hash_content("fn renamed() {}", options);
+        assert_eq!(
+            recover_partition(dir.path(), &commit, "lib.rs:2", &other_hash, options),
+            None
+        );
+        assert_eq!(
+            recover_partition(dir.path(), &commit, "missing.rs:1", &original_hash, options),
+            None
+        );
+    }
+
+    #[test]
+    fn test_last_commit_outside_repository() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join(".doks"), "default_doc=README.md\n").unwrap();
+        // Either git is missing or the temp dir isn't tracked; both mean no history
+        assert_eq!(last_commit(&dir.path().join(".doks")), None);
+    }
+}
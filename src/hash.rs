@@ -1,13 +1,177 @@
-pub fn hash_content(content: &str) -> String {
-    let hash = blake3::hash(content.as_bytes());
-    hash.to_hex().to_string()
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::comments::CommentSyntax;
+use crate::error::{DoksError, Result};
+
+/// Digest used for the stored hashes; recorded in the `.doks` header as
+/// `hash_algo=` so hashes from different algorithms are never compared.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    #[default]
+    Blake3,
+    Sha256,
+}
+
+impl fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HashAlgorithm::Blake3 => write!(f, "blake3"),
+            HashAlgorithm::Sha256 => write!(f, "sha256"),
+        }
+    }
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = DoksError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "blake3" => Ok(HashAlgorithm::Blake3),
+            "sha256" => Ok(HashAlgorithm::Sha256),
+            _ => Err(DoksError::InvalidConfig(format!(
+                "Unknown hash algorithm '{}' (expected blake3 or sha256)",
+                s
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HashOptions {
+    pub algorithm: HashAlgorithm,
+    /// Convert `\r\n` and lone `\r` to `\n` before hashing so stored hashes
+    /// don't depend on the platform a file was last saved on.
+    pub normalize_line_endings: bool,
+    /// Trim each line and drop blank lines before hashing, so re-indented
+    /// copies of a snippet (e.g. inside a Markdown code fence) still match.
+    pub normalize_whitespace: bool,
+    /// Strip comments written in this syntax before hashing, so comment-only
+    /// edits don't change the hash. Only ever set for code partitions.
+    pub strip_comments: Option<CommentSyntax>,
 }
 
-pub fn verify_hash(content: &str, expected_hash: &str) -> bool {
-    let actual_hash = hash_content(content);
+impl Default for HashOptions {
+    fn default() -> Self {
+        Self {
+            algorithm: HashAlgorithm::default(),
+            normalize_line_endings: true,
+            normalize_whitespace: false,
+            strip_comments: None,
+        }
+    }
+}
+
+pub fn hash_content(content: &str, options: HashOptions) -> String {
+    let mut content = if options.normalize_line_endings {
+        normalize_line_endings(content)
+    } else {
+        Cow::Borrowed(content)
+    };
+    if let Some(syntax) = options.strip_comments {
+        content = Cow::Owned(syntax.strip(&content));
+    }
+    if options.normalize_whitespace {
+        content = Cow::Owned(normalize_whitespace(&content));
+    }
+    match options.algorithm {
+        HashAlgorithm::Blake3 => blake3::hash(content.as_bytes()).to_hex().to_string(),
+        HashAlgorithm::Sha256 => Sha256::digest(content.as_bytes())
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect(),
+    }
+}
+
+/// Hex characters kept from each per-line digest; enough to tell lines
+/// apart without bloating `.doks`.
+const LINE_DIGEST_LEN: usize = 8;
+
+/// Short digests of each line of multi-line `content`, stored so that a
+/// failing mapping can report which lines changed. Empty for a single line.
+pub fn line_digests(content: &str, options: HashOptions) -> Vec<String> {
+    let mut content = normalize_line_endings(content);
+    // Comments are stripped from the whole content, as block comments can
+    // span lines; the offsets then count the remaining lines
+    if let Some(syntax) = options.strip_comments {
+        content = Cow::Owned(syntax.strip(&content));
+    }
+    let options = HashOptions {
+        strip_comments: None,
+        ..options
+    };
+    if content.lines().nth(1).is_none() {
+        return Vec::new();
+    }
+    content
+        .lines()
+        .map(|line| hash_content(line, options)[..LINE_DIGEST_LEN].to_string())
+        .collect()
+}
+
+/// 1-indexed offsets of the lines whose digests differ, including lines only
+/// present on one side.
+pub fn changed_lines(stored: &[String], current: &[String]) -> Vec<usize> {
+    (0..stored.len().max(current.len()))
+        .filter(|&i| stored.get(i) != current.get(i))
+        .map(|i| i + 1)
+        .collect()
+}
+
+/// Hex characters in a digest from either algorithm.
+const HASH_LEN: usize = 64;
+
+/// Whether `hash` has the shape of a digest from `hash_content`. Checked
+/// before hashing, so a malformed stored hash doesn't cost a pass over the
+/// content it can never match.
+pub fn is_valid_hash(hash: &str) -> bool {
+    hash.len() == HASH_LEN && hash.bytes().all(|byte| byte.is_ascii_hexdigit())
+}
+
+pub fn verify_hash(content: &str, expected_hash: &str, options: HashOptions) -> bool {
+    if !is_valid_hash(expected_hash) {
+        return false;
+    }
+    let actual_hash = hash_content(content, options);
     actual_hash == expected_hash
 }
 
+/// Like `verify_hash`, but reports a mismatch with both hashes, or a
+/// malformed `expected_hash` without hashing the content.
+pub fn check_hash(content: &str, expected_hash: &str, options: HashOptions) -> Result<()> {
+    if !is_valid_hash(expected_hash) {
+        return Err(DoksError::MalformedHash(expected_hash.to_string()));
+    }
+    let actual_hash = hash_content(content, options);
+    if actual_hash == expected_hash {
+        Ok(())
+    } else {
+        Err(DoksError::HashMismatch {
+            expected: expected_hash.to_string(),
+            actual: actual_hash,
+        })
+    }
+}
+
+pub fn normalize_line_endings(content: &str) -> Cow<'_, str> {
+    if content.contains('\r') {
+        Cow::Owned(content.replace("\r\n", "\n").replace('\r', "\n"))
+    } else {
+        Cow::Borrowed(content)
+    }
+}
+
+pub fn normalize_whitespace(content: &str) -> String {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -15,7 +179,7 @@ mod tests {
     #[test]
     fn test_hash_content() {
         let content = "Hello, world!";
-        let hash = hash_content(content);
+        let hash = hash_content(content, HashOptions::default());
         assert!(!hash.is_empty());
         assert_eq!(hash.len(), 64);
     }
@@ -23,23 +187,82 @@ mod tests {
     #[test]
     fn test_verify_hash() {
         let content = "Hello, world!";
-        let hash = hash_content(content);
-        assert!(verify_hash(content, &hash));
-        assert!(!verify_hash("Different content", &hash));
+        let hash = hash_content(content, HashOptions::default());
+        assert!(verify_hash(content, &hash, HashOptions::default()));
+        assert!(!verify_hash(
+            "Different content",
+            &hash,
+            HashOptions::default()
+        ));
+    }
+
+    #[test]
+    fn test_check_hash_reports_mismatch() {
+        let hash = hash_content("Hello, world!", HashOptions::default());
+        assert!(check_hash("Hello, world!", &hash, HashOptions::default()).is_ok());
+
+        match check_hash("Different content", &hash, HashOptions::default()) {
+            Err(DoksError::HashMismatch { expected, actual }) => {
+                assert_eq!(expected, hash);
+                assert_eq!(
+                    actual,
+                    hash_content("Different content", HashOptions::default())
+                );
+            }
+            other => panic!("expected a hash mismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_check_hash_rejects_too_short_hash() {
+        assert!(!verify_hash(
+            "Hello, world!",
+            "abc123",
+            HashOptions::default()
+        ));
+        assert_eq!(
+            check_hash("Hello, world!", "abc123", HashOptions::default()),
+            Err(DoksError::MalformedHash("abc123".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_check_hash_rejects_non_hex_hash() {
+        let hash = "g".repeat(64);
+        assert!(!is_valid_hash(&hash));
+        assert!(!verify_hash("Hello, world!", &hash, HashOptions::default()));
+        assert_eq!(
+            check_hash("Hello, world!", &hash, HashOptions::default())
+                .unwrap_err()
+                .to_string(),
+            format!(
+                "stored hash '{}' is malformed (expected 64 hex characters)",
+                hash
+            )
+        );
+
+        // Digests from either algorithm are well-formed
+        for algorithm in [HashAlgorithm::Blake3, HashAlgorithm::Sha256] {
+            let options = HashOptions {
+                algorithm,
+                ..HashOptions::default()
+            };
+            assert!(is_valid_hash(&hash_content("Hello, world!", options)));
+        }
     }
 
     #[test]
     fn test_consistent_hashing() {
         let content = "Consistent content";
-        let hash1 = hash_content(content);
-        let hash2 = hash_content(content);
+        let hash1 = hash_content(content, HashOptions::default());
+        let hash2 = hash_content(content, HashOptions::default());
         assert_eq!(hash1, hash2);
     }
 
     #[test]
     fn test_empty_content() {
         let content = "";
-        let hash = hash_content(content);
+        let hash = hash_content(content, HashOptions::default());
         assert!(!hash.is_empty());
         assert_eq!(hash.len(), 64);
     }
@@ -50,28 +273,148 @@ mod tests {
         let content2 = "Hello  world";
         let content3 = "Hello world\n";
 
-        let hash1 = hash_content(content1);
-        let hash2 = hash_content(content2);
-        let hash3 = hash_content(content3);
+        let hash1 = hash_content(content1, HashOptions::default());
+        let hash2 = hash_content(content2, HashOptions::default());
+        let hash3 = hash_content(content3, HashOptions::default());
 
         assert_ne!(hash1, hash2);
         assert_ne!(hash1, hash3);
         assert_ne!(hash2, hash3);
     }
 
+    #[test]
+    fn test_crlf_matches_lf() {
+        let options = HashOptions::default();
+        let lf = hash_content("line1\nline2\n", options);
+        assert_eq!(hash_content("line1\r\nline2\r\n", options), lf);
+        assert_eq!(hash_content("line1\rline2\r", options), lf);
+    }
+
+    #[test]
+    fn test_crlf_preserved_when_disabled() {
+        let options = HashOptions {
+            normalize_line_endings: false,
+            ..HashOptions::default()
+        };
+        assert_ne!(
+            hash_content("line1\r\nline2", options),
+            hash_content("line1\nline2", options)
+        );
+    }
+
+    #[test]
+    fn test_whitespace_normalization() {
+        let source = "fn main() {\n    run();\n\n    stop();\n}";
+        let fenced = "  fn main() {\n      run();\n      stop();  \n  }\n\n";
+
+        let strict = HashOptions::default();
+        assert_ne!(hash_content(source, strict), hash_content(fenced, strict));
+
+        let normalized = HashOptions {
+            normalize_whitespace: true,
+            ..HashOptions::default()
+        };
+        assert_eq!(
+            hash_content(source, normalized),
+            hash_content(fenced, normalized)
+        );
+        assert_ne!(
+            hash_content(source, normalized),
+            hash_content("fn main() {\n    run(); stop();\n}", normalized)
+        );
+    }
+
+    #[test]
+    fn test_strip_comments() {
+        let options = HashOptions {
+            strip_comments: Some(CommentSyntax::Rust),
+            ..HashOptions::default()
+        };
+        let code = "fn main() {\n    run();\n}";
+        let commented = "// Entry point\nfn main() {\n    run(); // go\n}";
+
+        assert_eq!(
+            hash_content(code, options),
+            hash_content(commented, options)
+        );
+        assert_ne!(
+            hash_content(code, HashOptions::default()),
+            hash_content(commented, HashOptions::default())
+        );
+        assert_ne!(
+            hash_content(code, options),
+            hash_content("fn main() {\n    stop();\n}", options)
+        );
+        assert_eq!(
+            line_digests(code, options),
+            line_digests(commented, options)
+        );
+    }
+
+    #[test]
+    fn test_line_digests() {
+        let options = HashOptions::default();
+        assert!(line_digests("single line", options).is_empty());
+
+        let digests = line_digests("a\r\nb\nc", options);
+        assert_eq!(digests.len(), 3);
+        assert!(digests.iter().all(|d| d.len() == LINE_DIGEST_LEN));
+        assert_eq!(digests[0], hash_content("a", options)[..LINE_DIGEST_LEN]);
+    }
+
+    #[test]
+    fn test_changed_lines() {
+        let options = HashOptions::default();
+        let stored = line_digests("a\nb\nc\nd", options);
+
+        let current = line_digests("a\nB\nc\nd", options);
+        assert_eq!(changed_lines(&stored, &current), vec![2]);
+
+        let current = line_digests("a\nb\nc\nd\ne", options);
+        assert_eq!(changed_lines(&stored, &current), vec![5]);
+
+        assert!(changed_lines(&stored, &stored).is_empty());
+    }
+
+    #[test]
+    fn test_sha256() {
+        let options = HashOptions {
+            algorithm: HashAlgorithm::Sha256,
+            ..HashOptions::default()
+        };
+        let hash = hash_content("abc", options);
+        assert_eq!(
+            hash,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert!(verify_hash("abc", &hash, options));
+        assert!(!verify_hash("abc", &hash, HashOptions::default()));
+    }
+
+    #[test]
+    fn test_hash_algorithm_round_trip() {
+        for algorithm in [HashAlgorithm::Blake3, HashAlgorithm::Sha256] {
+            assert_eq!(
+                algorithm.to_string().parse::<HashAlgorithm>().unwrap(),
+                algorithm
+            );
+        }
+        assert!("md5".parse::<HashAlgorithm>().is_err());
+    }
+
     #[test]
     fn test_unicode_content() {
         let content = "Hello 世界 🦀";
-        let hash = hash_content(content);
+        let hash = hash_content(content, HashOptions::default());
         assert!(!hash.is_empty());
-        assert!(verify_hash(content, &hash));
+        assert!(verify_hash(content, &hash, HashOptions::default()));
     }
 
     #[test]
     fn test_large_content() {
         let content = "A".repeat(10000);
-        let hash = hash_content(&content);
+        let hash = hash_content(&content, HashOptions::default());
         assert!(!hash.is_empty());
-        assert!(verify_hash(&content, &hash));
+        assert!(verify_hash(&content, &hash, HashOptions::default()));
     }
 }
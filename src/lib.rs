@@ -0,0 +1,29 @@
+//! Documentation-code mapping verification.
+//!
+//! The `doksnet` binary is a thin layer over this library, which can also be
+//! used directly, e.g. from a build script:
+//!
+//! ```no_run
+//! use doksnet::config::DoksConfig;
+//! use doksnet::verify::verify_all;
+//!
+//! let config = DoksConfig::from_file("docs/.doks").unwrap();
+//! for result in verify_all(&config, &config.root) {
+//!     if !result.passed {
+//!         eprintln!("{}: {}", result.id, result.errors.join("; "));
+//!     }
+//! }
+//! ```
+
+pub mod comments;
+pub mod config;
+pub mod error;
+pub mod hash;
+pub mod partition;
+mod suggest;
+pub mod verify;
+
+pub use config::{DoksConfig, Mapping};
+pub use error::DoksError;
+pub use partition::Partition;
+pub use verify::{verify_all, MappingResult};
@@ -1,23 +1,72 @@
 use anyhow::Result;
 use clap::Parser;
+use std::process;
 
 mod cli;
 mod commands;
-mod config;
-mod hash;
-mod partition;
+mod git;
+mod output;
 
 use cli::Cli;
+use doksnet::{config, error, hash, partition, verify};
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    output::init(cli.no_color);
+    commands::init(cli.doks_file, cli.cwd);
 
     match cli.command {
-        cli::Commands::New { path } => commands::new::handle(path),
-        cli::Commands::Add => commands::add::handle(),
-        cli::Commands::Edit { id } => commands::edit::handle(id),
-        cli::Commands::RemoveFailed => commands::remove_failed::handle(),
-        cli::Commands::Test => commands::test::handle(),
+        cli::Commands::New {
+            path,
+            doc,
+            doc_globs,
+        } => commands::new::handle(path, doc, doc_globs),
+        cli::Commands::Add(args) => commands::add::handle(args),
+        cli::Commands::AddBatch { file } => commands::add_batch::handle(file),
+        cli::Commands::Check => commands::check::handle(),
+        cli::Commands::Config { action } => commands::config::handle(action),
+        cli::Commands::Coverage { file } => commands::coverage::handle(file),
+        cli::Commands::Doctor => commands::doctor::handle(),
+        cli::Commands::Edit(args) => commands::edit::handle(args),
+        cli::Commands::Export { format } => commands::export::handle(format),
+        cli::Commands::Extract { partition } => commands::extract::handle(partition),
+        cli::Commands::Import { file, overwrite } => commands::import::handle(file, overwrite),
+        cli::Commands::List {
+            format,
+            with_status,
+        } => commands::list::handle(format, with_status),
+        cli::Commands::Merge { other, prefix } => commands::merge::handle(other, prefix),
+        cli::Commands::Move {
+            id,
+            to_doc,
+            to_code,
+        } => commands::move_mapping::handle(id, to_doc, to_code),
+        cli::Commands::Remove { id } => commands::remove::handle(id),
+        cli::Commands::RemoveFailed { yes, count } => commands::remove_failed::handle(yes, count),
+        cli::Commands::RenameFile {
+            from,
+            to,
+            move_file,
+        } => commands::rename_file::handle(from, to, move_file),
+        cli::Commands::Show { id, context } => commands::show::handle(id, context),
+        cli::Commands::Sort => commands::sort::handle(),
+        cli::Commands::Stats => commands::stats::handle(),
+        cli::Commands::Sync { check } => commands::sync::handle(check),
+        cli::Commands::Test(args) => {
+            let outcome = if args.status_only {
+                commands::test::status(&args)
+            } else {
+                commands::test::handle(&args)?
+            };
+            match outcome {
+                commands::test::Outcome::Passed => Ok(()),
+                commands::test::Outcome::Failed => process::exit(1),
+                commands::test::Outcome::Invalid => process::exit(2),
+            }
+        }
         cli::Commands::TestInteractive => commands::test_interactive::handle(),
+        cli::Commands::Undo => commands::undo::handle(),
+        cli::Commands::Validate => commands::validate::handle(),
+        cli::Commands::Watch => commands::watch::handle(),
     }
 }
@@ -0,0 +1,192 @@
+//! Status markers shared by every command.
+//!
+//! Emoji are only printed when stdout is a terminal; piped output and
+//! `--no-color` fall back to plain ASCII so logs and tools aren't cluttered.
+
+use similar::TextDiff;
+use std::fmt;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static PLAIN: AtomicBool = AtomicBool::new(false);
+
+/// Decides once, at startup, whether markers are rendered as emoji.
+pub fn init(no_color: bool) {
+    PLAIN.store(
+        no_color || !std::io::stdout().is_terminal(),
+        Ordering::Relaxed,
+    );
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Marker {
+    Ok,
+    Fail,
+    Warn,
+    Info,
+    Tip,
+    Alert,
+    Empty,
+    Stats,
+    Doc,
+    Docs,
+    Code,
+    Note,
+    List,
+    Search,
+    Inspect,
+    Test,
+    Sync,
+    Rehash,
+    Start,
+    Edit,
+    Fix,
+    Remove,
+    Skip,
+    Pin,
+    Save,
+    Finish,
+    Celebrate,
+    Bullet,
+}
+
+impl Marker {
+    /// The emoji and the separator that keeps the following text aligned.
+    fn emoji(self) -> &'static str {
+        match self {
+            Marker::Ok => "✅ ",
+            Marker::Fail => "❌ ",
+            Marker::Warn => "⚠️  ",
+            Marker::Info => "ℹ️  ",
+            Marker::Tip => "💡 ",
+            Marker::Alert => "🚨 ",
+            Marker::Empty => "📭 ",
+            Marker::Stats => "📊 ",
+            Marker::Doc => "📄 ",
+            Marker::Docs => "📚 ",
+            Marker::Code => "💻 ",
+            Marker::Note => "📝 ",
+            Marker::List => "📋 ",
+            Marker::Search => "🔍 ",
+            Marker::Inspect => "🔎 ",
+            Marker::Test => "🧪 ",
+            Marker::Sync => "🔄 ",
+            Marker::Rehash => "🔁 ",
+            Marker::Start => "🚀 ",
+            Marker::Edit => "✏️  ",
+            Marker::Fix => "🛠️  ",
+            Marker::Remove => "🗑️  ",
+            Marker::Skip => "⏭️  ",
+            Marker::Pin => "📍 ",
+            Marker::Save => "💾 ",
+            Marker::Finish => "🏁 ",
+            Marker::Celebrate => "🎉 ",
+            Marker::Bullet => "• ",
+        }
+    }
+
+    /// Plain rendering; the surrounding text (e.g. `PASS`/`FAIL`) carries
+    /// the meaning, so most markers simply disappear.
+    fn plain(self) -> &'static str {
+        match self {
+            Marker::Bullet => "- ",
+            _ => "",
+        }
+    }
+}
+
+impl fmt::Display for Marker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if PLAIN.load(Ordering::Relaxed) {
+            f.write_str(self.plain())
+        } else {
+            f.write_str(self.emoji())
+        }
+    }
+}
+
+/// The first `max_chars` characters of `content`, followed by a truncation
+/// note when anything was cut. A `max_chars` of 0 shows everything.
+pub fn preview(content: &str, max_chars: usize) -> String {
+    if max_chars == 0 || content.chars().count() <= max_chars {
+        return content.to_string();
+    }
+    let shown: String = content.chars().take(max_chars).collect();
+    format!("{}\n... (truncated)", shown)
+}
+
+/// Numbered lines from `Partition::extract_with_context_in`: `>` marks the
+/// lines of the partition, `|` the context lines around it.
+pub fn context_preview(lines: &[(usize, String, bool)]) -> String {
+    let width = lines
+        .iter()
+        .map(|(number, _, _)| number.to_string().len())
+        .max()
+        .unwrap_or(1);
+
+    let rendered: Vec<String> = lines
+        .iter()
+        .map(|(number, line, in_partition)| {
+            let separator = if *in_partition { '>' } else { '|' };
+            format!("{:>width$} {} {}", number, separator, line, width = width)
+        })
+        .collect();
+    rendered.join("\n")
+}
+
+/// A unified diff from the content a hash was stored for to the current
+/// content, with three lines of context.
+pub fn unified_diff(stored: &str, current: &str) -> String {
+    TextDiff::from_lines(stored, current)
+        .unified_diff()
+        .context_radius(3)
+        .header("stored", "current")
+        .missing_newline_hint(false)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff() {
+        let diff = unified_diff("fn main() {\n    run();\n}", "fn main() {\n    start();\n}");
+        assert!(diff.starts_with("--- stored\n+++ current\n"));
+        assert!(diff.contains("-    run();\n"));
+        assert!(diff.contains("+    start();\n"));
+        assert!(diff.contains(" fn main() {\n"));
+    }
+
+    #[test]
+    fn test_preview() {
+        let content = "0123456789".repeat(50);
+
+        let short = preview(&content, 200);
+        assert!(short.starts_with(&content[..200]));
+        assert!(!short.contains(&content[..201]));
+        assert!(short.ends_with("\n... (truncated)"));
+
+        let long = preview(&content, 400);
+        assert!(long.contains(&content[..400]));
+        assert!(long.len() > short.len());
+
+        assert_eq!(preview(&content, 0), content);
+        assert_eq!(preview(&content, 500), content);
+        // Counts characters, not bytes
+        assert_eq!(preview("héllo", 5), "héllo");
+    }
+
+    #[test]
+    fn test_context_preview() {
+        let lines = vec![
+            (9, "before".to_string(), false),
+            (10, "inside".to_string(), true),
+            (11, "after".to_string(), false),
+        ];
+        assert_eq!(
+            context_preview(&lines),
+            " 9 | before\n10 > inside\n11 | after"
+        );
+    }
+}
@@ -1,173 +1,830 @@
-use anyhow::{anyhow, Result};
-use std::path::Path;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::error::{DoksError, Result};
+use crate::suggest::similar_paths;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Partition {
     pub file_path: String,
-    pub start_line: Option<usize>,
-    pub end_line: Option<usize>,
+    pub addressing: Addressing,
+}
+
+/// How a partition selects content from its file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Addressing {
+    /// Line ranges to extract, concatenated in order. Empty means the entire file.
+    Lines(Vec<Segment>),
+    /// A 0-based, end-exclusive byte range, e.g. `file.min.js:#100-250`, for
+    /// files where line numbers are meaningless.
+    Bytes { start: usize, end: usize },
+    /// A named region between `doksnet:start <name>` and `doksnet:end <name>`
+    /// marker comments, e.g. `src/lib.rs:@@auth@@`, so the mapping survives
+    /// lines being added above it. `lines` optionally narrows it to a
+    /// 1-indexed, inclusive range relative to the region's first line, e.g.
+    /// `src/lib.rs:@@auth@@2-4`.
+    Anchor {
+        name: String,
+        lines: Option<(usize, usize)>,
+    },
+    /// The section of a Markdown file under an ATX heading, e.g.
+    /// `README.md:##Installation`, up to the next heading of the same or a
+    /// higher level. Nested subsections are included.
+    Heading { level: usize, title: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    pub start_line: usize,
+    pub end_line: usize,
     pub start_col: Option<usize>,
     pub end_col: Option<usize>,
+    /// When set, `start_line` and `end_line` count back from the last line of
+    /// the file (1 is the last line), e.g. `CHANGELOG.md:-5..`.
+    pub from_end: bool,
+    /// When set, the column window applies to every line of the range rather
+    /// than only the first and last, e.g. `file:10-20@@5-40`.
+    pub block: bool,
+    /// When set, the range was written end-exclusive, e.g. `file:10..20` for
+    /// lines 10-19. `end_line` is still the last included line; this only
+    /// decides how the range is written back.
+    pub exclusive_end: bool,
 }
 
 impl Partition {
     pub fn parse(partition_str: &str) -> Result<Self> {
         if partition_str.trim().is_empty() {
-            return Err(anyhow!("Partition string cannot be empty"));
+            return Err(DoksError::InvalidPartition(
+                "Partition string cannot be empty".to_string(),
+            ));
         }
 
         let parts: Vec<&str> = partition_str.split(':').collect();
         let file_path = parts[0].to_string();
 
         if file_path.trim().is_empty() {
-            return Err(anyhow!("File path cannot be empty"));
+            return Err(DoksError::InvalidPartition(
+                "File path cannot be empty".to_string(),
+            ));
         }
 
         if parts.len() == 1 {
             return Ok(Partition {
                 file_path,
-                start_line: None,
-                end_line: None,
-                start_col: None,
-                end_col: None,
+                addressing: Addressing::Lines(Vec::new()),
             });
         }
 
-        let range_part = parts[1];
-        let (line_range, col_range) = if range_part.contains('@') {
-            let range_parts: Vec<&str> = range_part.split('@').collect();
-            (range_parts[0], Some(range_parts[1]))
-        } else {
-            (range_part, None)
+        let rest = partition_str.split_once(':').map_or("", |(_, rest)| rest);
+        if let Some(heading) = parse_heading(rest) {
+            let (level, title) = heading?;
+            if !is_markdown(&file_path) {
+                return Err(DoksError::InvalidPartition(format!(
+                    "Heading partitions are only supported for Markdown files, not '{}'",
+                    file_path
+                )));
+            }
+            return Ok(Partition {
+                file_path,
+                addressing: Addressing::Heading { level, title },
+            });
+        }
+
+        if let Some(byte_range) = parts[1].strip_prefix('#') {
+            let (start, end) = byte_range.split_once('-').ok_or_else(|| {
+                DoksError::InvalidPartition(
+                    "Invalid byte range format (expected #start-end)".to_string(),
+                )
+            })?;
+            let (start, end) = (
+                parse_number(start, "byte", partition_str)?,
+                parse_number(end, "byte", partition_str)?,
+            );
+            if start >= end {
+                return Err(DoksError::InvalidPartition(
+                    "Start byte must be < end byte".to_string(),
+                ));
+            }
+            return Ok(Partition {
+                file_path,
+                addressing: Addressing::Bytes { start, end },
+            });
+        }
+
+        if let Some((name, range)) = parts[1]
+            .strip_prefix("@@")
+            .and_then(|rest| rest.split_once("@@"))
+        {
+            if name.is_empty() || name.contains(char::is_whitespace) {
+                return Err(DoksError::InvalidPartition(format!(
+                    "Invalid anchor name '{}'",
+                    name
+                )));
+            }
+            let lines = if range.is_empty() {
+                None
+            } else {
+                Some(parse_relative_range(range, name, partition_str)?)
+            };
+            return Ok(Partition {
+                file_path,
+                addressing: Addressing::Anchor {
+                    name: name.to_string(),
+                    lines,
+                },
+            });
+        }
+
+        let mut segments = Vec::new();
+        let segment_strs: Vec<&str> = parts[1].split(',').collect();
+        for segment_str in &segment_strs {
+            match Segment::parse(segment_str, partition_str)? {
+                Some(segment) => segments.push(segment),
+                None if segment_strs.len() > 1 => {
+                    return Err(DoksError::InvalidPartition(
+                        "Empty range in multi-range partition".to_string(),
+                    ));
+                }
+                None => {}
+            }
+        }
+
+        let absolute_ranges: Vec<(usize, usize)> = segments
+            .iter()
+            .filter(|s| !s.from_end)
+            .map(|s| (s.start_line, s.end_line))
+            .collect();
+        check_overlaps(&absolute_ranges)?;
+
+        Ok(Partition {
+            file_path,
+            addressing: Addressing::Lines(segments),
+        })
+    }
+
+    /// `file_path` with environment variables expanded, see `expand_env_vars`.
+    pub fn expanded_path(&self) -> Result<String> {
+        expand_env_vars(&self.file_path)
+    }
+
+    /// The existing file the partition points at under `root`: `file_path`
+    /// with environment variables expanded and, for a glob, the one file it
+    /// matches.
+    pub fn locate_in(&self, root: &Path) -> Result<PathBuf> {
+        locate_file(root, &self.file_path).map(|(_, path)| path)
+    }
+
+    /// Extracts the content, resolving a relative `file_path` against the
+    /// current directory.
+    pub fn extract_content(&self) -> Result<String> {
+        self.extract_content_in(Path::new(""))
+    }
+
+    /// Extracts the content, resolving a relative `file_path` against `root`
+    /// (normally the directory containing `.doks`). Line ranges counted from
+    /// the start of the file only read the file up to their last line.
+    pub fn extract_content_in(&self, root: &Path) -> Result<String> {
+        match self.last_line() {
+            Some(last_line) => {
+                self.extract_from(&read_leading_lines(root, &self.file_path, last_line)?)
+            }
+            None => self.extract_from(&read_file(root, &self.file_path)?),
+        }
+    }
+
+    /// Like `extract_content`, but reads the file through `cache` so that
+    /// partitions of the same file share a single read. A file too large to
+    /// cache is still read up to the last line of a line range.
+    pub fn extract_content_cached(&self, cache: &ContentCache) -> Result<String> {
+        match (cache.read(&self.file_path), self.last_line()) {
+            (Err(DoksError::FileTooLarge { .. }), Some(last_line)) => self.extract_from(
+                &read_leading_lines(&cache.root, &self.file_path, last_line)?,
+            ),
+            (content, _) => self.extract_from(&content?),
+        }
+    }
+
+    /// The last line extraction needs when the partition is made of line
+    /// ranges counted from the start of the file, so the rest of the file
+    /// can be left unread. A reversed range counts up to its start line, so
+    /// it's still reported as reversed rather than as past the end.
+    fn last_line(&self) -> Option<usize> {
+        match &self.addressing {
+            Addressing::Lines(segments) if segments.iter().all(|s| !s.from_end) => {
+                segments.iter().map(|s| s.start_line.max(s.end_line)).max()
+            }
+            _ => None,
+        }
+    }
+
+    /// Extracts the content paired with the 1-indexed line of the file each
+    /// extracted line comes from, for numbered previews.
+    pub fn extract_numbered_lines_in(&self, root: &Path) -> Result<Vec<(usize, String)>> {
+        self.numbered_lines(&read_file(root, &self.file_path)?)
+    }
+
+    /// Like `extract_numbered_lines_in`, plus up to `context` whole lines of
+    /// the file before and after the partition for orientation. Each line is
+    /// flagged with whether it belongs to the partition; the context is only
+    /// for display and never part of the extracted content.
+    pub fn extract_with_context_in(
+        &self,
+        root: &Path,
+        context: usize,
+    ) -> Result<Vec<(usize, String, bool)>> {
+        let content = read_file(root, &self.file_path)?;
+        let numbered = self.numbered_lines(&content)?;
+        let (Some(&(first, _)), Some(&(last, _))) = (numbered.first(), numbered.last()) else {
+            return Ok(Vec::new());
         };
 
-        let (start_line, end_line) = if line_range.is_empty() {
-            (None, None)
-        } else {
-            let line_parts: Vec<&str> = line_range.split('-').collect();
-            match line_parts.len() {
-                1 => {
-                    let line = line_parts[0].parse::<usize>()?;
-                    (Some(line), Some(line))
+        let file_lines: Vec<&str> = content.lines().collect();
+        let whole_line = |number: usize| (number, file_lines[number - 1].to_string(), false);
+        let before = first.saturating_sub(context).max(1)..first;
+        let after = last + 1..=(last + context).min(file_lines.len());
+
+        let mut lines: Vec<_> = before.map(whole_line).collect();
+        lines.extend(numbered.into_iter().map(|(n, line)| (n, line, true)));
+        lines.extend(after.map(whole_line));
+        Ok(lines)
+    }
+
+    fn numbered_lines(&self, content: &str) -> Result<Vec<(usize, String)>> {
+        let mut numbered = Vec::new();
+        for (first_line, text) in self.extract_pieces(content)? {
+            for (offset, line) in text.lines().enumerate() {
+                numbered.push((first_line + offset, line.to_string()));
+            }
+        }
+        Ok(numbered)
+    }
+
+    /// Extracts the partition from already-loaded file contents.
+    pub fn extract_from(&self, content: &str) -> Result<String> {
+        let pieces: Vec<String> = self
+            .extract_pieces(content)?
+            .into_iter()
+            .map(|(_, text)| text)
+            .collect();
+        Ok(pieces.join("\n"))
+    }
+
+    /// For lenient checks (`test --clamp`): a copy of the partition in which
+    /// every line range that ends past the last line of `content` ends at
+    /// that line instead, with a note describing each range cut short.
+    /// `None` when no range can be cut, e.g. because one starts past the end.
+    pub fn clamped(&self, content: &str) -> Option<(Partition, Vec<String>)> {
+        let Addressing::Lines(segments) = &self.addressing else {
+            return None;
+        };
+        let lines: Vec<&str> = content.lines().collect();
+        let line_count = lines.len();
+
+        let mut notes = Vec::new();
+        let mut clamped = Vec::new();
+        for segment in segments {
+            if segment.from_end || segment.end_line <= line_count {
+                clamped.push(segment.clone());
+                continue;
+            }
+            if segment.start_line > line_count {
+                return None;
+            }
+            notes.push(format!(
+                "line range {} clamped to {}-{} (file has {} lines)",
+                segment.to_string(),
+                segment.start_line,
+                line_count,
+                line_count
+            ));
+            // The end column belonged to a line that no longer exists, so
+            // the new last line is taken whole
+            let end_col = match segment.start_col {
+                Some(_) if !segment.block => Some(lines[line_count - 1].graphemes(true).count()),
+                _ => segment.end_col,
+            };
+            clamped.push(Segment {
+                end_line: line_count,
+                end_col,
+                exclusive_end: false,
+                ..segment.clone()
+            });
+        }
+
+        if notes.is_empty() {
+            return None;
+        }
+        let partition = Partition {
+            addressing: Addressing::Lines(clamped),
+            ..self.clone()
+        };
+        Some((partition, notes))
+    }
+
+    /// The 1-indexed, inclusive line ranges of `content` this partition
+    /// touches. Partially selected lines (columns, byte ranges) count as covered.
+    pub fn covered_lines(&self, content: &str) -> Result<Vec<(usize, usize)>> {
+        Ok(self
+            .extract_pieces(content)?
+            .into_iter()
+            .map(|(first_line, text)| (first_line, first_line + text.lines().count().max(1) - 1))
+            .collect())
+    }
+
+    /// The extracted pieces in order, each with the file line it starts on.
+    fn extract_pieces(&self, content: &str) -> Result<Vec<(usize, String)>> {
+        let segments = match &self.addressing {
+            Addressing::Lines(segments) if segments.is_empty() => {
+                return Ok(vec![(1, strip_final_newline(content).to_string())]);
+            }
+            Addressing::Lines(segments) => segments,
+            Addressing::Bytes { start, end } => {
+                if *end > content.len() {
+                    return Err(DoksError::OutOfRange(
+                        "Byte offset exceeds file length".to_string(),
+                    ));
+                }
+                let text = content.get(*start..*end).ok_or_else(|| {
+                    DoksError::InvalidPartition(
+                        "Byte range does not fall on UTF-8 character boundaries".to_string(),
+                    )
+                })?;
+                let first_line = content[..*start].matches('\n').count() + 1;
+                return Ok(vec![(first_line, text.to_string())]);
+            }
+            Addressing::Anchor { name, lines } => {
+                let (first_line, text) = extract_anchor(content, name)?;
+                let Some((start, end)) = *lines else {
+                    return Ok(vec![(first_line, text)]);
+                };
+                let region: Vec<&str> = text.lines().collect();
+                if end > region.len() {
+                    return Err(DoksError::OutOfRange(format!(
+                        "Line {} exceeds anchor '{}' (anchor has {} lines)",
+                        end,
+                        name,
+                        region.len()
+                    )));
                 }
-                2 => {
-                    let start = line_parts[0].parse::<usize>()?;
-                    let end = line_parts[1].parse::<usize>()?;
-                    (Some(start), Some(end))
+                return Ok(vec![(
+                    first_line + start - 1,
+                    region[start - 1..end].join("\n"),
+                )]);
+            }
+            Addressing::Heading { level, title } => {
+                return Ok(vec![extract_section(content, *level, title)?]);
+            }
+        };
+
+        let lines: Vec<&str> = content.lines().collect();
+        let mut resolved = Vec::new();
+        for segment in segments {
+            resolved.push(segment.resolve(lines.len())?);
+        }
+        check_overlaps(&resolved)?;
+
+        let mut pieces = Vec::new();
+        for (segment, (start, end)) in segments.iter().zip(resolved) {
+            pieces.push((start, segment.extract(&lines, start, end)?));
+        }
+        Ok(pieces)
+    }
+
+    #[allow(clippy::inherent_to_string)]
+    pub fn to_string(&self) -> String {
+        let mut result = self.file_path.clone();
+
+        match &self.addressing {
+            Addressing::Lines(segments) if !segments.is_empty() => {
+                let segments: Vec<String> = segments.iter().map(|s| s.to_string()).collect();
+                result.push(':');
+                result.push_str(&segments.join(","));
+            }
+            Addressing::Lines(_) => {}
+            Addressing::Bytes { start, end } => {
+                result.push_str(&format!(":#{}-{}", start, end));
+            }
+            Addressing::Anchor { name, lines } => {
+                result.push_str(&format!(":@@{}@@", name));
+                match lines {
+                    Some((start, end)) if start == end => result.push_str(&start.to_string()),
+                    Some((start, end)) => result.push_str(&format!("{}-{}", start, end)),
+                    None => {}
                 }
-                _ => return Err(anyhow!("Invalid line range format")),
             }
+            Addressing::Heading { level, title } => {
+                // Keep the space that stops `#2024` reading as a byte range
+                let separator = if *level == 1 && title.starts_with(|c: char| c.is_ascii_digit()) {
+                    " "
+                } else {
+                    ""
+                };
+                result.push_str(&format!(":{}{}{}", "#".repeat(*level), separator, title));
+            }
+        }
+
+        result
+    }
+}
+
+/// File contents shared between partitions within a single run, keyed by
+/// path. Safe to use from several threads; each file is read at most once.
+#[derive(Default)]
+pub struct ContentCache {
+    root: PathBuf,
+    files: Mutex<HashMap<String, Arc<CachedFile>>>,
+}
+
+/// Read result for one file, handed to every partition that asks for it.
+type CachedFile = OnceLock<Result<Arc<str>>>;
+
+impl ContentCache {
+    /// A cache resolving relative paths against `root`.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn read(&self, file_path: &str) -> Result<Arc<str>> {
+        // Only hold the map lock long enough to find the slot, so different
+        // files can be read concurrently while readers of the same file wait.
+        let slot = self
+            .files
+            .lock()
+            .unwrap()
+            .entry(file_path.to_string())
+            .or_default()
+            .clone();
+
+        slot.get_or_init(|| read_file(&self.root, file_path).map(Arc::from))
+            .clone()
+    }
+}
+
+/// Files larger than this many bytes aren't read whole unless
+/// `DOKSNET_MAX_FILE_SIZE` sets another limit.
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 64 * 1024 * 1024;
+
+/// The largest file read whole, in bytes: `DOKSNET_MAX_FILE_SIZE` when set,
+/// otherwise [`DEFAULT_MAX_FILE_SIZE`].
+pub fn max_file_size() -> Result<u64> {
+    match std::env::var("DOKSNET_MAX_FILE_SIZE") {
+        Ok(value) => value.trim().parse().map_err(|_| {
+            DoksError::InvalidConfig(format!(
+                "DOKSNET_MAX_FILE_SIZE must be a number of bytes, not '{}'",
+                value
+            ))
+        }),
+        Err(_) => Ok(DEFAULT_MAX_FILE_SIZE),
+    }
+}
+
+fn read_file(root: &Path, file_path: &str) -> Result<String> {
+    read_file_with_limit(root, file_path, max_file_size()?)
+}
+
+fn read_file_with_limit(root: &Path, file_path: &str, limit: u64) -> Result<String> {
+    let (file_path, path) = locate_file(root, file_path)?;
+    let size = std::fs::metadata(&path)
+        .map_err(|e| DoksError::io(file_path.clone(), e))?
+        .len();
+    if size > limit {
+        return Err(DoksError::FileTooLarge {
+            path: file_path,
+            size,
+            limit,
+        });
+    }
+    std::fs::read_to_string(path).map_err(|e| DoksError::io(file_path, e))
+}
+
+/// The first `line_count` lines of the file, line endings included, or the
+/// whole file when it's shorter. Nothing after those lines is read.
+fn read_leading_lines(root: &Path, file_path: &str, line_count: usize) -> Result<String> {
+    let (file_path, path) = locate_file(root, file_path)?;
+    let file = std::fs::File::open(path).map_err(|e| DoksError::io(file_path.clone(), e))?;
+    let mut reader = BufReader::new(file);
+    let mut content = String::new();
+    for _ in 0..line_count {
+        let read = reader
+            .read_line(&mut content)
+            .map_err(|e| DoksError::io(file_path.clone(), e))?;
+        if read == 0 {
+            break;
+        }
+    }
+    Ok(content)
+}
+
+/// `file_path` with environment variables expanded, and the path it refers
+/// to under `root`, which has to exist. A path that doesn't exist as written
+/// but contains `*`, `?` or `[` is taken as a glob, e.g. `target/doc-*.md`,
+/// and has to match exactly one file.
+fn locate_file(root: &Path, file_path: &str) -> Result<(String, PathBuf)> {
+    let file_path = expand_env_vars(file_path)?;
+    let path = root.join(&file_path);
+    if path.exists() {
+        return Ok((file_path, path));
+    }
+    if file_path.contains(['*', '?', '[']) {
+        return match_glob(root, &file_path);
+    }
+    Err(DoksError::FileNotFound {
+        suggestions: similar_paths(root, &file_path),
+        path: file_path,
+    })
+}
+
+/// The one file under `root` matching `pattern`, as a path relative to
+/// `root` and the path itself. `*` and `?` don't match `/`; `**` does.
+fn match_glob(root: &Path, pattern: &str) -> Result<(String, PathBuf)> {
+    let escaped_root = glob::Pattern::escape(&root.to_string_lossy());
+    let full_pattern = Path::new(&escaped_root).join(pattern);
+    let options = glob::MatchOptions {
+        require_literal_separator: true,
+        ..glob::MatchOptions::new()
+    };
+    let paths = glob::glob_with(&full_pattern.to_string_lossy(), options)
+        .map_err(|e| DoksError::InvalidPartition(format!("Invalid glob '{}': {}", pattern, e)))?;
+
+    let mut matches: Vec<PathBuf> = paths.flatten().filter(|path| path.is_file()).collect();
+    let relative = |path: &PathBuf| {
+        path.strip_prefix(root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .into_owned()
+    };
+    match matches.len() {
+        0 => Err(DoksError::FileNotFound {
+            path: pattern.to_string(),
+            suggestions: Vec::new(),
+        }),
+        1 => {
+            let path = matches.remove(0);
+            Ok((relative(&path), path))
+        }
+        _ => Err(DoksError::AmbiguousGlob {
+            pattern: pattern.to_string(),
+            matches: matches.iter().map(relative).collect(),
+        }),
+    }
+}
+
+/// Replaces `$VAR` and `${VAR}` in a partition's file path with the value of
+/// the environment variable, e.g. `$OUT_DIR/generated.md`. `$$` stands for a
+/// literal `$`, and a `$` not followed by a name is kept as is.
+pub fn expand_env_vars(file_path: &str) -> Result<String> {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut expanded = String::with_capacity(file_path.len());
+    let mut rest = file_path;
+
+    while let Some(dollar) = rest.find('$') {
+        expanded.push_str(&rest[..dollar]);
+        let after = &rest[dollar + 1..];
+
+        let (name, remainder) = if let Some(after) = after.strip_prefix('$') {
+            expanded.push('$');
+            rest = after;
+            continue;
+        } else if let Some(braced) = after.strip_prefix('{') {
+            let close = braced.find('}').ok_or_else(|| {
+                DoksError::InvalidPartition(format!(
+                    "Unterminated '${{' in file path '{}'",
+                    file_path
+                ))
+            })?;
+            (&braced[..close], &braced[close + 1..])
+        } else {
+            let end = after.find(|c| !is_name_char(c)).unwrap_or(after.len());
+            (&after[..end], &after[end..])
+        };
+
+        if name.is_empty() || !name.chars().all(is_name_char) {
+            if after.starts_with('{') {
+                return Err(DoksError::InvalidPartition(format!(
+                    "Invalid variable name '{}' in file path '{}'",
+                    name, file_path
+                )));
+            }
+            expanded.push('$');
+            rest = after;
+            continue;
+        }
+
+        let value = std::env::var(name).map_err(|_| DoksError::UnsetVariable {
+            name: name.to_string(),
+            path: file_path.to_string(),
+        })?;
+        expanded.push_str(&value);
+        rest = remainder;
+    }
+
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+impl Segment {
+    /// Parses a single `<lines>@<cols>` or `<lines>@@<cols>` range of
+    /// `partition_str`. Returns `None` when no line range is given.
+    fn parse(segment_str: &str, partition_str: &str) -> Result<Option<Self>> {
+        let block = segment_str.contains("@@");
+        let (line_range, col_range) = if let Some((lines, cols)) = segment_str.split_once("@@") {
+            (lines, Some(cols))
+        } else if segment_str.contains('@') {
+            let range_parts: Vec<&str> = segment_str.split('@').collect();
+            (range_parts[0], Some(range_parts[1]))
+        } else {
+            (segment_str, None)
+        };
+
+        let from_end = line_range.starts_with('-') && line_range.ends_with("..");
+        let exclusive_end = !from_end && line_range.contains("..");
+
+        let (start_line, end_line) = if line_range.is_empty() && block {
+            return Err(DoksError::InvalidPartition(
+                "A @@ column window requires a line range".to_string(),
+            ));
+        } else if line_range.is_empty() {
+            return Ok(None);
+        } else if from_end {
+            let count = line_range.trim_start_matches('-').trim_end_matches("..");
+            (parse_number(count, "line", partition_str)?, 1)
+        } else if exclusive_end {
+            let (start, end) = line_range.split_once("..").unwrap();
+            let (start, end) = (
+                parse_number(start, "line", partition_str)?,
+                parse_number(end, "line", partition_str)?,
+            );
+            if end <= start {
+                return Err(DoksError::InvalidPartition(format!(
+                    "Exclusive range {}..{} selects no lines",
+                    start, end
+                )));
+            }
+            (start, end - 1)
+        } else {
+            parse_range(line_range, "line", partition_str)?
         };
 
         let (start_col, end_col) = if let Some(col_range) = col_range {
             if col_range.is_empty() {
                 (None, None)
             } else {
-                let col_parts: Vec<&str> = col_range.split('-').collect();
-                match col_parts.len() {
-                    1 => {
-                        let col = col_parts[0].parse::<usize>()?;
-                        (Some(col), Some(col))
-                    }
-                    2 => {
-                        let start = col_parts[0].parse::<usize>()?;
-                        let end = col_parts[1].parse::<usize>()?;
-                        (Some(start), Some(end))
-                    }
-                    _ => return Err(anyhow!("Invalid column range format")),
+                let (start, end) = parse_range(col_range, "column", partition_str)?;
+                if start == 0 || end == 0 {
+                    return Err(DoksError::InvalidPartition(
+                        "Column numbers must be 1-indexed".to_string(),
+                    ));
                 }
+                (Some(start), Some(end))
             }
         } else {
             (None, None)
         };
 
-        Ok(Partition {
-            file_path,
+        if block {
+            match (start_col, end_col) {
+                (Some(start_col), Some(end_col)) if start_col > end_col => {
+                    return Err(DoksError::InvalidPartition(
+                        "Start column must be <= end column in a @@ window".to_string(),
+                    ));
+                }
+                (Some(_), Some(_)) => {}
+                _ => {
+                    return Err(DoksError::InvalidPartition(
+                        "A @@ column window requires columns".to_string(),
+                    ))
+                }
+            }
+        }
+
+        Ok(Some(Segment {
             start_line,
             end_line,
             start_col,
             end_col,
-        })
+            from_end,
+            block,
+            exclusive_end,
+        }))
     }
 
-    pub fn extract_content(&self) -> Result<String> {
-        let file_path = Path::new(&self.file_path);
-        if !file_path.exists() {
-            return Err(anyhow!("File not found: {}", self.file_path));
+    /// Validates the range against a file of `line_count` lines and returns
+    /// absolute, 1-indexed `(start, end)` line numbers.
+    fn resolve(&self, line_count: usize) -> Result<(usize, usize)> {
+        let (start, end) = (self.start_line, self.end_line);
+        if start == 0 || end == 0 {
+            return Err(DoksError::InvalidPartition(
+                "Line numbers must be 1-indexed".to_string(),
+            ));
         }
+        if start > line_count || end > line_count {
+            let requested = start.max(end);
+            return Err(if self.from_end {
+                DoksError::OutOfRange(format!(
+                    "Last {} lines requested, but the file has only {} lines",
+                    requested, line_count
+                ))
+            } else {
+                DoksError::OutOfRange(format!(
+                    "Line {} exceeds file length (file has {} lines)",
+                    requested, line_count
+                ))
+            });
+        }
+        let (start, end) = if self.from_end {
+            (line_count + 1 - start, line_count + 1 - end)
+        } else {
+            (start, end)
+        };
+        if start > end {
+            return Err(DoksError::InvalidPartition(
+                "Start line must be <= end line".to_string(),
+            ));
+        }
+        if let (Some(start_col), Some(end_col)) = (self.start_col, self.end_col) {
+            if start == end && start_col > end_col {
+                return Err(DoksError::InvalidPartition(
+                    "Start column must be <= end column on a single line".to_string(),
+                ));
+            }
+        }
+        Ok((start, end))
+    }
 
-        let content = std::fs::read_to_string(file_path)?;
-        let lines: Vec<&str> = content.lines().collect();
-
-        match (self.start_line, self.end_line) {
-            (Some(start), Some(end)) => {
-                if start == 0 || end == 0 {
-                    return Err(anyhow!("Line numbers must be 1-indexed"));
-                }
-                if start > lines.len() || end > lines.len() {
-                    return Err(anyhow!("Line numbers exceed file length"));
-                }
-                if start > end {
-                    return Err(anyhow!("Start line must be <= end line"));
-                }
-
-                let mut result = String::new();
-                for (idx, line) in lines.iter().enumerate().take(end).skip(start - 1) {
-                    let i = idx;
-                    let line = *line;
-                    let line_content = match (self.start_col, self.end_col) {
-                        (Some(start_col), Some(end_col)) => {
-                            if i == start - 1 && i == end - 1 {
-                                let chars: Vec<char> = line.chars().collect();
-                                if start_col > chars.len() || end_col > chars.len() {
-                                    return Err(anyhow!("Column numbers exceed line length"));
-                                }
-                                chars[(start_col - 1)..end_col].iter().collect()
-                            } else if i == start - 1 {
-                                let chars: Vec<char> = line.chars().collect();
-                                if start_col > chars.len() {
-                                    return Err(anyhow!("Start column exceeds line length"));
-                                }
-                                chars[(start_col - 1)..].iter().collect()
-                            } else if i == end - 1 {
-                                let chars: Vec<char> = line.chars().collect();
-                                if end_col > chars.len() {
-                                    return Err(anyhow!("End column exceeds line length"));
-                                }
-                                chars[..end_col].iter().collect()
-                            } else {
-                                line.to_string()
-                            }
+    fn extract(&self, lines: &[&str], start: usize, end: usize) -> Result<String> {
+        let mut result = String::new();
+        for (idx, line) in lines.iter().enumerate().take(end).skip(start - 1) {
+            let i = idx;
+            let line = *line;
+            let line_content = match (self.start_col, self.end_col) {
+                // Lines shorter than the window contribute what they have
+                (Some(start_col), Some(end_col)) if self.block => line
+                    .graphemes(true)
+                    .skip(start_col - 1)
+                    .take(end_col + 1 - start_col)
+                    .collect(),
+                (Some(start_col), Some(end_col)) => {
+                    if i == start - 1 && i == end - 1 {
+                        let graphemes: Vec<&str> = line.graphemes(true).collect();
+                        if start_col > graphemes.len() || end_col > graphemes.len() {
+                            return Err(DoksError::OutOfRange(
+                                "Column numbers exceed line length".to_string(),
+                            ));
                         }
-                        _ => line.to_string(),
-                    };
-
-                    if i > start - 1 {
-                        result.push('\n');
+                        graphemes[(start_col - 1)..end_col].concat()
+                    } else if i == start - 1 {
+                        let graphemes: Vec<&str> = line.graphemes(true).collect();
+                        if start_col > graphemes.len() {
+                            return Err(DoksError::OutOfRange(
+                                "Start column exceeds line length".to_string(),
+                            ));
+                        }
+                        graphemes[(start_col - 1)..].concat()
+                    } else if i == end - 1 {
+                        let graphemes: Vec<&str> = line.graphemes(true).collect();
+                        if end_col > graphemes.len() {
+                            return Err(DoksError::OutOfRange(
+                                "End column exceeds line length".to_string(),
+                            ));
+                        }
+                        graphemes[..end_col].concat()
+                    } else {
+                        line.to_string()
                     }
-                    result.push_str(&line_content);
                 }
-                Ok(result)
+                _ => line.to_string(),
+            };
+
+            if i > start - 1 {
+                result.push('\n');
             }
-            _ => Ok(content),
+            result.push_str(&line_content);
         }
+        Ok(result)
     }
 
-    #[allow(dead_code)]
     #[allow(clippy::inherent_to_string)]
-    pub fn to_string(&self) -> String {
-        let mut result = self.file_path.clone();
-
-        if let (Some(start_line), Some(end_line)) = (self.start_line, self.end_line) {
-            if start_line == end_line {
-                result.push_str(&format!(":{}", start_line));
-            } else {
-                result.push_str(&format!(":{}-{}", start_line, end_line));
-            }
-        }
+    fn to_string(&self) -> String {
+        let mut result = if self.from_end {
+            format!("-{}..", self.start_line)
+        } else if self.exclusive_end {
+            format!("{}..{}", self.start_line, self.end_line + 1)
+        } else if self.start_line == self.end_line {
+            format!("{}", self.start_line)
+        } else {
+            format!("{}-{}", self.start_line, self.end_line)
+        };
 
         if let (Some(start_col), Some(end_col)) = (self.start_col, self.end_col) {
-            if start_col == end_col {
+            if self.block {
+                result.push_str(&format!("@@{}-{}", start_col, end_col));
+            } else if start_col == end_col {
                 result.push_str(&format!("@{}", start_col));
             } else {
                 result.push_str(&format!("@{}-{}", start_col, end_col));
@@ -178,79 +835,432 @@ impl Partition {
     }
 }
 
+/// The lines strictly between the `doksnet:start <name>` and
+/// `doksnet:end <name>` markers, with the file line the region starts on.
+/// Markers may sit in any comment syntax (`//`, `#`, `<!-- -->`, ...).
+fn extract_anchor(content: &str, name: &str) -> Result<(usize, String)> {
+    let is_marker = |line: &str, kind: &str| {
+        line.split_once(&format!("doksnet:{}", kind))
+            .and_then(|(_, rest)| rest.split_whitespace().next())
+            == Some(name)
+    };
+
+    let lines: Vec<&str> = content.lines().collect();
+    let start = lines
+        .iter()
+        .position(|line| is_marker(line, "start"))
+        .ok_or_else(|| DoksError::AnchorNotFound(name.to_string()))?;
+    let end = lines[start + 1..]
+        .iter()
+        .position(|line| is_marker(line, "end"))
+        .map(|offset| start + 1 + offset)
+        .ok_or_else(|| DoksError::UnterminatedAnchor(name.to_string()))?;
+
+    Ok((start + 2, lines[start + 1..end].join("\n")))
+}
+
+/// Recognizes the `##Title` heading form, returning `None` for anything else.
+/// `#` directly followed by a digit is a byte range such as `#100-250`; a
+/// level 1 heading starting with a digit is written `# 2024 Release`.
+fn parse_heading(rest: &str) -> Option<Result<(usize, String)>> {
+    let title = rest.trim_start_matches('#');
+    let level = rest.len() - title.len();
+    if level == 0
+        || title.trim().is_empty()
+        || (level == 1 && title.starts_with(|c: char| c.is_ascii_digit()))
+    {
+        return None;
+    }
+    let title = title.trim();
+    if level > 6 {
+        return Some(Err(DoksError::InvalidPartition(format!(
+            "Heading level {} is deeper than Markdown allows (6)",
+            level
+        ))));
+    }
+    Some(Ok((level, title.to_string())))
+}
+
+fn is_markdown(file_path: &str) -> bool {
+    Path::new(file_path)
+        .extension()
+        .is_some_and(|ext| ["md", "markdown", "mdx"].iter().any(|e| ext == *e))
+}
+
+/// The level and text of an ATX heading line such as `## Install ##`.
+fn heading_of(line: &str) -> Option<(usize, &str)> {
+    let trimmed = line.trim_start_matches(' ');
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let text = trimmed.trim_start_matches('#');
+    let level = trimmed.len() - text.len();
+    if !(1..=6).contains(&level) || !(text.is_empty() || text.starts_with([' ', '\t'])) {
+        return None;
+    }
+    Some((level, text.trim().trim_end_matches('#').trim_end()))
+}
+
+/// The lines under the heading of `level` titled `title`, up to the next
+/// heading of the same or a higher level, with the file line they start on.
+/// Surrounding blank lines are dropped, and `#` lines inside fenced code
+/// blocks aren't treated as headings.
+fn extract_section(content: &str, level: usize, title: &str) -> Result<(usize, String)> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut in_fence = false;
+    let mut start = None;
+    let mut end = lines.len();
+
+    for (index, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        let Some((line_level, text)) = heading_of(line) else {
+            continue;
+        };
+        match start {
+            None if line_level == level && text == title => start = Some(index + 1),
+            Some(_) if line_level <= level => {
+                end = index;
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    let start = start
+        .ok_or_else(|| DoksError::HeadingNotFound(format!("{} {}", "#".repeat(level), title)))?;
+    let section = &lines[start..end];
+    let first = section
+        .iter()
+        .position(|line| !line.trim().is_empty())
+        .unwrap_or(section.len());
+    let last = section
+        .iter()
+        .rposition(|line| !line.trim().is_empty())
+        .map_or(first, |last| last + 1);
+
+    Ok((start + first + 1, section[first..last].join("\n")))
+}
+
+/// Drops a single trailing `\n` or `\r\n`, so a whole file hashes the same
+/// whether or not an editor added a final newline, just as its line ranges do.
+fn strip_final_newline(content: &str) -> &str {
+    match content.strip_suffix('\n') {
+        Some(rest) => rest.strip_suffix('\r').unwrap_or(rest),
+        None => content,
+    }
+}
+
+/// Parses an `N` or `N-M` range of lines or columns of `partition_str`.
+fn parse_range(range: &str, component: &str, partition_str: &str) -> Result<(usize, usize)> {
+    let parts: Vec<&str> = range.split('-').collect();
+    match parts[..] {
+        [value] => {
+            let value = parse_number(value, component, partition_str)?;
+            Ok((value, value))
+        }
+        [start, end] if !start.is_empty() && !end.is_empty() => Ok((
+            parse_number(start, component, partition_str)?,
+            parse_number(end, component, partition_str)?,
+        )),
+        // A leading or trailing '-', e.g. the negative column in `file:10@-5`
+        [_, _] => Err(invalid_number(range, component, partition_str)),
+        _ => Err(DoksError::InvalidPartition(format!(
+            "Invalid {} range format",
+            component
+        ))),
+    }
+}
+
+/// Parses a single line, column or byte number of `partition_str`.
+fn parse_number(value: &str, component: &str, partition_str: &str) -> Result<usize> {
+    value
+        .parse::<usize>()
+        .map_err(|_| invalid_number(value, component, partition_str))
+}
+
+fn invalid_number(value: &str, component: &str, partition_str: &str) -> DoksError {
+    DoksError::InvalidPartition(format!(
+        "Invalid {} value '{}' in partition '{}'",
+        component, value, partition_str
+    ))
+}
+
+/// Parses the `N` or `N-M` line range following an anchor name.
+fn parse_relative_range(range: &str, name: &str, partition_str: &str) -> Result<(usize, usize)> {
+    let (start, end) = parse_range(range, "line", partition_str)?;
+    if start == 0 {
+        return Err(DoksError::InvalidPartition(
+            "Line numbers must be 1-indexed".to_string(),
+        ));
+    }
+    if start > end {
+        return Err(DoksError::InvalidPartition(format!(
+            "Start line must be <= end line within anchor '{}'",
+            name
+        )));
+    }
+    Ok((start, end))
+}
+
+fn check_overlaps(ranges: &[(usize, usize)]) -> Result<()> {
+    for (i, &(start_a, end_a)) in ranges.iter().enumerate() {
+        for &(start_b, end_b) in &ranges[i + 1..] {
+            if start_a <= end_b && start_b <= end_a {
+                return Err(DoksError::InvalidPartition(format!(
+                    "Overlapping line ranges {}-{} and {}-{}",
+                    start_a, end_a, start_b, end_b
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::fs;
     use tempfile::tempdir;
 
+    fn segments(partition: &Partition) -> &[Segment] {
+        match &partition.addressing {
+            Addressing::Lines(segments) => segments,
+            _ => panic!("expected line addressing"),
+        }
+    }
+
     #[test]
     fn test_parse_file_only() {
         let partition = Partition::parse("src/main.rs").unwrap();
         assert_eq!(partition.file_path, "src/main.rs");
-        assert_eq!(partition.start_line, None);
-        assert_eq!(partition.end_line, None);
-        assert_eq!(partition.start_col, None);
-        assert_eq!(partition.end_col, None);
+        assert!(segments(&partition).is_empty());
+    }
+
+    #[test]
+    fn test_parse_with_line_range() {
+        let partition = Partition::parse("src/main.rs:10-20").unwrap();
+        assert_eq!(partition.file_path, "src/main.rs");
+        assert_eq!(segments(&partition)[0].start_line, 10);
+        assert_eq!(segments(&partition)[0].end_line, 20);
+        assert_eq!(segments(&partition)[0].start_col, None);
+        assert_eq!(segments(&partition)[0].end_col, None);
+    }
+
+    #[test]
+    fn test_parse_with_line_and_column_range() {
+        let partition = Partition::parse("src/main.rs:10-20@5-15").unwrap();
+        assert_eq!(partition.file_path, "src/main.rs");
+        assert_eq!(segments(&partition)[0].start_line, 10);
+        assert_eq!(segments(&partition)[0].end_line, 20);
+        assert_eq!(segments(&partition)[0].start_col, Some(5));
+        assert_eq!(segments(&partition)[0].end_col, Some(15));
+    }
+
+    #[test]
+    fn test_parse_single_line() {
+        let partition = Partition::parse("README.md:42").unwrap();
+        assert_eq!(partition.file_path, "README.md");
+        assert_eq!(segments(&partition)[0].start_line, 42);
+        assert_eq!(segments(&partition)[0].end_line, 42);
     }
 
-    #[test]
-    fn test_parse_with_line_range() {
-        let partition = Partition::parse("src/main.rs:10-20").unwrap();
-        assert_eq!(partition.file_path, "src/main.rs");
-        assert_eq!(partition.start_line, Some(10));
-        assert_eq!(partition.end_line, Some(20));
-        assert_eq!(partition.start_col, None);
-        assert_eq!(partition.end_col, None);
+    #[test]
+    fn test_parse_single_column() {
+        let partition = Partition::parse("file.txt:10@5").unwrap();
+        assert_eq!(partition.file_path, "file.txt");
+        assert_eq!(segments(&partition)[0].start_line, 10);
+        assert_eq!(segments(&partition)[0].end_line, 10);
+        assert_eq!(segments(&partition)[0].start_col, Some(5));
+        assert_eq!(segments(&partition)[0].end_col, Some(5));
+
+        for zero_column in ["file.txt:1@0", "file.txt:1@0-3", "file.txt:1-2@2-0"] {
+            assert_eq!(
+                Partition::parse(zero_column).unwrap_err(),
+                DoksError::InvalidPartition("Column numbers must be 1-indexed".to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_with_empty_ranges() {
+        let partition = Partition::parse("file.txt:@").unwrap();
+        assert_eq!(partition.file_path, "file.txt");
+        assert!(segments(&partition).is_empty());
+    }
+
+    #[test]
+    fn test_parse_invalid_format() {
+        let result = Partition::parse("");
+        assert!(result.is_err());
+
+        assert!(Partition::parse("file.txt:abc").is_err());
+        assert!(Partition::parse("file.txt:10@abc").is_err());
+
+        assert!(Partition::parse("file.txt:10-5").is_ok());
+    }
+
+    #[test]
+    fn test_parse_errors_name_component_and_value() {
+        let message = |partition: &str| Partition::parse(partition).unwrap_err().to_string();
+
+        assert_eq!(
+            message("file:10@-5"),
+            "Invalid column value '-5' in partition 'file:10@-5'"
+        );
+        assert_eq!(
+            message("file:10@2-x"),
+            "Invalid column value 'x' in partition 'file:10@2-x'"
+        );
+        assert_eq!(
+            message("file:ten-20"),
+            "Invalid line value 'ten' in partition 'file:ten-20'"
+        );
+        assert_eq!(
+            message("file:5,-3"),
+            "Invalid line value '-3' in partition 'file:5,-3'"
+        );
+        assert_eq!(
+            message("file:-x.."),
+            "Invalid line value 'x' in partition 'file:-x..'"
+        );
+        assert_eq!(
+            message("file:#10-z"),
+            "Invalid byte value 'z' in partition 'file:#10-z'"
+        );
+        assert_eq!(
+            message("file:@@auth@@2-"),
+            "Invalid line value '2-' in partition 'file:@@auth@@2-'"
+        );
+        assert_eq!(message("file:1-2-3"), "Invalid line range format");
+    }
+
+    #[test]
+    fn test_parse_from_end() {
+        let partition = Partition::parse("CHANGELOG.md:-5..").unwrap();
+        assert_eq!(partition.file_path, "CHANGELOG.md");
+        assert_eq!(segments(&partition)[0].start_line, 5);
+        assert_eq!(segments(&partition)[0].end_line, 1);
+        assert!(segments(&partition)[0].from_end);
+
+        assert!(Partition::parse("CHANGELOG.md:-abc..").is_err());
+    }
+
+    #[test]
+    fn test_extract_content_from_end() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("test.txt");
+        fs::write(&file_path, "line1\nline2\nline3\nline4\nline5").unwrap();
+
+        let partition = Partition::parse(&format!("{}:-2..", file_path.to_string_lossy())).unwrap();
+        assert_eq!(partition.extract_content().unwrap(), "line4\nline5");
+
+        fs::write(&file_path, "line1\nline2\nline3\nline4\nline5\nline6").unwrap();
+        assert_eq!(partition.extract_content().unwrap(), "line5\nline6");
+    }
+
+    #[test]
+    fn test_exclusive_and_inclusive_ranges() {
+        let content = (1..=25)
+            .map(|n| format!("line{}", n))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let expected = |start: usize, end: usize| {
+            (start..=end)
+                .map(|n| format!("line{}", n))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        let exclusive = Partition::parse("file.txt:10..20").unwrap();
+        assert_eq!(segments(&exclusive)[0].end_line, 19);
+        assert!(segments(&exclusive)[0].exclusive_end);
+        assert_eq!(exclusive.extract_from(&content).unwrap(), expected(10, 19));
+        assert_eq!(exclusive.to_string(), "file.txt:10..20");
+
+        let inclusive = Partition::parse("file.txt:10-20").unwrap();
+        assert!(!segments(&inclusive)[0].exclusive_end);
+        assert_eq!(inclusive.extract_from(&content).unwrap(), expected(10, 20));
+        assert_eq!(inclusive.to_string(), "file.txt:10-20");
+
+        let single = Partition::parse("file.txt:10..11@2-3").unwrap();
+        assert_eq!(single.extract_from(&content).unwrap(), "in");
+        assert_eq!(single.to_string(), "file.txt:10..11@2-3");
+
+        assert!(Partition::parse("file.txt:10..10").is_err());
+        assert!(Partition::parse("file.txt:10..abc").is_err());
     }
 
     #[test]
-    fn test_parse_with_line_and_column_range() {
-        let partition = Partition::parse("src/main.rs:10-20@5-15").unwrap();
-        assert_eq!(partition.file_path, "src/main.rs");
-        assert_eq!(partition.start_line, Some(10));
-        assert_eq!(partition.end_line, Some(20));
-        assert_eq!(partition.start_col, Some(5));
-        assert_eq!(partition.end_col, Some(15));
+    fn test_extract_content_from_end_shorter_file() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("test.txt");
+        fs::write(&file_path, "line1\nline2").unwrap();
+
+        let partition = Partition::parse(&format!("{}:-5..", file_path.to_string_lossy())).unwrap();
+        assert!(partition.extract_content().is_err());
+
+        let partition = Partition::parse(&format!("{}:-0..", file_path.to_string_lossy())).unwrap();
+        assert!(partition.extract_content().is_err());
     }
 
     #[test]
-    fn test_parse_single_line() {
-        let partition = Partition::parse("README.md:42").unwrap();
-        assert_eq!(partition.file_path, "README.md");
-        assert_eq!(partition.start_line, Some(42));
-        assert_eq!(partition.end_line, Some(42));
+    fn test_parse_multiple_segments() {
+        let partition = Partition::parse("src/lib.rs:5-8,40-55@3-10").unwrap();
+        assert_eq!(partition.file_path, "src/lib.rs");
+        assert_eq!(segments(&partition).len(), 2);
+        assert_eq!(segments(&partition)[0].start_line, 5);
+        assert_eq!(segments(&partition)[0].end_line, 8);
+        assert_eq!(segments(&partition)[1].start_line, 40);
+        assert_eq!(segments(&partition)[1].end_line, 55);
+        assert_eq!(segments(&partition)[1].start_col, Some(3));
+
+        assert!(Partition::parse("src/lib.rs:5-8,,40-55").is_err());
     }
 
     #[test]
-    fn test_parse_single_column() {
-        let partition = Partition::parse("file.txt:10@5").unwrap();
-        assert_eq!(partition.file_path, "file.txt");
-        assert_eq!(partition.start_line, Some(10));
-        assert_eq!(partition.end_line, Some(10));
-        assert_eq!(partition.start_col, Some(5));
-        assert_eq!(partition.end_col, Some(5));
+    fn test_extract_content_two_segments() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("test.txt");
+        fs::write(&file_path, "line1\nline2\nline3\nline4\nline5\nline6").unwrap();
+
+        let partition =
+            Partition::parse(&format!("{}:1-2,5", file_path.to_string_lossy())).unwrap();
+        assert_eq!(partition.extract_content().unwrap(), "line1\nline2\nline5");
     }
 
     #[test]
-    fn test_parse_with_empty_ranges() {
-        let partition = Partition::parse("file.txt:@").unwrap();
-        assert_eq!(partition.file_path, "file.txt");
-        assert_eq!(partition.start_line, None);
-        assert_eq!(partition.end_line, None);
-        assert_eq!(partition.start_col, None);
-        assert_eq!(partition.end_col, None);
+    fn test_extract_content_three_segments() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("test.txt");
+        fs::write(&file_path, "line1\nline2\nline3\nline4\nline5\nline6").unwrap();
+
+        let partition =
+            Partition::parse(&format!("{}:6,1,3-4", file_path.to_string_lossy())).unwrap();
+        assert_eq!(
+            partition.extract_content().unwrap(),
+            "line6\nline1\nline3\nline4"
+        );
     }
 
     #[test]
-    fn test_parse_invalid_format() {
-        let result = Partition::parse("");
-        assert!(result.is_err());
+    fn test_overlapping_segments() {
+        assert!(Partition::parse("file.txt:5-8,7-10").is_err());
+        assert!(Partition::parse("file.txt:5-8,9-10").is_ok());
 
-        assert!(Partition::parse("file.txt:abc").is_err());
-        assert!(Partition::parse("file.txt:10@abc").is_err());
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("test.txt");
+        fs::write(&file_path, "line1\nline2\nline3\nline4").unwrap();
 
-        assert!(Partition::parse("file.txt:10-5").is_ok());
+        let partition =
+            Partition::parse(&format!("{}:3,-2..", file_path.to_string_lossy())).unwrap();
+        assert!(partition.extract_content().is_err());
     }
 
     #[test]
@@ -261,10 +1271,7 @@ mod tests {
 
         let partition = Partition {
             file_path: file_path.to_string_lossy().to_string(),
-            start_line: None,
-            end_line: None,
-            start_col: None,
-            end_col: None,
+            addressing: Addressing::Lines(Vec::new()),
         };
 
         let content = partition.extract_content().unwrap();
@@ -279,10 +1286,15 @@ mod tests {
 
         let partition = Partition {
             file_path: file_path.to_string_lossy().to_string(),
-            start_line: Some(2),
-            end_line: Some(3),
-            start_col: None,
-            end_col: None,
+            addressing: Addressing::Lines(vec![Segment {
+                start_line: 2,
+                end_line: 3,
+                start_col: None,
+                end_col: None,
+                from_end: false,
+                block: false,
+                exclusive_end: false,
+            }]),
         };
 
         let content = partition.extract_content().unwrap();
@@ -297,10 +1309,15 @@ mod tests {
 
         let partition = Partition {
             file_path: file_path.to_string_lossy().to_string(),
-            start_line: Some(2),
-            end_line: Some(2),
-            start_col: None,
-            end_col: None,
+            addressing: Addressing::Lines(vec![Segment {
+                start_line: 2,
+                end_line: 2,
+                start_col: None,
+                end_col: None,
+                from_end: false,
+                block: false,
+                exclusive_end: false,
+            }]),
         };
 
         let content = partition.extract_content().unwrap();
@@ -315,10 +1332,15 @@ mod tests {
 
         let partition = Partition {
             file_path: file_path.to_string_lossy().to_string(),
-            start_line: Some(1),
-            end_line: Some(1),
-            start_col: Some(7),
-            end_col: Some(11),
+            addressing: Addressing::Lines(vec![Segment {
+                start_line: 1,
+                end_line: 1,
+                start_col: Some(7),
+                end_col: Some(11),
+                from_end: false,
+                block: false,
+                exclusive_end: false,
+            }]),
         };
 
         let content = partition.extract_content().unwrap();
@@ -333,27 +1355,822 @@ mod tests {
 
         let partition = Partition {
             file_path: file_path.to_string_lossy().to_string(),
-            start_line: Some(1),
-            end_line: Some(2),
-            start_col: Some(7),
-            end_col: Some(4),
+            addressing: Addressing::Lines(vec![Segment {
+                start_line: 1,
+                end_line: 2,
+                start_col: Some(7),
+                end_col: Some(4),
+                from_end: false,
+                block: false,
+                exclusive_end: false,
+            }]),
         };
 
         let content = partition.extract_content().unwrap();
         assert_eq!(content, "world\nrust");
     }
 
+    #[test]
+    fn test_parse_column_window() {
+        let partition = Partition::parse("src/main.rs:10-20@@5-40").unwrap();
+        let segment = &segments(&partition)[0];
+        assert_eq!((segment.start_line, segment.end_line), (10, 20));
+        assert_eq!((segment.start_col, segment.end_col), (Some(5), Some(40)));
+        assert!(segment.block);
+        assert_eq!(partition.to_string(), "src/main.rs:10-20@@5-40");
+
+        assert!(Partition::parse("src/main.rs:10-20@@").is_err());
+        assert!(Partition::parse("src/main.rs:10-20@@40-5").is_err());
+        assert_eq!(
+            Partition::parse("file:1-3@@0-2").unwrap_err(),
+            DoksError::InvalidPartition("Column numbers must be 1-indexed".to_string())
+        );
+        assert_eq!(
+            Partition::parse("f.txt:@@2-3").unwrap_err(),
+            DoksError::InvalidPartition("A @@ column window requires a line range".to_string())
+        );
+    }
+
+    #[test]
+    fn test_extract_content_column_window() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("test.txt");
+        fs::write(
+            &file_path,
+            "skip\n    let a = 1;\n    let bb = 2;\n    x\n\n    let c = 3;\n",
+        )
+        .unwrap();
+
+        let partition =
+            Partition::parse(&format!("{}:2-6@@5-10", file_path.to_string_lossy())).unwrap();
+        let content = partition.extract_content().unwrap();
+        // Line 4 is shorter than the window and line 5 is empty
+        assert_eq!(content, "let a \nlet bb\nx\n\nlet c ");
+    }
+
+    #[test]
+    fn test_columns_count_grapheme_clusters() {
+        // "e" followed by a combining acute accent renders as one character
+        let content = "cafe\u{301} au lait";
+        let partition = Partition::parse("menu.md:1@4-4").unwrap();
+        assert_eq!(partition.extract_from(content).unwrap(), "e\u{301}");
+        let partition = Partition::parse("menu.md:1@6-7").unwrap();
+        assert_eq!(partition.extract_from(content).unwrap(), "au");
+
+        // A flag is two regional indicator symbols
+        let content = "\u{1F1FA}\u{1F1F8} \u{1F1EB}\u{1F1F7}";
+        let partition = Partition::parse("flags.md:1@1-1").unwrap();
+        assert_eq!(
+            partition.extract_from(content).unwrap(),
+            "\u{1F1FA}\u{1F1F8}"
+        );
+        let partition = Partition::parse("flags.md:1@3-3").unwrap();
+        assert_eq!(
+            partition.extract_from(content).unwrap(),
+            "\u{1F1EB}\u{1F1F7}"
+        );
+        let partition = Partition::parse("flags.md:1@4-4").unwrap();
+        assert!(partition.extract_from(content).is_err());
+
+        let partition = Partition::parse("flags.md:1-2@@1-1").unwrap();
+        assert_eq!(
+            partition
+                .extract_from("\u{1F1FA}\u{1F1F8}!\ne\u{301}?")
+                .unwrap(),
+            "\u{1F1FA}\u{1F1F8}\ne\u{301}"
+        );
+    }
+
+    #[test]
+    fn test_extract_content_reversed_columns() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("test.txt");
+        fs::write(&file_path, "hello world\nrust programming").unwrap();
+
+        let partition =
+            Partition::parse(&format!("{}:1@10-3", file_path.to_string_lossy())).unwrap();
+        let err = partition.extract_content().unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Start column must be <= end column"));
+
+        let partition =
+            Partition::parse(&format!("{}:-1..@10-3", file_path.to_string_lossy())).unwrap();
+        assert!(partition.extract_content().is_err());
+    }
+
+    #[test]
+    fn test_parse_byte_range() {
+        let partition = Partition::parse("app.min.js:#100-250").unwrap();
+        assert_eq!(partition.file_path, "app.min.js");
+        assert_eq!(
+            partition.addressing,
+            Addressing::Bytes {
+                start: 100,
+                end: 250
+            }
+        );
+        assert_eq!(partition.to_string(), "app.min.js:#100-250");
+
+        assert!(Partition::parse("app.min.js:#100").is_err());
+        assert!(Partition::parse("app.min.js:#250-100").is_err());
+        assert!(Partition::parse("app.min.js:#a-b").is_err());
+    }
+
+    #[test]
+    fn test_extract_content_byte_range() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("app.min.js");
+        fs::write(&file_path, "var a=1;function run(){return a}run();").unwrap();
+        let path = file_path.to_string_lossy();
+
+        let partition = Partition::parse(&format!("{}:#8-32", path)).unwrap();
+        assert_eq!(
+            partition.extract_content().unwrap(),
+            "function run(){return a}"
+        );
+
+        let partition = Partition::parse(&format!("{}:#0-38", path)).unwrap();
+        assert_eq!(
+            partition.extract_content().unwrap(),
+            "var a=1;function run(){return a}run();"
+        );
+    }
+
+    #[test]
+    fn test_extract_content_byte_range_past_eof() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("app.min.js");
+        fs::write(&file_path, "short").unwrap();
+
+        let partition =
+            Partition::parse(&format!("{}:#2-40", file_path.to_string_lossy())).unwrap();
+        let err = partition.extract_content().unwrap_err();
+        assert!(err.to_string().contains("exceeds file length"));
+    }
+
+    #[test]
+    fn test_extract_content_byte_range_char_boundary() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("unicode.txt");
+        fs::write(&file_path, "héllo").unwrap();
+
+        // 'é' occupies bytes 1..3
+        let partition = Partition::parse(&format!("{}:#0-2", file_path.to_string_lossy())).unwrap();
+        assert!(partition.extract_content().is_err());
+
+        let partition = Partition::parse(&format!("{}:#0-3", file_path.to_string_lossy())).unwrap();
+        assert_eq!(partition.extract_content().unwrap(), "hé");
+    }
+
+    #[test]
+    fn test_extract_numbered_lines() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("test.rs");
+        fs::write(&file_path, "a\nb\nc\nd\ne\nf").unwrap();
+        let path = file_path.to_string_lossy();
+        let root = Path::new("");
+
+        let partition = Partition::parse(&format!("{}:2-3,5", path)).unwrap();
+        assert_eq!(
+            partition.extract_numbered_lines_in(root).unwrap(),
+            vec![
+                (2, "b".to_string()),
+                (3, "c".to_string()),
+                (5, "e".to_string())
+            ]
+        );
+
+        let partition = Partition::parse(&format!("{}:-2..", path)).unwrap();
+        let numbers: Vec<usize> = partition
+            .extract_numbered_lines_in(root)
+            .unwrap()
+            .into_iter()
+            .map(|(n, _)| n)
+            .collect();
+        assert_eq!(numbers, vec![5, 6]);
+
+        // Byte 4 is the start of line 3
+        let partition = Partition::parse(&format!("{}:#4-7", path)).unwrap();
+        assert_eq!(
+            partition.extract_numbered_lines_in(root).unwrap(),
+            vec![(3, "c".to_string()), (4, "d".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_extract_with_context() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("test.rs");
+        fs::write(&file_path, "a\nb\nc\nd\ne\nf").unwrap();
+        let path = file_path.to_string_lossy();
+        let root = Path::new("");
+
+        let partition = Partition::parse(&format!("{}:3-4@1-1", path)).unwrap();
+        assert_eq!(
+            partition.extract_with_context_in(root, 1).unwrap(),
+            vec![
+                (2, "b".to_string(), false),
+                (3, "c".to_string(), true),
+                (4, "d".to_string(), true),
+                (5, "e".to_string(), false)
+            ]
+        );
+
+        // Context stops at the start and end of the file
+        let partition = Partition::parse(&format!("{}:1-2", path)).unwrap();
+        let numbers: Vec<usize> = partition
+            .extract_with_context_in(root, 10)
+            .unwrap()
+            .into_iter()
+            .map(|(n, _, _)| n)
+            .collect();
+        assert_eq!(numbers, vec![1, 2, 3, 4, 5, 6]);
+
+        // The extracted content is unchanged
+        assert_eq!(partition.extract_content().unwrap(), "a\nb");
+    }
+
+    #[test]
+    fn test_clamped() {
+        let content = "a\nb\nc";
+
+        let partition = Partition::parse("file.rs:2-10").unwrap();
+        let (clamped, notes) = partition.clamped(content).unwrap();
+        assert_eq!(clamped.to_string(), "file.rs:2-3");
+        assert_eq!(clamped.extract_from(content).unwrap(), "b\nc");
+        assert_eq!(
+            notes,
+            vec!["line range 2-10 clamped to 2-3 (file has 3 lines)".to_string()]
+        );
+
+        // Ranges that fit are kept as they are
+        let partition = Partition::parse("file.rs:1,2..9").unwrap();
+        let (clamped, notes) = partition.clamped(content).unwrap();
+        assert_eq!(clamped.to_string(), "file.rs:1,2-3");
+        assert_eq!(notes.len(), 1);
+
+        // The column window only keeps its start once the end line is gone
+        let partition = Partition::parse("file.rs:1-5@1-1").unwrap();
+        let (clamped, _) = partition.clamped(content).unwrap();
+        assert_eq!(clamped.extract_from(content).unwrap(), "a\nb\nc");
+
+        assert!(Partition::parse("file.rs:1-3")
+            .unwrap()
+            .clamped(content)
+            .is_none());
+        assert!(Partition::parse("file.rs:5-9")
+            .unwrap()
+            .clamped(content)
+            .is_none());
+        assert!(Partition::parse("file.rs:#0-1")
+            .unwrap()
+            .clamped(content)
+            .is_none());
+    }
+
+    #[test]
+    fn test_covered_lines() {
+        let content = "a\nb\nc\nd\ne\n";
+
+        let partition = Partition::parse("file.rs:2-3,5").unwrap();
+        assert_eq!(
+            partition.covered_lines(content).unwrap(),
+            vec![(2, 3), (5, 5)]
+        );
+
+        let partition = Partition::parse("file.rs:4@1-1").unwrap();
+        assert_eq!(partition.covered_lines(content).unwrap(), vec![(4, 4)]);
+
+        let partition = Partition::parse("file.rs:#2-5").unwrap();
+        assert_eq!(partition.covered_lines(content).unwrap(), vec![(2, 3)]);
+
+        let partition = Partition::parse("file.rs").unwrap();
+        assert_eq!(partition.covered_lines(content).unwrap(), vec![(1, 5)]);
+    }
+
+    #[test]
+    fn test_parse_anchor() {
+        let partition = Partition::parse("src/lib.rs:@@auth@@").unwrap();
+        assert_eq!(partition.file_path, "src/lib.rs");
+        assert_eq!(
+            partition.addressing,
+            Addressing::Anchor {
+                name: "auth".to_string(),
+                lines: None
+            }
+        );
+        assert_eq!(partition.to_string(), "src/lib.rs:@@auth@@");
+
+        let partition = Partition::parse("src/lib.rs:@@auth@@2-4").unwrap();
+        assert_eq!(
+            partition.addressing,
+            Addressing::Anchor {
+                name: "auth".to_string(),
+                lines: Some((2, 4))
+            }
+        );
+        assert_eq!(partition.to_string(), "src/lib.rs:@@auth@@2-4");
+        assert_eq!(
+            Partition::parse("src/lib.rs:@@auth@@3")
+                .unwrap()
+                .to_string(),
+            "src/lib.rs:@@auth@@3"
+        );
+
+        assert!(Partition::parse("src/lib.rs:@@auth@@4-2").is_err());
+        assert!(Partition::parse("src/lib.rs:@@auth@@0-2").is_err());
+        assert!(Partition::parse("src/lib.rs:@@auth@@x").is_err());
+
+        assert!(Partition::parse("src/lib.rs:@@@@").is_err());
+        assert!(Partition::parse("src/lib.rs:@@two words@@").is_err());
+    }
+
+    #[test]
+    fn test_extract_content_anchor() {
+        let content = "use std::io;\n\
+            // doksnet:start authenticate\n\
+            // doksnet:start auth\n\
+            fn login() {}\n\
+            fn logout() {}\n\
+            // doksnet:end auth\n\
+            // doksnet:end authenticate\n";
+
+        let partition = Partition::parse("src/lib.rs:@@auth@@").unwrap();
+        assert_eq!(
+            partition.extract_from(content).unwrap(),
+            "fn login() {}\nfn logout() {}"
+        );
+        assert_eq!(partition.covered_lines(content).unwrap(), vec![(4, 5)]);
+
+        // Lines added above the region don't affect it
+        let shifted = format!("// new header\n\n{}", content);
+        assert_eq!(
+            partition.extract_from(&shifted).unwrap(),
+            "fn login() {}\nfn logout() {}"
+        );
+
+        let markdown = "Intro\n<!-- doksnet:start usage -->\nRun it.\n<!-- doksnet:end usage -->\n";
+        let partition = Partition::parse("README.md:@@usage@@").unwrap();
+        assert_eq!(partition.extract_from(markdown).unwrap(), "Run it.");
+    }
+
+    #[test]
+    fn test_extract_content_anchor_relative_lines() {
+        let content = "use std::io;\n\
+            // doksnet:start auth\n\
+            fn login() {}\n\
+            fn logout() {}\n\
+            fn refresh() {}\n\
+            // doksnet:end auth\n";
+
+        let partition = Partition::parse("src/lib.rs:@@auth@@2-3").unwrap();
+        assert_eq!(
+            partition.extract_from(content).unwrap(),
+            "fn logout() {}\nfn refresh() {}"
+        );
+        assert_eq!(partition.covered_lines(content).unwrap(), vec![(4, 5)]);
+
+        let partition = Partition::parse("src/lib.rs:@@auth@@1").unwrap();
+        assert_eq!(partition.extract_from(content).unwrap(), "fn login() {}");
+
+        let partition = Partition::parse("src/lib.rs:@@auth@@2-4").unwrap();
+        assert_eq!(
+            partition.extract_from(content),
+            Err(DoksError::OutOfRange(
+                "Line 4 exceeds anchor 'auth' (anchor has 3 lines)".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_extract_content_missing_anchor() {
+        let partition = Partition::parse("src/lib.rs:@@billing@@").unwrap();
+        let err = partition
+            .extract_from("// doksnet:start auth\nfn login() {}\n// doksnet:end auth\n")
+            .unwrap_err();
+        assert!(err.to_string().contains("Anchor 'billing' not found"));
+    }
+
+    #[test]
+    fn test_extract_content_unterminated_anchor() {
+        let partition = Partition::parse("src/lib.rs:@@auth@@").unwrap();
+        let err = partition
+            .extract_from("// doksnet:start auth\nfn login() {}\n// doksnet:end other\n")
+            .unwrap_err();
+        assert!(err.to_string().contains("Anchor 'auth' is not terminated"));
+    }
+
+    #[test]
+    fn test_parse_heading() {
+        let partition = Partition::parse("README.md:##Installation").unwrap();
+        assert_eq!(
+            partition.addressing,
+            Addressing::Heading {
+                level: 2,
+                title: "Installation".to_string()
+            }
+        );
+        assert_eq!(partition.to_string(), "README.md:##Installation");
+        assert_eq!(
+            Partition::parse("docs/guide.md:### Step 1: Setup")
+                .unwrap()
+                .to_string(),
+            "docs/guide.md:###Step 1: Setup"
+        );
+
+        // A byte range is still a byte range
+        assert!(matches!(
+            Partition::parse("README.md:#100-250").unwrap().addressing,
+            Addressing::Bytes { .. }
+        ));
+        assert_eq!(
+            Partition::parse("CHANGELOG.md:# 2024 Release")
+                .unwrap()
+                .to_string(),
+            "CHANGELOG.md:# 2024 Release"
+        );
+        assert!(Partition::parse("src/lib.rs:##Installation").is_err());
+        assert!(Partition::parse("README.md:#######Too deep").is_err());
+    }
+
+    #[test]
+    fn test_extract_content_heading() {
+        let content = "# Project\n\
+            \n\
+            Intro.\n\
+            \n\
+            ## Installation\n\
+            \n\
+            Run `cargo install`.\n\
+            \n\
+            ## Usage ##\n\
+            Run it.\n";
+
+        let partition = Partition::parse("README.md:##Installation").unwrap();
+        assert_eq!(
+            partition.extract_from(content).unwrap(),
+            "Run `cargo install`."
+        );
+        assert_eq!(partition.covered_lines(content).unwrap(), vec![(7, 7)]);
+
+        // Closing hashes aren't part of the title, and the last section runs to the end
+        let partition = Partition::parse("README.md:##Usage").unwrap();
+        assert_eq!(partition.extract_from(content).unwrap(), "Run it.");
+    }
+
+    #[test]
+    fn test_extract_content_heading_with_subsections() {
+        let content = "## Installation\n\
+            Pick one:\n\
+            ### From crates.io\n\
+            cargo install doksnet\n\
+            ```sh\n\
+            # not a heading\n\
+            ```\n\
+            ### From source\n\
+            cargo build\n\
+            # License\n\
+            MIT\n";
+
+        let partition = Partition::parse("README.md:##Installation").unwrap();
+        assert_eq!(
+            partition.extract_from(content).unwrap(),
+            "Pick one:\n### From crates.io\ncargo install doksnet\n```sh\n# not a heading\n```\n### From source\ncargo build"
+        );
+
+        let partition = Partition::parse("README.md:###From crates.io").unwrap();
+        assert_eq!(
+            partition.extract_from(content).unwrap(),
+            "cargo install doksnet\n```sh\n# not a heading\n```"
+        );
+    }
+
+    #[test]
+    fn test_extract_content_missing_heading() {
+        let partition = Partition::parse("README.md:##Installation").unwrap();
+        let err = partition
+            .extract_from("# Installation\nText\n### Installation\nMore\n")
+            .unwrap_err();
+        assert_eq!(
+            err,
+            DoksError::HeadingNotFound("## Installation".to_string())
+        );
+        assert_eq!(err.to_string(), "Heading '## Installation' not found");
+    }
+
+    #[test]
+    fn test_content_cache_reads_file_once() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("test.rs");
+        let lines: Vec<String> = (1..=10).map(|i| format!("line {}", i)).collect();
+        fs::write(&file_path, lines.join("\n")).unwrap();
+
+        let cache = ContentCache::default();
+        for (i, line) in lines.iter().enumerate() {
+            let partition =
+                Partition::parse(&format!("{}:{}", file_path.to_string_lossy(), i + 1)).unwrap();
+            assert_eq!(&partition.extract_content_cached(&cache).unwrap(), line);
+
+            // Every later partition must be served without touching the disk
+            if i == 0 {
+                fs::remove_file(&file_path).unwrap();
+            }
+        }
+
+        let missing = Partition::parse(&file_path.to_string_lossy()).unwrap();
+        assert!(missing.extract_content().is_err());
+        assert!(missing.extract_content_cached(&cache).is_ok());
+    }
+
+    #[test]
+    fn test_content_cache_missing_file() {
+        let cache = ContentCache::default();
+        let partition = Partition::parse("nonexistent.txt:1").unwrap();
+        let err = partition.extract_content_cached(&cache).unwrap_err();
+        assert!(err.to_string().contains("File not found"));
+    }
+
+    #[test]
+    fn test_crlf_file_hashes_like_lf_file() {
+        use crate::hash::{hash_content, HashOptions};
+
+        let dir = tempdir().unwrap();
+        let crlf_path = dir.path().join("crlf.rs");
+        let lf_path = dir.path().join("lf.rs");
+        fs::write(&crlf_path, "fn main() {\r\n    run();\r\n}\r\n").unwrap();
+        fs::write(&lf_path, "fn main() {\n    run();\n}\n").unwrap();
+
+        for range in ["", ":1-3"] {
+            let crlf = Partition::parse(&format!("{}{}", crlf_path.to_string_lossy(), range))
+                .unwrap()
+                .extract_content()
+                .unwrap();
+            let lf = Partition::parse(&format!("{}{}", lf_path.to_string_lossy(), range))
+                .unwrap()
+                .extract_content()
+                .unwrap();
+            assert_eq!(
+                hash_content(&crlf, HashOptions::default()),
+                hash_content(&lf, HashOptions::default())
+            );
+        }
+    }
+
+    #[test]
+    fn test_final_newline_does_not_change_content() {
+        for range in ["", ":1-2", ":2", ":-1..", ":@@usage@@"] {
+            let partition = Partition::parse(&format!("README.md{}", range)).unwrap();
+            let content = "<!-- doksnet:start usage -->\nRun it.\n<!-- doksnet:end usage -->";
+            let without = partition.extract_from(content).unwrap();
+            for ending in ["\n", "\r\n"] {
+                let with = partition
+                    .extract_from(&format!("{}{}", content, ending))
+                    .unwrap();
+                assert_eq!(with, without, "partition README.md{}", range);
+            }
+        }
+
+        // Only one final newline is dropped; a trailing blank line is content
+        let partition = Partition::parse("README.md").unwrap();
+        assert_eq!(partition.extract_from("Run it.\n\n").unwrap(), "Run it.\n");
+    }
+
     #[test]
     fn test_extract_content_file_not_found() {
         let partition = Partition {
             file_path: "nonexistent.txt".to_string(),
-            start_line: None,
-            end_line: None,
-            start_col: None,
-            end_col: None,
+            addressing: Addressing::Lines(Vec::new()),
         };
 
-        assert!(partition.extract_content().is_err());
+        assert!(matches!(
+            partition.extract_content(),
+            Err(DoksError::FileNotFound { path, .. }) if path == "nonexistent.txt"
+        ));
+    }
+
+    #[test]
+    fn test_extract_line_range_stops_at_last_line() {
+        let dir = tempdir().unwrap();
+        // Invalid UTF-8 after line 3 makes reading the whole file fail
+        let mut bytes = b"one\ntwo\nthree\n".to_vec();
+        bytes.extend([0xFF; 4096]);
+        fs::write(dir.path().join("big.txt"), bytes).unwrap();
+
+        let partition = Partition::parse("big.txt:2-3").unwrap();
+        assert_eq!(
+            partition.extract_content_in(dir.path()).unwrap(),
+            "two\nthree"
+        );
+        let partition = Partition::parse("big.txt:1,3@1-3").unwrap();
+        assert_eq!(
+            partition.extract_content_in(dir.path()).unwrap(),
+            "one\nthr"
+        );
+
+        let whole = Partition::parse("big.txt").unwrap();
+        assert!(matches!(
+            whole.extract_content_in(dir.path()),
+            Err(DoksError::Io { .. })
+        ));
+        let from_end = Partition::parse("big.txt:-1..").unwrap();
+        assert!(from_end.extract_content_in(dir.path()).is_err());
+
+        // Short files still report the range they don't have
+        fs::write(dir.path().join("short.txt"), "a\nb").unwrap();
+        let partition = Partition::parse("short.txt:2-5").unwrap();
+        assert_eq!(
+            partition.extract_content_in(dir.path()).unwrap_err(),
+            DoksError::OutOfRange("Line 5 exceeds file length (file has 2 lines)".to_string())
+        );
+    }
+
+    #[test]
+    fn test_extract_reversed_line_range() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("g.txt"), "1\n2\n3\n4\n5\n").unwrap();
+
+        let partition = Partition::parse("g.txt:4-2").unwrap();
+        assert_eq!(
+            partition.extract_content_in(dir.path()).unwrap_err(),
+            DoksError::InvalidPartition("Start line must be <= end line".to_string())
+        );
+    }
+
+    #[test]
+    fn test_read_file_with_limit() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("file.txt"), "0123456789").unwrap();
+
+        assert_eq!(
+            read_file_with_limit(dir.path(), "file.txt", 10).unwrap(),
+            "0123456789"
+        );
+        assert_eq!(
+            read_file_with_limit(dir.path(), "file.txt", 9).unwrap_err(),
+            DoksError::FileTooLarge {
+                path: "file.txt".to_string(),
+                size: 10,
+                limit: 9,
+            }
+        );
+    }
+
+    #[test]
+    fn test_extract_content_from_glob() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("target/nested")).unwrap();
+        fs::write(dir.path().join("target/doc-1.4.2.md"), "# Docs\nv1.4.2").unwrap();
+        fs::write(dir.path().join("target/nested/doc-0.1.md"), "old").unwrap();
+
+        let partition = Partition::parse("target/doc-*.md:2").unwrap();
+        assert_eq!(partition.extract_content_in(dir.path()).unwrap(), "v1.4.2");
+        assert_eq!(
+            partition.locate_in(dir.path()).unwrap(),
+            dir.path().join("target/doc-1.4.2.md")
+        );
+
+        let partition = Partition::parse("target/api-*.md").unwrap();
+        assert_eq!(
+            partition.extract_content_in(dir.path()).unwrap_err(),
+            DoksError::FileNotFound {
+                path: "target/api-*.md".to_string(),
+                suggestions: Vec::new(),
+            }
+        );
+
+        let partition = Partition::parse("target/**/doc-*.md").unwrap();
+        assert_eq!(
+            partition.extract_content_in(dir.path()).unwrap_err(),
+            DoksError::AmbiguousGlob {
+                pattern: "target/**/doc-*.md".to_string(),
+                matches: vec![
+                    "target/doc-1.4.2.md".to_string(),
+                    "target/nested/doc-0.1.md".to_string(),
+                ],
+            }
+        );
+
+        // A file whose name looks like a glob is read as it is
+        fs::write(dir.path().join("target/[id].md"), "literal").unwrap();
+        fs::write(dir.path().join("target/i.md"), "glob match").unwrap();
+        let partition = Partition::parse("target/[id].md").unwrap();
+        assert_eq!(partition.extract_content_in(dir.path()).unwrap(), "literal");
+    }
+
+    #[test]
+    fn test_extract_content_suggests_similar_file() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/main.rs"), "fn main() {}").unwrap();
+
+        let partition = Partition::parse("src/mian.rs:1").unwrap();
+        let err = partition.extract_content_in(dir.path()).unwrap_err();
+        assert_eq!(
+            err,
+            DoksError::FileNotFound {
+                path: "src/mian.rs".to_string(),
+                suggestions: vec!["src/main.rs".to_string()],
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "File not found: src/mian.rs (did you mean 'src/main.rs'?)"
+        );
+    }
+
+    #[test]
+    fn test_expand_env_vars() {
+        std::env::set_var("DOKSNET_TEST_EXPAND", "target/out");
+
+        assert_eq!(
+            expand_env_vars("$DOKSNET_TEST_EXPAND/generated.md").unwrap(),
+            "target/out/generated.md"
+        );
+        assert_eq!(
+            expand_env_vars("${DOKSNET_TEST_EXPAND}_v2.md").unwrap(),
+            "target/out_v2.md"
+        );
+        assert_eq!(
+            expand_env_vars("docs/$$price.md").unwrap(),
+            "docs/$price.md"
+        );
+        assert_eq!(expand_env_vars("docs/a$.md").unwrap(), "docs/a$.md");
+        assert!(matches!(
+            expand_env_vars("${DOKSNET_TEST_EXPAND/x.md"),
+            Err(DoksError::InvalidPartition(_))
+        ));
+    }
+
+    #[test]
+    fn test_extract_content_from_env_var_path() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("generated.md"), "line1\nline2").unwrap();
+        std::env::set_var("DOKSNET_TEST_OUT_DIR", dir.path());
+
+        let partition = Partition::parse("${DOKSNET_TEST_OUT_DIR}/generated.md:2").unwrap();
+        assert_eq!(partition.extract_content().unwrap(), "line2");
+        assert_eq!(
+            partition.expanded_path().unwrap(),
+            format!("{}/generated.md", dir.path().display())
+        );
+    }
+
+    #[test]
+    fn test_extract_content_unset_env_var() {
+        let partition = Partition::parse("$DOKSNET_TEST_UNSET/generated.md").unwrap();
+        let error = partition.extract_content().unwrap_err();
+
+        assert_eq!(
+            error,
+            DoksError::UnsetVariable {
+                name: "DOKSNET_TEST_UNSET".to_string(),
+                path: "$DOKSNET_TEST_UNSET/generated.md".to_string(),
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "Environment variable 'DOKSNET_TEST_UNSET' used in '$DOKSNET_TEST_UNSET/generated.md' is not set"
+        );
+    }
+
+    #[test]
+    fn test_error_kinds() {
+        assert!(matches!(
+            Partition::parse("src/lib.rs:abc"),
+            Err(DoksError::InvalidPartition(_))
+        ));
+        assert!(matches!(
+            Partition::parse("src/lib.rs:5-9")
+                .unwrap()
+                .extract_from("one line"),
+            Err(DoksError::OutOfRange(_))
+        ));
+        assert_eq!(
+            Partition::parse("src/lib.rs:@@auth@@")
+                .unwrap()
+                .extract_from("fn main() {}"),
+            Err(DoksError::AnchorNotFound("auth".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_line_range_past_eof_reports_line_count() {
+        let content = (1..=42)
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let partition = Partition::parse("src/lib.rs:40-50").unwrap();
+        let err = partition.extract_from(&content).unwrap_err().to_string();
+        assert_eq!(err, "Line 50 exceeds file length (file has 42 lines)");
+
+        let partition = Partition::parse("CHANGELOG.md:-50..").unwrap();
+        let err = partition.extract_from(&content).unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "Last 50 lines requested, but the file has only 42 lines"
+        );
     }
 
     #[test]
@@ -364,28 +2181,43 @@ mod tests {
 
         let partition = Partition {
             file_path: file_path.to_string_lossy().to_string(),
-            start_line: Some(0),
-            end_line: Some(1),
-            start_col: None,
-            end_col: None,
+            addressing: Addressing::Lines(vec![Segment {
+                start_line: 0,
+                end_line: 1,
+                start_col: None,
+                end_col: None,
+                from_end: false,
+                block: false,
+                exclusive_end: false,
+            }]),
         };
         assert!(partition.extract_content().is_err());
 
         let partition = Partition {
             file_path: file_path.to_string_lossy().to_string(),
-            start_line: Some(1),
-            end_line: Some(5),
-            start_col: None,
-            end_col: None,
+            addressing: Addressing::Lines(vec![Segment {
+                start_line: 1,
+                end_line: 5,
+                start_col: None,
+                end_col: None,
+                from_end: false,
+                block: false,
+                exclusive_end: false,
+            }]),
         };
         assert!(partition.extract_content().is_err());
 
         let partition = Partition {
             file_path: file_path.to_string_lossy().to_string(),
-            start_line: Some(2),
-            end_line: Some(1),
-            start_col: None,
-            end_col: None,
+            addressing: Addressing::Lines(vec![Segment {
+                start_line: 2,
+                end_line: 1,
+                start_col: None,
+                end_col: None,
+                from_end: false,
+                block: false,
+                exclusive_end: false,
+            }]),
         };
         assert!(partition.extract_content().is_err());
     }
@@ -394,29 +2226,42 @@ mod tests {
     fn test_to_string() {
         let partition = Partition {
             file_path: "src/main.rs".to_string(),
-            start_line: Some(10),
-            end_line: Some(20),
-            start_col: Some(5),
-            end_col: Some(15),
+            addressing: Addressing::Lines(vec![Segment {
+                start_line: 10,
+                end_line: 20,
+                start_col: Some(5),
+                end_col: Some(15),
+                from_end: false,
+                block: false,
+                exclusive_end: false,
+            }]),
         };
         assert_eq!(partition.to_string(), "src/main.rs:10-20@5-15");
 
         let partition = Partition {
             file_path: "README.md".to_string(),
-            start_line: Some(5),
-            end_line: Some(5),
-            start_col: None,
-            end_col: None,
+            addressing: Addressing::Lines(vec![Segment {
+                start_line: 5,
+                end_line: 5,
+                start_col: None,
+                end_col: None,
+                from_end: false,
+                block: false,
+                exclusive_end: false,
+            }]),
         };
         assert_eq!(partition.to_string(), "README.md:5");
 
         let partition = Partition {
             file_path: "file.txt".to_string(),
-            start_line: None,
-            end_line: None,
-            start_col: None,
-            end_col: None,
+            addressing: Addressing::Lines(Vec::new()),
         };
         assert_eq!(partition.to_string(), "file.txt");
+
+        let partition = Partition::parse("CHANGELOG.md:-5..@1-10").unwrap();
+        assert_eq!(partition.to_string(), "CHANGELOG.md:-5..@1-10");
+
+        let partition = Partition::parse("src/lib.rs:5-8,40-55@3-10,60").unwrap();
+        assert_eq!(partition.to_string(), "src/lib.rs:5-8,40-55@3-10,60");
     }
 }
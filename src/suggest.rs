@@ -0,0 +1,132 @@
+use std::collections::VecDeque;
+use std::path::Path;
+
+/// Most paths offered for a missing file.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Directory entries looked at before giving up, so a missing file in a huge
+/// tree doesn't stall the run.
+const MAX_SCANNED_ENTRIES: usize = 10_000;
+
+/// Directories that hold build output or dependencies rather than files a
+/// partition would point into. Hidden directories are skipped too.
+const SKIPPED_DIRS: [&str; 2] = ["target", "node_modules"];
+
+/// Existing files under `root` whose name is close to `missing`'s, e.g. the
+/// same file in another directory or a one-letter typo, best match first.
+/// Paths are relative to `root` and use `/` as the separator.
+pub(crate) fn similar_paths(root: &Path, missing: &str) -> Vec<String> {
+    let missing_name = file_name(missing).to_lowercase();
+    let max_distance = 2.max(missing_name.chars().count() / 4);
+
+    let mut candidates: Vec<(usize, usize, String)> = list_files(root)
+        .into_iter()
+        .filter(|path| path != missing)
+        .filter_map(|path| {
+            let distance = edit_distance(&missing_name, &file_name(&path).to_lowercase());
+            (distance <= max_distance).then(|| (distance, edit_distance(missing, &path), path))
+        })
+        .collect();
+
+    candidates.sort();
+    candidates
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, _, path)| path)
+        .collect()
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().unwrap_or(path)
+}
+
+/// Files under `root`, breadth first so shallow files are found before the
+/// scan limit is reached.
+fn list_files(root: &Path) -> Vec<String> {
+    let root = if root.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        root
+    };
+
+    let mut files = Vec::new();
+    let mut scanned = 0;
+    let mut pending = VecDeque::from([(root.to_path_buf(), String::new())]);
+    while let Some((dir, prefix)) = pending.pop_front() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            scanned += 1;
+            if scanned > MAX_SCANNED_ENTRIES {
+                return files;
+            }
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let relative = format!("{}{}", prefix, name);
+            match entry.file_type() {
+                Ok(kind)
+                    if kind.is_dir()
+                        && !name.starts_with('.')
+                        && !SKIPPED_DIRS.contains(&name.as_str()) =>
+                {
+                    pending.push_back((entry.path(), format!("{}/", relative)));
+                }
+                Ok(kind) if kind.is_file() => files.push(relative),
+                _ => {}
+            }
+        }
+    }
+    files
+}
+
+/// Levenshtein distance between `a` and `b`, counted in characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("main.rs", "main.rs"), 0);
+        assert_eq!(edit_distance("mian.rs", "main.rs"), 2);
+        assert_eq!(edit_distance("lib.rs", "libs.rs"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_similar_paths() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src/auth")).unwrap();
+        fs::create_dir_all(dir.path().join("target/debug")).unwrap();
+        fs::write(dir.path().join("src/main.rs"), "").unwrap();
+        fs::write(dir.path().join("src/auth/login.rs"), "").unwrap();
+        fs::write(dir.path().join("src/unrelated.rs"), "").unwrap();
+        fs::write(dir.path().join("target/debug/main.rs"), "").unwrap();
+
+        assert_eq!(
+            similar_paths(dir.path(), "src/mian.rs"),
+            vec!["src/main.rs"]
+        );
+        // Same name, other directory
+        assert_eq!(
+            similar_paths(dir.path(), "src/login.rs"),
+            vec!["src/auth/login.rs"]
+        );
+        assert!(similar_paths(dir.path(), "docs/guide.md").is_empty());
+    }
+}
@@ -0,0 +1,249 @@
+use rayon::prelude::*;
+use std::path::Path;
+
+use crate::config::{DoksConfig, Mapping};
+use crate::error::DoksError;
+use crate::hash::{changed_lines, check_hash, line_digests, HashOptions};
+use crate::partition::{ContentCache, Partition};
+
+/// The outcome of checking one mapping against the files it points into.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MappingResult {
+    pub id: String,
+    pub passed: bool,
+    /// One human-readable description per failing side of the mapping.
+    pub errors: Vec<String>,
+    /// A side couldn't be checked at all: its partition doesn't parse, its
+    /// content can't be extracted or its stored hash is malformed. Reported
+    /// as an error rather than a failure, which is content that drifted.
+    pub broken: bool,
+    /// Things worth reporting that don't fail the mapping, e.g. a line range
+    /// cut short at the end of its file by `DoksConfig::clamp_ranges`.
+    pub warnings: Vec<String>,
+}
+
+/// Content caches for the documentation and code sides of mappings. The two
+/// sides share one cache unless they resolve from different directories.
+pub struct SideCaches {
+    doc: ContentCache,
+    code: Option<ContentCache>,
+}
+
+impl SideCaches {
+    pub fn new(doc_root: &Path, code_root: &Path) -> Self {
+        Self {
+            doc: ContentCache::new(doc_root),
+            code: (code_root != doc_root).then(|| ContentCache::new(code_root)),
+        }
+    }
+
+    /// Caches resolving from `config.doc_root()` and `config.code_root()`.
+    pub fn for_config(config: &DoksConfig) -> Self {
+        Self::new(config.doc_root(), config.code_root())
+    }
+
+    pub fn doc(&self) -> &ContentCache {
+        &self.doc
+    }
+
+    pub fn code(&self) -> &ContentCache {
+        self.code.as_ref().unwrap_or(&self.doc)
+    }
+}
+
+/// Verifies every mapping concurrently, resolving partitions against
+/// `base_dir`, or `config.doc_base`/`config.code_base` when set. Results are
+/// returned in the same order as `config.mappings` so reports stay
+/// deterministic. Files are read once per run, however many mappings point
+/// into them.
+pub fn verify_all(config: &DoksConfig, base_dir: &Path) -> Vec<MappingResult> {
+    verify_all_with_progress(config, base_dir, |_| {})
+}
+
+/// Like `verify_all`, calling `on_verified` as each mapping finishes, e.g. to
+/// advance a progress bar. Calls come from worker threads in no particular
+/// order, unless `config.parallelism` is 1.
+pub fn verify_all_with_progress(
+    config: &DoksConfig,
+    base_dir: &Path,
+    on_verified: impl Fn(&MappingResult) + Sync,
+) -> Vec<MappingResult> {
+    let caches = SideCaches::new(
+        config.doc_base.as_deref().unwrap_or(base_dir),
+        config.code_base.as_deref().unwrap_or(base_dir),
+    );
+    let verify = || {
+        config
+            .mappings
+            .par_iter()
+            .map(|mapping| {
+                let result =
+                    verify_mapping_in(mapping, config.hash_options, &caches, config.clamp_ranges);
+                on_verified(&result);
+                result
+            })
+            .collect()
+    };
+
+    // A dedicated pool bounds how many files are read at once; rayon's
+    // global pool has one thread per CPU
+    let pool = config.parallelism.and_then(|threads| {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .ok()
+    });
+    match pool {
+        Some(pool) => pool.install(verify),
+        None => verify(),
+    }
+}
+
+/// Verifies both sides of a mapping, using `defaults` for any hash option the
+/// mapping doesn't override.
+pub fn verify_mapping(
+    mapping: &Mapping,
+    defaults: HashOptions,
+    cache: &ContentCache,
+) -> MappingResult {
+    verify_sides(mapping, defaults, cache, cache, false)
+}
+
+/// Like `verify_mapping`, reading each side through its own cache. With
+/// `clamp`, line ranges ending past the end of their file are cut short with
+/// a warning instead of failing.
+pub fn verify_mapping_in(
+    mapping: &Mapping,
+    defaults: HashOptions,
+    caches: &SideCaches,
+    clamp: bool,
+) -> MappingResult {
+    verify_sides(mapping, defaults, caches.doc(), caches.code(), clamp)
+}
+
+fn verify_sides(
+    mapping: &Mapping,
+    defaults: HashOptions,
+    doc_cache: &ContentCache,
+    code_cache: &ContentCache,
+    clamp: bool,
+) -> MappingResult {
+    let hash_options = mapping.hash_options(defaults);
+    let mut doc_warnings = Vec::new();
+    let mut code_warnings = Vec::new();
+    let doc_result = verify_partition(
+        &mapping.doc_partition,
+        &mapping.doc_hash,
+        &mapping.doc_lines,
+        "documentation",
+        hash_options,
+        doc_cache,
+        clamp.then_some(&mut doc_warnings),
+    );
+
+    let code_result = verify_partition(
+        &mapping.code_partition,
+        &mapping.code_hash,
+        &mapping.code_lines,
+        "code",
+        mapping.code_hash_options(defaults),
+        code_cache,
+        clamp.then_some(&mut code_warnings),
+    );
+
+    let mut errors = Vec::new();
+    let mut broken = false;
+    for (result, side) in [(doc_result, "Documentation"), (code_result, "Code")] {
+        if let Err(e) = result {
+            errors.push(format!("{}: {}", side, e.message));
+            broken |= e.broken;
+        }
+    }
+
+    let warnings = doc_warnings
+        .into_iter()
+        .map(|warning| format!("Documentation: {}", warning))
+        .chain(
+            code_warnings
+                .into_iter()
+                .map(|warning| format!("Code: {}", warning)),
+        )
+        .collect();
+
+    MappingResult {
+        id: mapping.id.clone(),
+        passed: errors.is_empty(),
+        errors,
+        broken,
+        warnings,
+    }
+}
+
+/// Why one side of a mapping didn't verify.
+struct SideError {
+    message: String,
+    /// The side couldn't be checked, as opposed to its content drifting.
+    broken: bool,
+}
+
+impl SideError {
+    fn broken(message: String) -> Self {
+        Self {
+            message,
+            broken: true,
+        }
+    }
+}
+
+fn verify_partition(
+    partition_str: &str,
+    expected_hash: &str,
+    expected_lines: &[String],
+    content_type: &str,
+    hash_options: HashOptions,
+    cache: &ContentCache,
+    clamp_warnings: Option<&mut Vec<String>>,
+) -> Result<(), SideError> {
+    let partition = Partition::parse(partition_str).map_err(|e| {
+        SideError::broken(format!(
+            "Failed to parse {} partition '{}': {}",
+            content_type, partition_str, e
+        ))
+    })?;
+
+    let content = match (partition.extract_content_cached(cache), clamp_warnings) {
+        (Err(e @ DoksError::OutOfRange(_)), Some(warnings)) => {
+            let clamped = cache.read(&partition.file_path).ok().and_then(|file| {
+                let (clamped, notes) = partition.clamped(&file)?;
+                Some((clamped.extract_from(&file), notes))
+            });
+            match clamped {
+                Some((content, notes)) => {
+                    warnings.extend(notes);
+                    content
+                }
+                None => Err(e),
+            }
+        }
+        (content, _) => content,
+    }
+    .map_err(|e| SideError::broken(format!("Failed to extract {} content: {}", content_type, e)))?;
+
+    if let Err(mismatch) = check_hash(&content, expected_hash, hash_options) {
+        let broken = matches!(mismatch, DoksError::MalformedHash(_));
+        let mut message = format!("{} {}", content_type, mismatch);
+        if !expected_lines.is_empty() {
+            let changed = changed_lines(expected_lines, &line_digests(&content, hash_options));
+            if !changed.is_empty() {
+                let offsets: Vec<String> = changed.iter().map(usize::to_string).collect();
+                message.push_str(&format!(
+                    "; changed line(s) within the partition: {}",
+                    offsets.join(", ")
+                ));
+            }
+        }
+        return Err(SideError { message, broken });
+    }
+
+    Ok(())
+}
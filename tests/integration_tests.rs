@@ -49,24 +49,46 @@ fn test_new_command_creates_doks_file() {
     assert!(content.contains("default_doc=README.md"));
 }
 
-// Commented out because it requires interactive input which doesn't work in CI
-// #[test]
-// fn test_new_command_with_custom_doc_file() {
-//     let dir = tempdir().unwrap();
-//
-//     let mut cmd = Command::cargo_bin("doksnet").unwrap();
-//     cmd.arg("new")
-//         .arg(dir.path())
-//         .write_stdin("docs.md\n") // Custom documentation file
-//         .assert()
-//         .success();
-//
-//     let doks_path = dir.path().join(".doks");
-//     assert!(doks_path.exists());
-//
-//     let content = fs::read_to_string(doks_path).unwrap();
-//     assert!(content.contains("default_doc = \"docs.md\""));
-// }
+#[test]
+fn test_new_command_with_custom_doc_file() {
+    let dir = tempdir().unwrap();
+    // Would be offered by the interactive scan; --doc must bypass it
+    fs::write(dir.path().join("README.md"), "# Test").unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.arg("new")
+        .arg(dir.path())
+        .arg("--doc")
+        .arg("docs.md")
+        .assert()
+        .success();
+
+    let doks_path = dir.path().join(".doks");
+    assert!(doks_path.exists());
+
+    let content = fs::read_to_string(doks_path).unwrap();
+    assert!(content.contains("default_doc=docs.md"));
+}
+
+#[test]
+fn test_new_command_offers_doc_glob_matches() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("main.rs"), "fn main() {}").unwrap();
+    fs::write(dir.path().join("guide.adoc"), "= Guide").unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.arg("new")
+        .arg(dir.path())
+        .args(["--doc-glob", "*.adoc"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Found documentation file: guide.adoc",
+        ));
+
+    let content = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    assert!(content.contains("default_doc=guide.adoc"));
+}
 
 #[test]
 fn test_new_command_fails_when_doks_exists() {
@@ -82,6 +104,89 @@ fn test_new_command_fails_when_doks_exists() {
         .stderr(predicate::str::contains("A .doks file already exists"));
 }
 
+#[test]
+fn test_custom_file_flag() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["--file", "custom.doks", "new", "--doc", "README.md"])
+        .assert()
+        .success();
+    assert!(dir.path().join("custom.doks").exists());
+    assert!(!dir.path().join(".doks").exists());
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["add", "--doc", "README.md:2", "--code", "README.md:1"])
+        .args(["--file", "custom.doks"])
+        .assert()
+        .success();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["--file", "custom.doks", "test"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Passed: 1/1"));
+
+    // Without the flag only .doks is searched for
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("test")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No .doks file found"));
+}
+
+#[test]
+fn test_cwd_flag_runs_against_another_project() {
+    let parent = tempdir().unwrap();
+    let project = parent.path().join("project");
+    fs::create_dir_all(project.join("src")).unwrap();
+    fs::write(project.join("README.md"), "# Test\nContent").unwrap();
+    fs::write(project.join("src/main.rs"), "fn main() {}").unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&parent)
+        .args(["--cwd", "project", "new", "--doc", "README.md"])
+        .assert()
+        .success();
+    assert!(project.join(".doks").exists());
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&parent)
+        .args(["--cwd", "project", "add"])
+        .args(["--doc", "README.md:2", "--code", "src/main.rs"])
+        .assert()
+        .success();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&parent)
+        .args(["test", "--cwd", "project"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Passed: 1/1"));
+
+    fs::write(project.join("src/main.rs"), "fn main() { run(); }").unwrap();
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&parent)
+        .args(["--cwd", "project", "check"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::starts_with(
+            "src/main.rs:1: code content has changed",
+        ));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&parent)
+        .args(["--cwd", "missing", "test"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--cwd missing is not a directory"));
+}
+
 #[test]
 fn test_commands_fail_without_doks_file() {
     let dir = tempdir().unwrap();
@@ -167,6 +272,342 @@ fn test_remove_failed_with_empty_mappings() {
         .stdout(predicate::str::contains("No mappings found"));
 }
 
+#[test]
+fn test_remove_failed_yes_skips_confirmation() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/main.rs"), "fn main() {}").unwrap();
+    create_doks_with_mapping(&dir, "README.md:2", "src/main.rs:1");
+
+    let stale = format!(
+        "\nstale-mapping|README.md:1|src/main.rs:1|{0}|{0}|",
+        "0".repeat(64)
+    );
+    let mut doks = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    doks.push_str(&stale);
+    fs::write(dir.path().join(".doks"), doks).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["remove-failed", "--yes"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Successfully removed 1 failed mapping(s)",
+        ))
+        .stdout(predicate::str::contains("Remaining mappings: 1"));
+
+    let doks = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    assert!(!doks.contains("stale-mapping"));
+    assert!(doks.contains("test-mapping-123"));
+}
+
+#[test]
+fn test_short_ids_are_shown_in_full() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/main.rs"), "fn main() {}").unwrap();
+    fs::write(
+        dir.path().join(".doks"),
+        format!(
+            "version=0.1.0\ndefault_doc=README.md\n\nabc|README.md:1|src/main.rs:1|{0}|{0}|\n",
+            "0".repeat(64)
+        ),
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("test")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("abc (ID: abc)"));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("abc"));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["remove-failed", "--yes"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("ID: abc (abc...)"));
+    assert!(!fs::read_to_string(dir.path().join(".doks"))
+        .unwrap()
+        .contains("abc|"));
+}
+
+#[test]
+fn test_add_command_json_format() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("README.md"),
+        "# Test\nRun `cargo build` first",
+    )
+    .unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::write(
+        dir.path().join("src/main.rs"),
+        "fn main() {\n    build();\n}",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir).arg("new").assert().success();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    let output = cmd
+        .current_dir(&dir)
+        .args([
+            "add",
+            "--doc",
+            "README.md:2@5-17",
+            "--code",
+            "src/main.rs:2@5-11",
+            "--format",
+            "json",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.lines().count(), 1);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let id = json["id"].as_str().unwrap();
+    assert_eq!(json.as_object().unwrap().len(), 1);
+    let doks = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    assert!(doks.contains(&format!("{}|README.md:2@5-17|src/main.rs:2@5-11|", id)));
+
+    // A selection past the end of its line fails with nothing on stdout
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args([
+            "add",
+            "--doc",
+            "README.md:2@5-40",
+            "--code",
+            "src/main.rs:2",
+            "--format",
+            "json",
+        ])
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr(predicate::str::contains(
+            "Column numbers exceed line length",
+        ));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["add", "--doc", "README.md:2", "--format", "json"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_remove_failed_count() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/main.rs"), "fn main() {}").unwrap();
+    create_doks_with_mapping(&dir, "README.md:2", "src/main.rs:1");
+
+    let stale = format!(
+        "\nstale-mapping|README.md:1|src/main.rs:1|{0}|{0}|",
+        "0".repeat(64)
+    );
+    let mut doks = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    doks.push_str(&stale);
+    fs::write(dir.path().join(".doks"), &doks).unwrap();
+
+    // Without --yes nothing is removed, and there's no prompt
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["remove-failed", "--count"])
+        .assert()
+        .success()
+        .stdout("1\n");
+    assert_eq!(fs::read_to_string(dir.path().join(".doks")).unwrap(), doks);
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["remove-failed", "--count", "--yes"])
+        .assert()
+        .success()
+        .stdout("1\n");
+
+    let doks = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    assert!(!doks.contains("stale-mapping"));
+    assert!(doks.contains("test-mapping-123"));
+    assert!(dir.path().join(".doks.undo").exists());
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["remove-failed", "--count", "--yes"])
+        .assert()
+        .success()
+        .stdout("0\n");
+}
+
+#[test]
+fn test_edit_sets_code_partition_from_flags() {
+    let dir = tempdir().unwrap();
+
+    fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+    let src_dir = dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    fs::write(src_dir.join("main.rs"), "fn main() {}\nfn helper() {}").unwrap();
+
+    create_doks_with_mapping(&dir, "README.md:2", "src/main.rs:1");
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["edit", "test-mapping", "--code", "src/main.rs:2"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Successfully updated mapping"));
+
+    let doks = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    let helper_hash = blake3::hash(b"fn helper() {}").to_hex().to_string();
+    assert!(doks.contains("|src/main.rs:2|"));
+    assert!(doks.contains(&helper_hash));
+    assert!(doks.contains("|Test mapping"));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("test")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Passed: 1/1"));
+
+    // An unreadable partition leaves the mapping untouched
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["edit", "test-mapping", "--code", "src/main.rs:9"])
+        .assert()
+        .failure();
+    let after = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    assert_eq!(doks, after);
+}
+
+#[test]
+fn test_move_code_partition() {
+    let dir = tempdir().unwrap();
+
+    fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+    let src_dir = dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    fs::write(src_dir.join("main.rs"), "fn main() {}\nfn helper() {}").unwrap();
+    fs::write(src_dir.join("helper.rs"), "// Helpers\nfn helper() {}").unwrap();
+
+    create_doks_with_mapping(&dir, "README.md:2", "src/main.rs:2");
+    fs::write(src_dir.join("main.rs"), "fn main() {}").unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["move", "test-mapping", "--to-code", "src/helper.rs:2"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Code: src/main.rs:2 -> src/helper.rs:2",
+        ));
+
+    let doks = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    assert!(doks.contains("|README.md:2|src/helper.rs:2|"));
+    assert!(doks.contains("|Test mapping"));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("test")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Passed: 1/1"));
+}
+
+#[test]
+fn test_move_to_invalid_target_saves_nothing() {
+    let dir = tempdir().unwrap();
+
+    fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+    let src_dir = dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    fs::write(src_dir.join("main.rs"), "fn main() {}").unwrap();
+
+    create_doks_with_mapping(&dir, "README.md:2", "src/main.rs:1");
+    let before = fs::read_to_string(dir.path().join(".doks")).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["move", "test-mapping", "--to-code", "src/missing.rs:1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("File not found: src/missing.rs"));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["move", "test-mapping", "--to-code", "src/main.rs:abc"])
+        .assert()
+        .failure();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["move", "test-mapping"])
+        .assert()
+        .failure();
+
+    let after = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    assert_eq!(before, after);
+}
+
+#[test]
+fn test_edit_clears_description_from_flags() {
+    let dir = tempdir().unwrap();
+
+    fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+    let src_dir = dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    fs::write(src_dir.join("main.rs"), "fn main() {}").unwrap();
+
+    create_doks_with_mapping(&dir, "README.md:2", "src/main.rs:1");
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["edit", "test-mapping", "--clear-description"])
+        .assert()
+        .success();
+
+    let doks = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    assert!(!doks.contains("Test mapping"));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["edit", "test-mapping", "--description", "Entry point"])
+        .assert()
+        .success();
+
+    let doks = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    assert!(doks.contains("|Entry point"));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args([
+            "edit",
+            "test-mapping",
+            "--description",
+            "x",
+            "--clear-description",
+        ])
+        .assert()
+        .failure();
+}
+
 #[test]
 fn test_edit_with_nonexistent_id() {
     let dir = tempdir().unwrap();
@@ -221,7 +662,7 @@ fn test_test_command_with_valid_mappings() {
         .stdout(predicate::str::contains(
             "Testing 1 documentation-code mappings",
         ))
-        .stdout(predicate::str::contains("✅ Passed: 1/1"));
+        .stdout(predicate::str::contains("Passed: 1/1"));
 }
 
 #[test]
@@ -248,12 +689,2699 @@ fn test_test_command_with_changed_content() {
         .arg("test")
         .assert()
         .failure() // Should fail with exit code 1
-        .stdout(predicate::str::contains("❌ Failed: 1/1"))
+        .stdout(predicate::str::contains("Failed: 1/1"))
         .stdout(predicate::str::contains(
             "documentation content has changed",
         ));
 }
 
+#[test]
+fn test_add_batch_command_skips_invalid_lines() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("README.md"), "# Test\nContent\nMore").unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/main.rs"), "fn main() {}\nfn run() {}").unwrap();
+    create_basic_doks_file(&dir);
+
+    let manifest = "README.md:1 src/main.rs:1 Entry point\n\
+        README.md:9-20 src/main.rs:2\n\
+        README.md:2-3 src/main.rs:2\n";
+    fs::write(dir.path().join("manifest.txt"), manifest).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["add-batch", "manifest.txt"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Line 2: Failed to extract documentation content",
+        ))
+        .stdout(predicate::str::contains("Added 2 mapping(s)"))
+        .stdout(predicate::str::contains("Failed line(s): 2"));
+
+    let doks = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    assert!(doks.contains("|README.md:1|src/main.rs:1|"));
+    assert!(doks.contains("|Entry point"));
+    assert!(doks.contains("|README.md:2-3|src/main.rs:2|"));
+    assert!(!doks.contains("README.md:9-20"));
+}
+
+#[test]
+fn test_list_command_shows_mappings() {
+    let dir = tempdir().unwrap();
+    let doks_content = r#"# .doks - Mapping doks to code 
+default_doc=README.md
+
+# Format: id|doc_partition|code_partition|doc_hash|code_hash|description
+first-mapping|README.md:1-2|src/main.rs:1-2|abc123|def456|First mapping
+second-mapping|README.md:5|src/lib.rs:10-20|fedcba|654321|"#;
+    fs::write(dir.path().join(".doks"), doks_content).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("first-ma"))
+        .stdout(predicate::str::contains("second-m"))
+        .stdout(predicate::str::contains("src/lib.rs:10-20"))
+        .stdout(predicate::str::contains("First mapping"));
+}
+
+#[test]
+fn test_list_command_json() {
+    let dir = tempdir().unwrap();
+    let doks_content = "default_doc=README.md\n\n\
+        first-mapping|README.md:1-2|src/main.rs:1-2|abc123|def456|First mapping\n\
+        second-mapping|README.md:5|src/lib.rs:10-20|fedcba|654321|\n";
+    fs::write(dir.path().join(".doks"), doks_content).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    let output = cmd
+        .current_dir(&dir)
+        .args(["list", "--format", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let listed: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let mappings = listed.as_array().unwrap();
+    assert_eq!(mappings.len(), 2);
+    assert_eq!(mappings[0]["id"], "first-mapping");
+    assert_eq!(mappings[0]["description"], "First mapping");
+    assert_eq!(mappings[1]["code_partition"], "src/lib.rs:10-20");
+    assert!(mappings[0].get("status").is_none());
+}
+
+#[test]
+fn test_list_command_json_with_status() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/main.rs"), "fn main() {}").unwrap();
+    create_doks_with_mapping(&dir, "README.md:2", "src/main.rs:1");
+
+    let stale = format!(
+        "\nstale-mapping|README.md:1|src/main.rs:1|{0}|{0}|",
+        "0".repeat(64)
+    );
+    let mut doks = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    doks.push_str(&stale);
+    fs::write(dir.path().join(".doks"), doks).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    let output = cmd
+        .current_dir(&dir)
+        .args(["list", "--format", "json", "--with-status"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let listed: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let mappings = listed.as_array().unwrap();
+    assert_eq!(mappings.len(), 2);
+    assert_eq!(mappings[0]["status"], "pass");
+    assert_eq!(mappings[1]["id"], "stale-mapping");
+    assert_eq!(mappings[1]["status"], "fail");
+}
+
+#[test]
+fn test_list_command_with_empty_mappings() {
+    let dir = tempdir().unwrap();
+    create_basic_doks_file(&dir);
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No mappings found"));
+}
+
+#[test]
+fn test_show_command_with_matching_prefix() {
+    let dir = tempdir().unwrap();
+
+    let readme_path = dir.path().join("README.md");
+    fs::write(&readme_path, "# Test\nShown documentation line").unwrap();
+
+    let src_dir = dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    let main_path = src_dir.join("main.rs");
+    fs::write(&main_path, "fn main() {}\nfn helper() {}").unwrap();
+
+    create_doks_with_mapping(&dir, "README.md:2", "src/main.rs:2");
+    fs::write(&main_path, "fn main() {}\nfn renamed() {}").unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("show")
+        .arg("test-map")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("test-mapping-123"))
+        .stdout(predicate::str::contains("Test mapping"))
+        .stdout(predicate::str::contains("Shown documentation line"))
+        .stdout(predicate::str::contains("Matches stored hash"))
+        .stdout(predicate::str::contains("fn renamed() {}"))
+        .stdout(predicate::str::contains("Content has changed"));
+}
+
+#[test]
+fn test_show_command_with_nonexistent_id() {
+    let dir = tempdir().unwrap();
+    create_basic_doks_file(&dir);
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("show")
+        .arg("nonexistent")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "No mapping found with ID starting with",
+        ));
+}
+
+#[test]
+fn test_test_command_json_format() {
+    let dir = tempdir().unwrap();
+
+    let readme_path = dir.path().join("README.md");
+    fs::write(&readme_path, "# Test\nOriginal content\nLine 3").unwrap();
+
+    let src_dir = dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    let main_path = src_dir.join("main.rs");
+    fs::write(&main_path, "fn main() {\n    println!(\"Hello\");\n}").unwrap();
+
+    create_doks_with_mapping(&dir, "README.md:2", "src/main.rs:2");
+    fs::write(&readme_path, "# Test\nModified content\nLine 3").unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    let output = cmd
+        .current_dir(&dir)
+        .arg("test")
+        .arg("--format")
+        .arg("json")
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["summary"]["total"], 1);
+    assert_eq!(report["summary"]["passed"], 0);
+    assert_eq!(report["summary"]["failed"], 1);
+
+    let result = &report["results"][0];
+    assert_eq!(result["id"], "test-mapping-123");
+    assert_eq!(result["doc_partition"], "README.md:2");
+    assert_eq!(result["code_partition"], "src/main.rs:2");
+    assert_eq!(result["status"], "fail");
+
+    let errors = result["errors"].as_array().unwrap();
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0]
+        .as_str()
+        .unwrap()
+        .contains("documentation content has changed"));
+}
+
+#[test]
+fn test_add_command_with_flags() {
+    let dir = tempdir().unwrap();
+    create_basic_doks_file(&dir);
+
+    fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+    let src_dir = dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    fs::write(src_dir.join("main.rs"), "fn main() {}").unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("add")
+        .arg("--doc")
+        .arg("README.md:1")
+        .arg("--code")
+        .arg("src/main.rs:1")
+        .arg("--description")
+        .arg("Scripted mapping")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Successfully added mapping"));
+
+    let content = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    assert!(content.contains("|README.md:1|src/main.rs:1|"));
+    assert!(content.contains("|Scripted mapping"));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("test")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Passed: 1/1"));
+}
+
+#[test]
+fn test_add_command_dry_run_leaves_doks_unchanged() {
+    let dir = tempdir().unwrap();
+    create_basic_doks_file(&dir);
+    let before = fs::read_to_string(dir.path().join(".doks")).unwrap();
+
+    fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+    let src_dir = dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    fs::write(src_dir.join("main.rs"), "fn main() {}").unwrap();
+
+    let code_hash = blake3::hash(b"fn main() {}").to_hex().to_string();
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["add", "--doc", "README.md:2", "--code", "src/main.rs:1"])
+        .args(["--description", "Trial", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("fn main() {}"))
+        .stdout(predicate::str::contains(format!(
+            "Code hash: {}",
+            code_hash
+        )))
+        .stdout(predicate::str::contains("|README.md:2|src/main.rs:1|"))
+        .stdout(predicate::str::contains("Dry run: .doks was not changed"));
+
+    let after = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    assert_eq!(before, after);
+}
+
+#[test]
+fn test_add_command_context_is_not_hashed() {
+    let dir = tempdir().unwrap();
+    create_basic_doks_file(&dir);
+
+    fs::write(dir.path().join("README.md"), "# Test\nContent\nMore").unwrap();
+    let src_dir = dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    fs::write(src_dir.join("main.rs"), "// entry\nfn main() {}\n// end").unwrap();
+
+    let doc_hash = blake3::hash(b"Content").to_hex().to_string();
+    let code_hash = blake3::hash(b"fn main() {}").to_hex().to_string();
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["add", "--doc", "README.md:2", "--code", "src/main.rs:2"])
+        .args(["--dry-run", "--context", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "1 | # Test\n2 > Content\n3 | More",
+        ))
+        .stdout(predicate::str::contains(
+            "1 | // entry\n2 > fn main() {}\n3 | // end",
+        ))
+        .stdout(predicate::str::contains(format!(
+            "Documentation hash: {}",
+            doc_hash
+        )))
+        .stdout(predicate::str::contains(format!(
+            "Code hash: {}",
+            code_hash
+        )));
+}
+
+#[test]
+fn test_add_command_with_flags_invalid_partition() {
+    let dir = tempdir().unwrap();
+    create_basic_doks_file(&dir);
+    fs::write(dir.path().join("README.md"), "# Test").unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("add")
+        .arg("--doc")
+        .arg("README.md:1")
+        .arg("--code")
+        .arg("src/missing.rs:1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Failed to extract code content"));
+}
+
+#[test]
+fn test_sync_command_rehashes_changed_mappings() {
+    let dir = tempdir().unwrap();
+
+    let readme_path = dir.path().join("README.md");
+    fs::write(&readme_path, "# Test\nOriginal content\nLine 3").unwrap();
+
+    let src_dir = dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    let main_path = src_dir.join("main.rs");
+    fs::write(&main_path, "fn main() {\n    println!(\"Hello\");\n}").unwrap();
+
+    create_doks_with_mapping(&dir, "README.md:2", "src/main.rs:2");
+    fs::write(&main_path, "fn main() {\n    println!(\"Refactored\");\n}").unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir).arg("test").assert().failure();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("sync")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Hashes changed: 1"));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("test")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Passed: 1/1"));
+}
+
+#[test]
+fn test_sync_command_keeps_updated_at_of_unchanged_mappings() {
+    let dir = tempdir().unwrap();
+
+    fs::write(dir.path().join("README.md"), "# Test\nLine 2\nLine 3").unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    let main_path = dir.path().join("src/main.rs");
+    fs::write(&main_path, "fn main() {\n    println!(\"Hello\");\n}").unwrap();
+    fs::write(dir.path().join("src/lib.rs"), "pub fn lib() {}").unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["new", "--doc", "README.md"])
+        .assert()
+        .success();
+    for (doc, code) in [
+        ("README.md:2", "src/main.rs:2"),
+        ("README.md:3", "src/lib.rs:1"),
+    ] {
+        let mut cmd = Command::cargo_bin("doksnet").unwrap();
+        cmd.current_dir(&dir)
+            .args(["add", "--doc", doc, "--code", code])
+            .assert()
+            .success();
+    }
+
+    // Backdate both mappings so that a new timestamp is recognizable
+    let doks_path = dir.path().join(".doks");
+    let old = "updated_at=2024-01-02T03:04:05Z";
+    let backdated: Vec<String> = fs::read_to_string(&doks_path)
+        .unwrap()
+        .lines()
+        .map(|line| match line.find("updated_at=") {
+            Some(start) => {
+                let end = line[start..]
+                    .find('|')
+                    .map_or(line.len(), |end| start + end);
+                format!("{}{}{}", &line[..start], old, &line[end..])
+            }
+            None => line.to_string(),
+        })
+        .collect();
+    fs::write(&doks_path, backdated.join("\n") + "\n").unwrap();
+    assert_eq!(
+        fs::read_to_string(&doks_path).unwrap().matches(old).count(),
+        2
+    );
+
+    fs::write(&main_path, "fn main() {\n    println!(\"Refactored\");\n}").unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("sync")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Hashes changed: 1"));
+
+    let content = fs::read_to_string(&doks_path).unwrap();
+    let line_of = |partition: &str| {
+        content
+            .lines()
+            .find(|line| line.contains(partition))
+            .unwrap()
+            .to_string()
+    };
+    assert!(!line_of("src/main.rs:2").contains(old));
+    assert!(line_of("src/lib.rs:1").contains(old));
+}
+
+#[test]
+fn test_sync_command_skips_broken_mappings() {
+    let dir = tempdir().unwrap();
+
+    fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+    let src_dir = dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    let main_path = src_dir.join("main.rs");
+    fs::write(&main_path, "fn main() {}").unwrap();
+
+    create_doks_with_mapping(&dir, "README.md:2", "src/main.rs:1");
+    let original = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    fs::remove_file(&main_path).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("sync")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Skipped: 1"))
+        .stdout(predicate::str::contains("test-mapping-123"));
+
+    let after = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    assert_eq!(original, after);
+}
+
+#[test]
+fn test_test_command_github_format() {
+    let dir = tempdir().unwrap();
+
+    fs::write(dir.path().join("README.md"), "# Test\nLine 2\nLine 3").unwrap();
+    let src_dir = dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    let main_path = src_dir.join("main.rs");
+    fs::write(&main_path, "fn main() {\n    println!(\"Hello\");\n}").unwrap();
+
+    create_doks_with_mapping(&dir, "README.md:2", "src/main.rs:2");
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["test", "--format", "github"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+
+    fs::write(&main_path, "fn main() {\n    println!(\"Changed\");\n}").unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["test", "--format", "github"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::diff(
+            "::error file=src/main.rs,line=2::code content has changed (mapping test-mapping-123)\n",
+        ));
+}
+
+#[test]
+fn test_test_command_sarif_format() {
+    let dir = tempdir().unwrap();
+
+    fs::write(dir.path().join("README.md"), "# Test\nLine 2\nLine 3").unwrap();
+    let src_dir = dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    let main_path = src_dir.join("main.rs");
+    fs::write(&main_path, "fn main() {\n    println!(\"Hello\");\n}").unwrap();
+
+    create_doks_with_mapping(&dir, "README.md:2", "src/main.rs:2");
+
+    let run = |code: i32| {
+        let mut cmd = Command::cargo_bin("doksnet").unwrap();
+        let output = cmd
+            .current_dir(&dir)
+            .args(["test", "--format", "sarif"])
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(code));
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+    };
+
+    let sarif = run(0);
+    assert_eq!(sarif["version"], "2.1.0");
+    assert!(sarif["$schema"].as_str().unwrap().contains("sarif-2.1.0"));
+    let run_log = &sarif["runs"][0];
+    assert_eq!(run_log["tool"]["driver"]["name"], "doksnet");
+    assert!(run_log["tool"]["driver"]["rules"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|rule| rule["id"] == "doksnet/content-drift"));
+    assert_eq!(run_log["results"], serde_json::json!([]));
+
+    fs::write(&main_path, "fn main() {\n    println!(\"Changed\");\n}").unwrap();
+
+    let sarif = run(1);
+    let results = sarif["runs"][0]["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["ruleId"], "doksnet/content-drift");
+    assert_eq!(results[0]["level"], "error");
+    assert_eq!(
+        results[0]["message"]["text"],
+        "code content has changed (mapping test-mapping-123)"
+    );
+    let location = &results[0]["locations"][0]["physicalLocation"];
+    assert_eq!(location["artifactLocation"]["uri"], "src/main.rs");
+    assert_eq!(location["region"]["startLine"], 2);
+}
+
+#[test]
+fn test_test_command_junit_format() {
+    let dir = tempdir().unwrap();
+
+    fs::write(dir.path().join("README.md"), "# Test\nLine 2\nLine 3").unwrap();
+    let src_dir = dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    let main_path = src_dir.join("main.rs");
+    fs::write(&main_path, "fn main() {\n    println!(\"Hello\");\n}").unwrap();
+
+    create_doks_with_mapping(&dir, "README.md:2", "src/main.rs:2");
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["add", "--doc", "README.md:3", "--code", "src/main.rs:1"])
+        .assert()
+        .success();
+
+    fs::write(&main_path, "fn main() {\n    println!(\"<Changed>\");\n}").unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    let output = cmd
+        .current_dir(&dir)
+        .args(["test", "--format", "junit"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+
+    let xml = String::from_utf8(output.stdout).unwrap();
+    assert!(xml.starts_with("<?xml"));
+    assert!(xml.contains("<testsuite name=\"doksnet\" tests=\"2\" failures=\"1\""));
+    assert_eq!(xml.matches("<testcase ").count(), 2);
+    assert_eq!(xml.matches("<failure ").count(), 1);
+    assert!(xml.contains(
+        "<testcase name=\"test-mapping-123\" classname=\"README.md\">\n    \
+         <failure message=\"Code: code content has changed"
+    ));
+}
+
+#[test]
+fn test_test_command_group_by_file() {
+    let dir = tempdir().unwrap();
+
+    fs::write(dir.path().join("README.md"), "# Test\nLine 2\nLine 3").unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    let main_path = dir.path().join("src/main.rs");
+    fs::write(&main_path, "fn main() {\n    println!(\"Hello\");\n}").unwrap();
+    fs::write(dir.path().join("src/lib.rs"), "pub fn lib() {}").unwrap();
+
+    create_doks_with_mapping(&dir, "README.md:1", "src/main.rs:1");
+    for (doc, code) in [
+        ("README.md:2", "src/main.rs:2"),
+        ("README.md:3", "src/lib.rs:1"),
+    ] {
+        let mut cmd = Command::cargo_bin("doksnet").unwrap();
+        cmd.current_dir(&dir)
+            .args(["add", "--doc", doc, "--code", code])
+            .assert()
+            .success();
+    }
+
+    fs::write(&main_path, "fn start() {\n    println!(\"Changed\");\n}").unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    let output = cmd
+        .current_dir(&dir)
+        .args(["test", "--group-by", "file"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Failed: 2/3"));
+    assert_eq!(stdout.matches("src/main.rs (2 failing)").count(), 1);
+    assert!(!stdout.contains("src/lib.rs ("));
+
+    let details = &stdout[stdout.find("src/main.rs (2 failing)").unwrap()..];
+    assert!(details.contains("\n      1. test-mapping-123 (ID: test-map)\n"));
+    assert!(details.contains("\n      2. "));
+    assert!(details.contains("\n         - Code: code content has changed"));
+}
+
+#[test]
+fn test_test_command_reports_errors_apart_from_failures() {
+    let dir = tempdir().unwrap();
+
+    fs::write(dir.path().join("README.md"), "# Test\nLine 2\nLine 3").unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    let main_path = dir.path().join("src/main.rs");
+    fs::write(&main_path, "fn main() {\n    println!(\"Hello\");\n}").unwrap();
+    fs::write(dir.path().join("src/util.rs"), "pub fn util() {}").unwrap();
+
+    create_doks_with_mapping(&dir, "README.md:2", "src/main.rs:2");
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["add", "--doc", "README.md:3", "--code", "src/util.rs:1"])
+        .assert()
+        .success();
+
+    // The first mapping drifts, the second can't be checked at all
+    fs::write(&main_path, "fn main() {\n    println!(\"Changed\");\n}").unwrap();
+    fs::remove_file(dir.path().join("src/util.rs")).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("test")
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("   FAIL\n"))
+        .stdout(predicate::str::contains("   ERROR\n"))
+        .stdout(predicate::str::contains("Failed: 1/2"))
+        .stdout(predicate::str::contains("Errors: 1/2"));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    let output = cmd
+        .current_dir(&dir)
+        .args(["test", "--format", "json"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["results"][0]["status"], "fail");
+    assert_eq!(report["results"][1]["status"], "error");
+    assert_eq!(report["summary"]["passed"], 0);
+    assert_eq!(report["summary"]["failed"], 1);
+    assert_eq!(report["summary"]["errors"], 1);
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["test", "--format", "junit"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("failures=\"1\" errors=\"1\""))
+        .stdout(predicate::str::contains(
+            "<error message=\"Code: Failed to extract",
+        ));
+}
+
+#[test]
+fn test_test_command_repair() {
+    let dir = tempdir().unwrap();
+
+    fs::write(dir.path().join("README.md"), "# Test\nLine 2\nLine 3").unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::write(
+        dir.path().join("src/main.rs"),
+        "fn main() {\n    println!(\"Hello\");\n}",
+    )
+    .unwrap();
+    fs::write(dir.path().join("src/util.rs"), "pub fn util() {}").unwrap();
+
+    create_doks_with_mapping(&dir, "README.md:2", "src/main.rs:2");
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["add", "--doc", "README.md:3", "--code", "src/util.rs:1"])
+        .assert()
+        .success();
+
+    // One file only moved, the other also changed
+    fs::create_dir(dir.path().join("crates")).unwrap();
+    fs::rename(dir.path().join("src"), dir.path().join("crates/app")).unwrap();
+    fs::write(dir.path().join("crates/app/util.rs"), "pub fn utility() {}").unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir).arg("test").assert().failure();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["test", "--repair"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "Repaired test-map: src/main.rs:2 -> crates/app/main.rs:2",
+        ))
+        .stdout(predicate::str::contains("Passed: 1/2"));
+
+    let doks = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    assert!(doks.contains("test-mapping-123|README.md:2|crates/app/main.rs:2|"));
+    assert!(doks.contains("|README.md:3|src/util.rs:1|"));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["test", "--only", "test-mapping"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_test_command_markdown_format() {
+    let dir = tempdir().unwrap();
+
+    fs::write(dir.path().join("README.md"), "# Test\nLine 2\nLine 3").unwrap();
+    let src_dir = dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    let main_path = src_dir.join("main.rs");
+    fs::write(&main_path, "fn main() {\n    println!(\"Hello\");\n}").unwrap();
+
+    create_doks_with_mapping(&dir, "README.md:2", "src/main.rs:2");
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["add", "--doc", "README.md:3", "--code", "src/main.rs:1"])
+        .assert()
+        .success();
+
+    fs::write(&main_path, "fn main() {\n    println!(\"Changed\");\n}").unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    let output = cmd
+        .current_dir(&dir)
+        .args(["test", "--format", "markdown"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+
+    let markdown = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = markdown.lines().collect();
+    assert_eq!(lines[0], "| Status | ID | Doc | Code | Message |");
+    assert_eq!(lines[1], "| --- | --- | --- | --- | --- |");
+    assert!(lines[2].starts_with(
+        "| ❌ | `test-mapping-123` | `README.md:2` | `src/main.rs:2` | Code: code content has changed"
+    ));
+    assert!(lines[3].starts_with("| ✅ | `"));
+    assert!(lines[3].ends_with("` | `README.md:3` | `src/main.rs:1` |  |"));
+    assert_eq!(lines[5], "**1 passed, 1 failed** of 2 mapping(s)");
+}
+
+#[test]
+fn test_test_command_verbose_diffs_stored_snippet() {
+    let dir = tempdir().unwrap();
+
+    fs::write(dir.path().join("README.md"), "# Test\nLine 2\nLine 3").unwrap();
+    let src_dir = dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    let main_path = src_dir.join("main.rs");
+    fs::write(&main_path, "fn main() {\n    println!(\"Hello\");\n}").unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir).args(["new"]).assert().success();
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args([
+            "add",
+            "--doc",
+            "README.md:2",
+            "--code",
+            "src/main.rs:1-3",
+            "--store-snippet",
+        ])
+        .assert()
+        .success();
+    let doks = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    assert!(doks.contains("|doc_snippet=Line 2|code_snippet=fn main() {\\n"));
+
+    fs::write(&main_path, "fn main() {\n    println!(\"Changed\");\n}").unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["test", "--verbose"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "--- Changes to code content (src/main.rs:1-3) ---",
+        ))
+        .stdout(predicate::str::contains("-    println!(\"Hello\");"))
+        .stdout(predicate::str::contains("+    println!(\"Changed\");"));
+
+    // Without the file, the snippet still shows what was expected
+    fs::remove_file(&main_path).unwrap();
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["test", "--verbose"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "--- Expected code content (src/main.rs:1-3) ---\n      fn main() {\n          \
+             println!(\"Hello\");",
+        ));
+}
+
+#[test]
+fn test_test_command_quiet() {
+    let dir = tempdir().unwrap();
+
+    fs::write(dir.path().join("README.md"), "# Test\nLine 2\nLine 3").unwrap();
+    let src_dir = dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    let main_path = src_dir.join("main.rs");
+    fs::write(&main_path, "fn main() {\n    println!(\"Hello\");\n}").unwrap();
+
+    create_doks_with_mapping(&dir, "README.md:2", "src/main.rs:2");
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("test")
+        .arg("--quiet")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Testing mapping").not())
+        .stdout(predicate::str::contains("Passed: 1/1"));
+
+    fs::write(&main_path, "fn main() {\n    println!(\"Changed\");\n}").unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("test")
+        .arg("--quiet")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Testing mapping").not())
+        .stdout(predicate::str::contains("Failed: 1/1"))
+        .stdout(predicate::str::contains("code content has changed"));
+}
+
+#[test]
+fn test_test_command_verbose_shows_current_content() {
+    let dir = tempdir().unwrap();
+
+    fs::write(dir.path().join("README.md"), "# Test\nLine 2\nLine 3").unwrap();
+    let src_dir = dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    let main_path = src_dir.join("main.rs");
+    fs::write(&main_path, "fn main() {\n    println!(\"Hello\");\n}").unwrap();
+
+    create_doks_with_mapping(&dir, "README.md:2", "src/main.rs:2");
+    fs::write(&main_path, "fn main() {\n    println!(\"Changed\");\n}").unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("test")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("println!(\"Changed\")").not());
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("test")
+        .arg("--verbose")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "--- Current code content (src/main.rs:2) ---",
+        ))
+        .stdout(predicate::str::contains("println!(\"Changed\");"))
+        .stdout(predicate::str::contains("Current documentation content").not());
+}
+
+#[test]
+fn test_partition_path_expands_env_vars() {
+    let dir = tempdir().unwrap();
+    let out_dir = tempdir().unwrap();
+    fs::write(out_dir.path().join("generated.md"), "# Generated\nRun it.").unwrap();
+    fs::write(dir.path().join("main.rs"), "fn main() {}").unwrap();
+
+    create_basic_doks_file(&dir);
+    let mut doks = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    doks.push_str(&format!(
+        "\ngenerated-mapping|$OUT_DIR/generated.md:2|main.rs|{}|{}|",
+        "0".repeat(64),
+        "0".repeat(64)
+    ));
+    fs::write(dir.path().join(".doks"), doks).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("sync")
+        .env("OUT_DIR", out_dir.path())
+        .assert()
+        .success();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("test")
+        .env("OUT_DIR", out_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Passed: 1/1"));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("test")
+        .env_remove("OUT_DIR")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "Environment variable 'OUT_DIR' used in '$OUT_DIR/generated.md' is not set",
+        ));
+}
+
+#[test]
+fn test_add_command_normalize_whitespace() {
+    let dir = tempdir().unwrap();
+    create_basic_doks_file(&dir);
+
+    let readme_path = dir.path().join("README.md");
+    fs::write(&readme_path, "```rust\nfn main() {\n    run();\n}\n```").unwrap();
+    let src_dir = dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    fs::write(src_dir.join("main.rs"), "fn main() {}").unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("add")
+        .arg("--doc")
+        .arg("README.md:2-4")
+        .arg("--code")
+        .arg("src/main.rs:1")
+        .arg("--normalize-whitespace")
+        .assert()
+        .success();
+
+    let content = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    assert!(content.contains("|normalize_whitespace=true"));
+
+    // Re-indenting the snippet is not a change
+    fs::write(&readme_path, "```rust\nfn main() {\n  run();  \n}\n```").unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("test")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Passed: 1/1"));
+}
+
+#[test]
+fn test_add_command_strip_comments() {
+    let dir = tempdir().unwrap();
+    create_basic_doks_file(&dir);
+
+    fs::write(dir.path().join("README.md"), "# Test\nRuns the app").unwrap();
+    let src_dir = dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    let main_path = src_dir.join("main.rs");
+    fs::write(&main_path, "fn main() {\n    run(\"//\");\n}").unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["add", "--doc", "README.md:2", "--code", "src/main.rs"])
+        .arg("--strip-comments")
+        .assert()
+        .success();
+
+    let content = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    assert!(content.contains("|strip_comments=true"));
+
+    // Adding and editing comments is not a change
+    fs::write(
+        &main_path,
+        "// Entry point\nfn main() {\n    run(\"//\"); /* start */\n}",
+    )
+    .unwrap();
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("test")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Passed: 1/1"));
+
+    // The string literal is code, not a comment
+    fs::write(&main_path, "fn main() {\n    run(\"// x\");\n}").unwrap();
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir).arg("test").assert().failure();
+}
+
+#[test]
+fn test_rename_file_rewrites_partitions() {
+    let dir = tempdir().unwrap();
+
+    fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+    let src_dir = dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    fs::write(src_dir.join("old.rs"), "fn start() {}\nfn stop() {}").unwrap();
+
+    create_doks_with_mapping(&dir, "README.md:2", "src/old.rs:1");
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["add", "--doc", "README.md:1", "--code", "src/old.rs:2"])
+        .assert()
+        .success();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["rename-file", "src/old.rs", "src/new.rs", "--move-file"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Updated 2 mapping(s) referencing src/old.rs",
+        ))
+        .stdout(predicate::str::contains("don't match").not());
+
+    assert!(!src_dir.join("old.rs").exists());
+    assert!(src_dir.join("new.rs").exists());
+    let doks = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    assert!(doks.contains("|README.md:2|src/new.rs:1|"));
+    assert!(doks.contains("|README.md:1|src/new.rs:2|"));
+    assert!(!doks.contains("src/old.rs"));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("test")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Passed: 2/2"));
+
+    // Without --move-file the file stays put and the mismatch is reported
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["rename-file", "src/new.rs", "src/renamed.rs"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "2 updated mapping(s) don't match their stored hashes",
+        ));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["rename-file", "src/missing.rs", "src/other.rs"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "No partitions reference 'src/missing.rs'",
+        ));
+}
+
+#[test]
+fn test_remove_command_with_matching_prefix() {
+    let dir = tempdir().unwrap();
+
+    fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+    let src_dir = dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    fs::write(src_dir.join("main.rs"), "fn main() {}").unwrap();
+
+    create_doks_with_mapping(&dir, "README.md:2", "src/main.rs:1");
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("remove")
+        .arg("test-map")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Removed mapping test-mapping-123"));
+
+    let content = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    assert!(!content.contains("test-mapping-123"));
+}
+
+#[test]
+fn test_undo_restores_removed_mapping() {
+    let dir = tempdir().unwrap();
+
+    fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+    let src_dir = dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    fs::write(src_dir.join("main.rs"), "fn main() {}").unwrap();
+
+    create_doks_with_mapping(&dir, "README.md:2", "src/main.rs:1");
+    let before = fs::read_to_string(dir.path().join(".doks")).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["remove", "test-mapping-123"])
+        .assert()
+        .success();
+    assert!(!fs::read_to_string(dir.path().join(".doks"))
+        .unwrap()
+        .contains("test-mapping-123"));
+    assert!(dir.path().join(".doks.undo").exists());
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("undo")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Restored 1 mapping(s)"));
+
+    let after = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    let mapping_line = |doks: &str| {
+        doks.lines()
+            .find(|line| line.starts_with("test-mapping-123|"))
+            .map(str::to_string)
+    };
+    assert!(mapping_line(&after).is_some());
+    assert_eq!(mapping_line(&before), mapping_line(&after));
+    assert!(!dir.path().join(".doks.undo").exists());
+
+    // The log only covers the last removal
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("undo")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Nothing to undo"));
+}
+
+#[test]
+fn test_remove_command_with_nonexistent_id() {
+    let dir = tempdir().unwrap();
+    create_basic_doks_file(&dir);
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("remove")
+        .arg("nonexistent")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "No mapping found with ID starting with 'nonexistent'",
+        ));
+}
+
+#[test]
+fn test_remove_command_with_ambiguous_prefix() {
+    let dir = tempdir().unwrap();
+
+    fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+    let src_dir = dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    fs::write(src_dir.join("main.rs"), "fn main() {}").unwrap();
+
+    create_doks_with_mapping(&dir, "README.md:2", "src/main.rs:1");
+    let doks_path = dir.path().join(".doks");
+    let mut content = fs::read_to_string(&doks_path).unwrap();
+    let second = content
+        .lines()
+        .last()
+        .unwrap()
+        .replace("test-mapping-123", "test-mapping-456");
+    content.push('\n');
+    content.push_str(&second);
+    fs::write(&doks_path, &content).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("remove")
+        .arg("test-mapping")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("is ambiguous"));
+
+    let after = fs::read_to_string(&doks_path).unwrap();
+    assert_eq!(content, after);
+}
+
+#[test]
+fn test_piped_output_uses_plain_markers() {
+    let dir = tempdir().unwrap();
+
+    fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+    let src_dir = dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    fs::write(src_dir.join("main.rs"), "fn main() {}").unwrap();
+
+    create_doks_with_mapping(&dir, "README.md:2", "src/main.rs:1");
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("test")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("   PASS"))
+        .stdout(predicate::str::contains("✅").not())
+        .stdout(predicate::str::contains("📊").not());
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("--no-color")
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("📋").not());
+}
+
+#[test]
+fn test_stats_command_groups_by_file() {
+    let dir = tempdir().unwrap();
+
+    fs::write(dir.path().join("README.md"), "# Test\nOne\nTwo\nThree").unwrap();
+    let src_dir = dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    fs::write(src_dir.join("main.rs"), "fn main() {}\nfn a() {}").unwrap();
+    fs::write(src_dir.join("lib.rs"), "pub fn b() {}").unwrap();
+
+    let doks = "default_doc=README.md\n\n\
+        m1|README.md:2|src/main.rs:1|h|h|\n\
+        m2|README.md:3|src/main.rs:2|h|h|\n\
+        m3|README.md:4|src/lib.rs:1|h|h|\n";
+    fs::write(dir.path().join(".doks"), doks).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("stats")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Total mappings: 3"))
+        .stdout(predicate::str::contains("3  README.md"))
+        .stdout(predicate::str::is_match("2  src/main.rs\n\\s+1  src/lib.rs").unwrap());
+}
+
+#[test]
+fn test_sort_command_orders_mappings() {
+    let dir = tempdir().unwrap();
+
+    let doks = "default_doc=README.md\n\n\
+        m1|README.md:20|src/main.rs:1|h|h|\n\
+        # Setup\n\
+        m2|README.md:3|src/main.rs:2|h|h|\n\
+        m3|README.md:3|src/lib.rs:1|h|h|\n";
+    fs::write(dir.path().join(".doks"), doks).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("sort")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Sorted 3 mapping(s)"));
+
+    let content = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    assert!(content.contains("sort_mappings=true\n"));
+    let m1 = content.find("m1|").unwrap();
+    let m2 = content.find("# Setup\nm2|").unwrap();
+    let m3 = content.find("m3|").unwrap();
+    assert!(m3 < m2 && m2 < m1);
+}
+
+#[test]
+fn test_config_command_gets_and_sets_headers() {
+    let dir = tempdir().unwrap();
+
+    let doks = "default_doc=README.md\n\n\
+        # Setup\n\
+        m1|README.md:3|src/main.rs:2|h|h|Install\n";
+    fs::write(dir.path().join(".doks"), doks).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["config", "get", "default_doc"])
+        .assert()
+        .success()
+        .stdout("README.md\n");
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["config", "set", "default_doc", "docs/guide.md"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Set default_doc=docs/guide.md"));
+
+    let content = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    assert!(content.contains("default_doc=docs/guide.md\n"));
+    assert!(!content.contains("default_doc=README.md"));
+    assert!(content.contains("# Setup\nm1|README.md:3|src/main.rs:2|h|h|Install\n"));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["config", "get", "default_doc"])
+        .assert()
+        .success()
+        .stdout("docs/guide.md\n");
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["config", "set", "preview_chars", "lots"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Invalid preview_chars value: lots",
+        ));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["config", "get", "version"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown header 'version'"));
+}
+
+#[test]
+fn test_config_command_rejects_unloadable_default_doc() {
+    let dir = tempdir().unwrap();
+
+    let doks = "default_doc=README.md\n\nm1|README.md:3|src/main.rs:2|h|h|\n";
+    fs::write(dir.path().join(".doks"), doks).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["config", "set", "default_doc", ""])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("default_doc can't be empty"));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["config", "set", "default_doc", "README.md\nx|y|z|w|v|"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "default_doc can't contain line breaks",
+        ));
+
+    assert_eq!(fs::read_to_string(dir.path().join(".doks")).unwrap(), doks);
+}
+
+#[test]
+fn test_coverage_command_reports_gaps() {
+    let dir = tempdir().unwrap();
+
+    fs::write(dir.path().join("README.md"), "# Test\nOne\nTwo").unwrap();
+    let src_dir = dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    let lines: Vec<String> = (1..=10).map(|i| format!("line {}", i)).collect();
+    fs::write(src_dir.join("lib.rs"), lines.join("\n")).unwrap();
+
+    let doks = "default_doc=README.md\n\n\
+        m1|README.md:2|src/lib.rs:1-3|h|h|\n\
+        m2|README.md:3|src/lib.rs:5-6|h|h|\n\
+        m3|README.md:3|README.md:1|h|h|\n";
+    fs::write(dir.path().join(".doks"), doks).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["coverage", "src/lib.rs"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Mappings: 2"))
+        .stdout(predicate::str::contains("Covered lines: 5/10 (50.0%)"))
+        .stdout(predicate::str::contains("- 4\n   - 7-10\n"));
+}
+
+#[test]
+fn test_test_command_reports_changed_line_offsets() {
+    let dir = tempdir().unwrap();
+    create_basic_doks_file(&dir);
+
+    fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+    let src_dir = dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    let lib_path = src_dir.join("lib.rs");
+    fs::write(
+        &lib_path,
+        "// header\nfn a() {}\nfn b() {}\nfn c() {}\nfn d() {}\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["add", "--doc", "README.md:2", "--code", "src/lib.rs:2-5"])
+        .assert()
+        .success();
+
+    // Line 4 of the file is the third line of the partition
+    fs::write(
+        &lib_path,
+        "// header\nfn a() {}\nfn b() {}\nfn renamed() {}\nfn d() {}\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("test")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "code content has changed (expected: ",
+        ))
+        .stdout(predicate::str::contains(
+            "changed line(s) within the partition: 3\n",
+        ));
+}
+
+#[test]
+fn test_test_command_fail_fast() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("README.md"), "# Test\nOne\nTwo").unwrap();
+
+    let stale = "0".repeat(64);
+    let doks = format!(
+        "default_doc=README.md\n\n\
+        first-failure|README.md:2|README.md:2|{0}|{0}|\n\
+        second-failure|README.md:3|README.md:3|{0}|{0}|\n",
+        stale
+    );
+    fs::write(dir.path().join(".doks"), doks).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["test", "--fail-fast"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("first-failure"))
+        .stdout(predicate::str::contains("second-failure").not())
+        .stdout(predicate::str::contains("1/2 mappings checked"))
+        .stdout(predicate::str::contains("Failed: 1/2"));
+
+    // Without the flag every mapping is still evaluated
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("test")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("second-failure"))
+        .stdout(predicate::str::contains("Failed: 2/2"));
+}
+
+#[test]
+fn test_test_command_parallel() {
+    let dir = tempdir().unwrap();
+    let lines: Vec<String> = (1..=12).map(|n| format!("Line {}", n)).collect();
+    fs::write(dir.path().join("README.md"), lines.join("\n")).unwrap();
+
+    // Every third mapping has a stale hash
+    let mut doks = "default_doc=README.md\n\n".to_string();
+    for (i, line) in lines.iter().enumerate() {
+        let hash = if i % 3 == 0 {
+            "0".repeat(64)
+        } else {
+            blake3::hash(line.as_bytes()).to_hex().to_string()
+        };
+        doks.push_str(&format!(
+            "mapping-{:02}|README.md:{1}|README.md:{1}|{2}|{2}|\n",
+            i + 1,
+            i + 1,
+            hash
+        ));
+    }
+    fs::write(dir.path().join(".doks"), doks).unwrap();
+
+    let run = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("doksnet").unwrap();
+        let output = cmd.current_dir(&dir).args(args).output().unwrap();
+        assert_eq!(output.status.code(), Some(1));
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+    };
+
+    let sequential = run(&["test", "--parallel", "1", "--format", "json"]);
+    let results = sequential["results"].as_array().unwrap();
+    assert_eq!(results.len(), 12);
+    for (i, result) in results.iter().enumerate() {
+        assert_eq!(result["id"], format!("mapping-{:02}", i + 1));
+        let expected = if i % 3 == 0 { "fail" } else { "pass" };
+        assert_eq!(result["status"], expected);
+    }
+    assert_eq!(sequential["summary"]["failed"], 4);
+
+    assert_eq!(run(&["test", "--format", "json"]), sequential);
+    assert_eq!(
+        run(&["test", "--parallel", "4", "--format", "json"]),
+        sequential
+    );
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["test", "--parallel", "0"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--parallel"));
+}
+
+#[test]
+fn test_test_command_stdin() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+    fs::write(dir.path().join("main.rs"), "fn main() {}").unwrap();
+    let doks = format!(
+        "default_doc=README.md\n\npiped-mapping|README.md:2|main.rs:1|{}|{}|\n",
+        blake3::hash(b"Content").to_hex(),
+        blake3::hash(b"fn main() {}").to_hex()
+    );
+
+    // No .doks on disk, and partitions resolve from --cwd
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.arg("--cwd")
+        .arg(dir.path())
+        .args(["test", "--stdin"])
+        .write_stdin(doks.clone())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Passed: 1/1"));
+    assert!(!dir.path().join(".doks").exists());
+
+    fs::write(dir.path().join("main.rs"), "fn main() { changed() }").unwrap();
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["test", "--stdin", "--format", "github"])
+        .write_stdin(doks)
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("::error file=main.rs,line=1::"));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["test", "--stdin"])
+        .write_stdin("broken-line\n")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid .doks on stdin"));
+}
+
+#[test]
+fn test_test_command_tag() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("README.md"), "# Test\nLogin\nInvoices").unwrap();
+    create_basic_doks_file(&dir);
+
+    for (partition, tag) in [
+        ("README.md:1", "general"),
+        ("README.md:2", "auth"),
+        ("README.md:3", "billing,api"),
+    ] {
+        let mut cmd = Command::cargo_bin("doksnet").unwrap();
+        cmd.current_dir(&dir)
+            .args(["add", "--doc", partition, "--code", partition, "--tag", tag])
+            .assert()
+            .success();
+    }
+    let doks = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    assert!(doks.contains("|README.md:3|README.md:3|"));
+    assert!(doks.contains("|tags=billing,api|"));
+
+    // Break the general and auth mappings; a billing-only run still passes
+    fs::write(dir.path().join("README.md"), "# Changed\nChanged\nInvoices").unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["test", "--tag", "billing"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Testing 1 documentation-code mappings",
+        ))
+        .stdout(predicate::str::contains("Passed: 1/1"));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["test", "--tag", "auth", "--tag", "api"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("Failed: 1/2"));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["test", "--tag", "shipping"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No mapping is tagged 'shipping'"));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Tags"))
+        .stdout(predicate::str::contains("billing,api"));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("stats")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Tags:"))
+        .stdout(predicate::str::contains("1  billing"));
+}
+
+#[test]
+fn test_test_command_only() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("README.md"), "# Test\nOne\nTwo").unwrap();
+
+    let stale = "0".repeat(64);
+    let doks = format!(
+        "default_doc=README.md\n\n\
+        auth-login|README.md:1|README.md:1|{0}|{0}|\n\
+        auth-logout|README.md:2|README.md:2|{0}|{0}|\n\
+        billing-invoice|README.md:3|README.md:3|{0}|{0}|\n",
+        stale
+    );
+    fs::write(dir.path().join(".doks"), doks).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["test", "--only", "billing"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains(
+            "Testing 1 documentation-code mappings",
+        ))
+        .stdout(predicate::str::contains("billing-invoice"))
+        .stdout(predicate::str::contains("auth-").not())
+        .stdout(predicate::str::contains("Failed: 1/1"))
+        .stdout(predicate::str::contains("Skipped").not());
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["test", "--only", "auth-login", "--only", "billing"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("auth-login"))
+        .stdout(predicate::str::contains("billing-invoice"))
+        .stdout(predicate::str::contains("auth-logout").not())
+        .stdout(predicate::str::contains("Failed: 2/2"));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["test", "--only", "auth", "--only", "shipping"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Testing").not())
+        .stderr(predicate::str::contains(
+            "No mapping found with ID starting with 'shipping'",
+        ));
+}
+
+#[test]
+fn test_extract_command() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("file.txt"), "one\ntwo\nthree\nfour\n").unwrap();
+
+    // No .doks is needed
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["extract", "file.txt:2-3"])
+        .assert()
+        .success()
+        .stdout("two\nthree\n");
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["extract", "file.txt:7-9"])
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr(predicate::str::contains("Cannot extract 'file.txt:7-9'"));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["extract", "file.txt:x"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid partition 'file.txt:x'"));
+}
+
+#[test]
+fn test_max_file_size() {
+    let dir = tempdir().unwrap();
+    let content = "# Title\nShort intro\n".to_string() + &"filler line\n".repeat(100);
+    fs::write(dir.path().join("big.md"), &content).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .env("DOKSNET_MAX_FILE_SIZE", "64")
+        .args(["extract", "big.md"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(format!(
+            "'big.md' is {} bytes, more than the 64 bytes read whole",
+            content.len()
+        )));
+
+    // Line ranges are read up to their last line, so the limit doesn't apply
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .env("DOKSNET_MAX_FILE_SIZE", "64")
+        .args(["extract", "big.md:2"])
+        .assert()
+        .success()
+        .stdout("Short intro\n");
+
+    let doks = format!(
+        "default_doc=big.md\n\nintro|big.md:2|big.md:1|{}|{}|\n",
+        blake3::hash(b"Short intro").to_hex(),
+        blake3::hash(b"# Title").to_hex()
+    );
+    fs::write(dir.path().join(".doks"), doks).unwrap();
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .env("DOKSNET_MAX_FILE_SIZE", "64")
+        .arg("test")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Passed: 1/1"));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .env("DOKSNET_MAX_FILE_SIZE", "lots")
+        .args(["extract", "big.md"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "DOKSNET_MAX_FILE_SIZE must be a number of bytes, not 'lots'",
+        ));
+}
+
+#[test]
+fn test_test_command_clamp() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("main.rs"), "fn main() {}").unwrap();
+
+    // The mapping covered "Content" and two trailing blank lines, which
+    // whitespace normalization ignores; the file has since been trimmed
+    fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+    let doc_hash = blake3::hash(b"Content").to_hex().to_string();
+    let code_hash = blake3::hash(b"fn main() {}").to_hex().to_string();
+    let doks = format!(
+        "default_doc=README.md\n\n\
+        tail-mapping|README.md:2-4|main.rs:1|{}|{}||normalize_whitespace=true\n",
+        doc_hash, code_hash
+    );
+    fs::write(dir.path().join(".doks"), doks).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("test")
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains(
+            "Line 4 exceeds file length (file has 2 lines)",
+        ))
+        .stdout(predicate::str::contains("clamped").not());
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["test", "--clamp"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Passed: 1/1"))
+        .stdout(predicate::str::contains(
+            "Documentation: line range 2-4 clamped to 2-2 (file has 2 lines)",
+        ));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["test", "--clamp", "--format", "json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"warnings\": ["));
+}
+
+#[test]
+fn test_test_command_exclude() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("README.md"), "# Test\nOne\nTwo").unwrap();
+
+    let stale = "0".repeat(64);
+    let doks = format!(
+        "default_doc=README.md\n\n\
+        generated-api|README.md:2|README.md:2|{0}|{0}|\n\
+        handwritten-guide|README.md:3|README.md:3|{0}|{0}|\n",
+        stale
+    );
+    fs::write(dir.path().join(".doks"), doks).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["test", "--exclude", "generated"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("handwritten-guide"))
+        .stdout(predicate::str::contains("generated-api").not())
+        .stdout(predicate::str::contains("Failed: 1/1"))
+        .stdout(predicate::str::contains("Skipped: 1 (excluded)"));
+
+    // Excluded failures don't affect the exit code
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["test", "--exclude", "generated", "--exclude", "hand"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("All 2 mappings were excluded"));
+}
+
+#[test]
+fn test_test_command_skips_disabled_mappings() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("README.md"), "# Test\nOne\nTwo").unwrap();
+
+    let stale = "0".repeat(64);
+    let doks = format!(
+        "default_doc=README.md\n\n\
+        flaky-mapping|README.md:2|README.md:2|{0}|{0}|\n\
+        other-mapping|README.md:3|README.md:3|{0}|{0}|\n",
+        stale
+    );
+    fs::write(dir.path().join(".doks"), doks).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["edit", "flaky", "--disabled"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Status: disabled"));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("test")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("other-mapping"))
+        .stdout(predicate::str::contains("flaky-mapping").not())
+        .stdout(predicate::str::contains("Failed: 1/1"))
+        .stdout(predicate::str::contains("Skipped: 1 (disabled)"));
+
+    // The mapping and its hashes are still in the file
+    let doks = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    assert!(doks.contains(&format!(
+        "flaky-mapping|README.md:2|README.md:2|{0}|{0}|",
+        stale
+    )));
+    assert!(doks.contains("enabled=false"));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["edit", "flaky", "--enabled"])
+        .assert()
+        .success();
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("test")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Failed: 2/2"));
+}
+
+#[test]
+fn test_disabled_mappings_are_skipped_by_test_interactive_and_doctor() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("README.md"), "# Test\nOne\nTwo").unwrap();
+
+    let stale = "0".repeat(64);
+    let doks = format!(
+        "default_doc=README.md\n\n\
+        flaky-mapping|README.md:2|README.md:2|{0}|{0}||enabled=false\n",
+        stale
+    );
+    fs::write(dir.path().join(".doks"), &doks).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("test-interactive")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("All 1 mappings are disabled"))
+        .stdout(predicate::str::contains("FAIL").not());
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("doctor")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No problems found"));
+
+    // Next to a drifted enabled mapping, only that one is offered for fixing
+    fs::write(
+        dir.path().join(".doks"),
+        format!(
+            "{}other-mapping|README.md:3|README.md:3|{1}|{1}|\n",
+            doks, stale
+        ),
+    )
+    .unwrap();
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("test-interactive")
+        .assert()
+        .stdout(predicate::str::contains("Testing mapping 1/1: other-ma"))
+        .stdout(predicate::str::contains("flaky-ma").not())
+        .stdout(predicate::str::contains("Failed: 1/1"))
+        .stdout(predicate::str::contains("Skipped: 1 (disabled)"));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("doctor")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("other-mapping (doc)"))
+        .stdout(predicate::str::contains("flaky-mapping").not());
+}
+
+#[test]
+fn test_test_command_allow_missing() {
+    let dir = tempdir().unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["test", "--allow-missing"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "No .doks file found, nothing to verify",
+        ));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["test", "--allow-missing", "--status-only"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+
+    // Without the flag a missing .doks is still an error
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("test")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No .doks file found"));
+}
+
+#[test]
+fn test_test_command_code_base() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("README.md"), "# Test\nCall generated()").unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/gen.rs"), "pub fn generated() {}").unwrap();
+    create_doks_with_mapping(&dir, "README.md:2", "src/gen.rs:1");
+
+    // The generated file only exists in a separate build output directory
+    fs::remove_file(dir.path().join("src/gen.rs")).unwrap();
+    let build = tempdir().unwrap();
+    fs::create_dir(build.path().join("src")).unwrap();
+    fs::write(build.path().join("src/gen.rs"), "pub fn generated() {}").unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("test")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("File not found: src/gen.rs"));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["test", "--code-base"])
+        .arg(build.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Passed: 1/1"));
+
+    // Documentation still resolves from the .doks directory
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["test", "--doc-base"])
+        .arg(build.path())
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("File not found: README.md"));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["test", "--code-base", "no-such-dir"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "--code-base no-such-dir is not a directory",
+        ));
+}
+
+#[test]
+fn test_test_command_warn_identical() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("README.md"),
+        "# Usage\n    let x = run();\n",
+    )
+    .unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/main.rs"), "let x = run();\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["new", "--doc", "README.md"])
+        .assert()
+        .success();
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["add", "--doc", "README.md:2", "--code", "src/main.rs:1"])
+        .arg("--normalize-whitespace")
+        .assert()
+        .success();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("test")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("identical").not());
+
+    // Only the indentation differs, which the mapping ignores
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["test", "--warn-identical"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Documentation and code content are identical:",
+        ))
+        .stdout(predicate::str::contains("(README.md:2 = src/main.rs:1)"));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["test", "--warn-identical", "--deny-identical"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Failed: 1/1"));
+}
+
+#[test]
+fn test_final_newline_is_ignored_between_add_and_test() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+    fs::write(dir.path().join("VERSION"), "1.2.3").unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["new", "--doc", "README.md"])
+        .assert()
+        .success();
+    for code in ["VERSION", "VERSION:1"] {
+        let mut cmd = Command::cargo_bin("doksnet").unwrap();
+        cmd.current_dir(&dir)
+            .args(["add", "--doc", "README.md", "--code", code])
+            .assert()
+            .success();
+    }
+
+    // An editor adds a final newline to both files, then removes it again
+    for (readme, version) in [
+        ("# Test\nContent\n", "1.2.3\n"),
+        ("# Test\nContent", "1.2.3"),
+    ] {
+        fs::write(dir.path().join("README.md"), readme).unwrap();
+        fs::write(dir.path().join("VERSION"), version).unwrap();
+
+        let mut cmd = Command::cargo_bin("doksnet").unwrap();
+        cmd.current_dir(&dir)
+            .arg("test")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Passed: 2/2"));
+    }
+}
+
+#[test]
+fn test_check_command_compact_output() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/main.rs"), "// main\nfn main() {}").unwrap();
+    create_doks_with_mapping(&dir, "README.md:2", "src/main.rs:2");
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("check")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+
+    fs::write(
+        dir.path().join("src/main.rs"),
+        "// main\nfn main() { run(); }",
+    )
+    .unwrap();
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("check")
+        .assert()
+        .code(1)
+        .stdout(predicate::str::diff(
+            "src/main.rs:2: code content has changed (mapping test-mapping-123)\n",
+        ));
+}
+
+#[test]
+fn test_test_command_status_only_exit_codes() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/main.rs"), "fn main() {}").unwrap();
+    create_doks_with_mapping(&dir, "README.md:2", "src/main.rs:1");
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["test", "--status-only"])
+        .assert()
+        .code(0)
+        .stdout(predicate::str::is_empty());
+
+    // Content drift
+    fs::write(dir.path().join("src/main.rs"), "fn main() { run(); }").unwrap();
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["test", "--status-only"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::is_empty());
+
+    // A missing file can't be checked at all
+    fs::remove_file(dir.path().join("src/main.rs")).unwrap();
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["test", "--status-only"])
+        .assert()
+        .code(2)
+        .stdout(predicate::str::is_empty());
+
+    // A malformed partition is a configuration error, not drift
+    let stale = "0".repeat(64);
+    let doks = format!(
+        "default_doc=README.md\n\nbroken|README.md:abc|src/main.rs:1|{0}|{0}|\n",
+        stale
+    );
+    fs::write(dir.path().join(".doks"), doks).unwrap();
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["test", "--status-only"])
+        .assert()
+        .code(2)
+        .stdout(predicate::str::is_empty());
+}
+
+#[test]
+fn test_test_command_status_only_without_doks_file() {
+    let dir = tempdir().unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["test", "--status-only"])
+        .assert()
+        .code(2)
+        .stdout(predicate::str::is_empty());
+}
+
+#[test]
+fn test_test_command_reports_many_mappings_in_order() {
+    let dir = tempdir().unwrap();
+
+    let lines: Vec<String> = (1..=200).map(|i| format!("line {}", i)).collect();
+    fs::write(dir.path().join("README.md"), lines.join("\n")).unwrap();
+
+    let mut doks = String::from("default_doc=README.md\n\n");
+    let stale = "0".repeat(64);
+    for (i, line) in lines.iter().enumerate().map(|(i, line)| (i + 1, line)) {
+        let hash = blake3::hash(line.as_bytes()).to_hex().to_string();
+        // Every third mapping has a stale code hash
+        let code_hash = if i % 3 == 0 { &stale } else { &hash };
+        doks.push_str(&format!(
+            "mapping-{:03}|README.md:{}|README.md:{}|{}|{}|\n",
+            i, i, i, hash, code_hash
+        ));
+    }
+    fs::write(dir.path().join(".doks"), doks).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    let output = cmd
+        .current_dir(&dir)
+        .arg("test")
+        .arg("--format")
+        .arg("json")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let results = report["results"].as_array().unwrap();
+    assert_eq!(results.len(), 200);
+    for (index, result) in results.iter().enumerate() {
+        let i = index + 1;
+        assert_eq!(result["id"], format!("mapping-{:03}", i));
+        let expected = if i % 3 == 0 { "fail" } else { "pass" };
+        assert_eq!(result["status"], expected);
+    }
+    assert_eq!(report["summary"]["failed"], 66);
+}
+
+#[test]
+fn test_validate_command_with_valid_mappings() {
+    let dir = tempdir().unwrap();
+
+    fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+    let src_dir = dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    fs::write(src_dir.join("main.rs"), "fn main() {}").unwrap();
+
+    create_doks_with_mapping(&dir, "README.md:2", "src/main.rs:1");
+    // Content changes are not validate's concern
+    fs::write(src_dir.join("main.rs"), "fn changed() {}").unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("validate")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("All partitions are valid"));
+}
+
+#[test]
+fn test_validate_command_with_malformed_partition() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("README.md"), "# Test").unwrap();
+
+    let doks = "default_doc=README.md\n\nbad-mapping|README.md:abc|README.md:1|h|h|\n";
+    fs::write(dir.path().join(".doks"), doks).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("validate")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("bad-mapping"))
+        .stdout(predicate::str::contains(
+            "Documentation: Malformed partition 'README.md:abc'",
+        ));
+}
+
+#[test]
+fn test_validate_command_with_missing_file() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("README.md"), "# Test").unwrap();
+
+    let doks = "default_doc=README.md\n\nmissing-file|README.md:1|src/gone.rs:1|h|h|\n";
+    fs::write(dir.path().join(".doks"), doks).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("validate")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("missing-file"))
+        .stdout(predicate::str::contains(
+            "Code: File not found: src/gone.rs",
+        ));
+}
+
+#[test]
+fn test_validate_command_with_glob_partition() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("README.md"), "# Test").unwrap();
+    fs::create_dir(dir.path().join("target")).unwrap();
+    fs::write(dir.path().join("target/doc-1.2.md"), "Generated").unwrap();
+
+    let doks = "default_doc=README.md\n\nglob|README.md:1|target/doc-*.md:1|h|h|\n";
+    fs::write(dir.path().join(".doks"), doks).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("validate")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("All partitions are valid"));
+
+    fs::write(dir.path().join("target/doc-1.3.md"), "Generated").unwrap();
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("validate")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("glob"))
+        .stdout(predicate::str::contains("target/doc-1.2.md"))
+        .stdout(predicate::str::contains("target/doc-1.3.md"));
+}
+
+#[test]
+fn test_doctor_command_reports_missing_file() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+
+    let doc_hash = blake3::hash("Content".as_bytes()).to_hex().to_string();
+    let doks = format!(
+        "default_doc=README.md\n\nmissing-file|README.md:2|src/gone.rs:1|{}|h|\n",
+        doc_hash
+    );
+    fs::write(dir.path().join(".doks"), doks).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("doctor")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Missing files: 1"))
+        .stdout(predicate::str::contains("missing-file (code): src/gone.rs"))
+        .stdout(predicate::str::contains("Duplicate IDs: none"))
+        .stdout(predicate::str::contains("Hash mismatches: none"));
+}
+
+#[test]
+fn test_doctor_command_reports_duplicate_ids_and_other_problems() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+
+    // test refuses to load this file at all; doctor reports everything
+    let doks = "default_doc=README.md\n\n\
+        dup-id|README.md:2|README.md:1|h|h|\n\
+        dup-id|README.md:2|README.md:5-9|h|h|\n\
+        other-id|README.md:x|README.md:1|h|h|\n";
+    fs::write(dir.path().join(".doks"), doks).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("doctor")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Duplicate IDs: 1\n   - dup-id\n"))
+        .stdout(predicate::str::contains("Unparseable partitions: 1"))
+        .stdout(predicate::str::contains("other-id (doc): 'README.md:x'"))
+        .stdout(predicate::str::contains("Ranges beyond end of file: 1"))
+        .stdout(predicate::str::contains("dup-id (code): README.md:5-9"))
+        .stdout(predicate::str::contains("Malformed stored hashes: 4"))
+        .stdout(predicate::str::contains("dup-id (doc): 'h'"))
+        .stdout(predicate::str::contains("Hash mismatches: none"));
+}
+
+#[test]
+fn test_doctor_command_healthy() {
+    let dir = tempdir().unwrap();
+
+    fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+    let src_dir = dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    fs::write(src_dir.join("main.rs"), "fn main() {}").unwrap();
+
+    create_doks_with_mapping(&dir, "README.md:2", "src/main.rs:1");
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("doctor")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No problems found"));
+}
+
+#[test]
+fn test_export_command_json() {
+    let dir = tempdir().unwrap();
+
+    let doks = "default_doc=README.md\n\n\
+        first|README.md:1|src/main.rs:1-3|aaa|bbb|Main entry\n\
+        second|README.md:2|src/lib.rs|ccc|ddd|\n";
+    fs::write(dir.path().join(".doks"), doks).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    let output = cmd
+        .current_dir(&dir)
+        .args(["export", "--format", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let exported: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let mappings = exported.as_array().unwrap();
+    assert_eq!(mappings.len(), 2);
+    assert_eq!(mappings[0]["id"], "first");
+    assert_eq!(mappings[0]["doc_partition"], "README.md:1");
+    assert_eq!(mappings[0]["code_partition"], "src/main.rs:1-3");
+    assert_eq!(mappings[0]["doc_hash"], "aaa");
+    assert_eq!(mappings[0]["code_hash"], "bbb");
+    assert_eq!(mappings[0]["description"], "Main entry");
+    assert!(mappings[1]["description"].is_null());
+}
+
+#[test]
+fn test_export_command_csv_quotes_descriptions() {
+    let dir = tempdir().unwrap();
+
+    let doks = "default_doc=README.md\n\n\
+        first|README.md:1|src/main.rs:1|aaa|bbb|Parses args, then runs\n\
+        second|README.md:2|src/lib.rs:1|ccc|ddd|The \"core\" module\n";
+    fs::write(dir.path().join(".doks"), doks).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["export", "--format", "csv"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "id,doc_partition,code_partition,doc_hash,code_hash,description\n",
+        ))
+        .stdout(predicate::str::contains(
+            "first,README.md:1,src/main.rs:1,aaa,bbb,\"Parses args, then runs\"\n",
+        ))
+        .stdout(predicate::str::contains(
+            "second,README.md:2,src/lib.rs:1,ccc,ddd,\"The \"\"core\"\" module\"\n",
+        ));
+}
+
+#[test]
+fn test_import_command_merges_mappings() {
+    let dir = tempdir().unwrap();
+
+    fs::write(dir.path().join("README.md"), "# Test\nContent\nMore").unwrap();
+    let src_dir = dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    fs::write(src_dir.join("main.rs"), "fn main() {}").unwrap();
+
+    create_doks_with_mapping(&dir, "README.md:2", "src/main.rs:1");
+
+    let stale = "0".repeat(64);
+    let import = format!(
+        r#"[
+        {{"id": "imported-one", "doc_partition": "README.md:3", "code_partition": "src/main.rs:1",
+         "doc_hash": "{0}", "code_hash": "{0}", "description": "From JSON"}},
+        {{"doc_partition": "README.md:1", "code_partition": "src/main.rs:1"}},
+        {{"id": "test-mapping-123", "doc_partition": "README.md:1", "code_partition": "src/main.rs:1"}}
+    ]"#,
+        stale
+    );
+    fs::write(dir.path().join("mappings.json"), import).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["import", "mappings.json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Imported 2 mapping(s)"))
+        .stdout(predicate::str::contains("Skipped (ID already exists): 1"))
+        .stdout(predicate::str::contains("Total mappings: 3"));
+
+    let doks = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    assert!(doks.contains(&format!(
+        "imported-one|README.md:3|src/main.rs:1|{0}|{0}|From JSON",
+        stale
+    )));
+    // The existing mapping was left alone
+    assert!(doks.contains("test-mapping-123|README.md:2|"));
+
+    // The mapping without hashes was hashed from current content
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("test")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Passed: 2/3"));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["import", "mappings.json", "--overwrite"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Overwritten: 2"));
+
+    let doks = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    assert!(doks.contains("test-mapping-123|README.md:1|"));
+}
+
+#[test]
+fn test_merge_command_prefixes_paths() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/main.rs"), "fn main() {}").unwrap();
+    create_doks_with_mapping(&dir, "README.md:2", "src/main.rs:1");
+
+    // The other repository, moved into services/api with its own .doks
+    let api = dir.path().join("services/api");
+    fs::create_dir_all(api.join("src")).unwrap();
+    fs::write(api.join("README.md"), "# API\nCall serve()").unwrap();
+    fs::write(api.join("src/lib.rs"), "pub fn serve() {}").unwrap();
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&api)
+        .args(["new", "--doc", "README.md"])
+        .assert()
+        .success();
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&api)
+        .args(["add", "--doc", "README.md:2", "--code", "src/lib.rs:1"])
+        .assert()
+        .success();
+    let mut other = fs::read_to_string(api.join(".doks")).unwrap();
+    other.push_str(&format!(
+        "test-mapping-123|README.md:1|src/lib.rs:1|{0}|{0}|\n",
+        "0".repeat(64)
+    ));
+    fs::write(api.join(".doks"), other).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["merge", "services/api/.doks", "--prefix", "services/api"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Merged 1 mapping(s)"))
+        .stdout(predicate::str::contains("Skipped (ID already exists): 1"))
+        .stdout(predicate::str::contains("Total mappings: 2"));
+
+    let doks = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    assert!(doks.contains("|services/api/README.md:2|services/api/src/lib.rs:1|"));
+    // The colliding mapping was left out, and ours kept its partitions
+    assert!(doks.contains("test-mapping-123|README.md:2|src/main.rs:1|"));
+    assert!(!doks.contains("services/api/README.md:1"));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("test")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Passed: 2/2"));
+}
+
+#[test]
+fn test_export_import_round_trip_keeps_hashing_flags() {
+    let dir = tempdir().unwrap();
+    create_basic_doks_file(&dir);
+
+    fs::write(
+        dir.path().join("README.md"),
+        "```rust\nfn main() {\n    run();\n}\n```",
+    )
+    .unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/main.rs"), "fn main() {} // entry").unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["add", "--doc", "README.md:2-4", "--code", "src/main.rs:1"])
+        .args([
+            "--normalize-whitespace",
+            "--strip-comments",
+            "--tag",
+            "cli,setup",
+        ])
+        .assert()
+        .success();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    let output = cmd.current_dir(&dir).arg("export").output().unwrap();
+    assert!(output.status.success());
+    fs::write(dir.path().join("mappings.json"), &output.stdout).unwrap();
+
+    fs::write(dir.path().join(".doks"), "default_doc=README.md\n").unwrap();
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["import", "mappings.json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Imported 1 mapping(s)"));
+
+    let content = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    assert!(content.contains("|normalize_whitespace=true"));
+    assert!(content.contains("|strip_comments=true"));
+    assert!(content.contains("|tags=cli,setup"));
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("test")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Passed: 1/1"));
+}
+
+#[test]
+fn test_import_command_rejects_invalid_partition() {
+    let dir = tempdir().unwrap();
+    create_basic_doks_file(&dir);
+    let before = fs::read_to_string(dir.path().join(".doks")).unwrap();
+
+    let import = r#"[{"doc_partition": "README.md:x", "code_partition": "src/main.rs:1",
+        "doc_hash": "a", "code_hash": "b"}]"#;
+    fs::write(dir.path().join("mappings.json"), import).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .args(["import", "mappings.json"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Mapping #1"))
+        .stderr(predicate::str::contains("Invalid partition 'README.md:x'"));
+
+    let after = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    assert_eq!(before, after);
+}
+
+#[test]
+fn test_sync_check_reports_drift_without_writing() {
+    let dir = tempdir().unwrap();
+
+    fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+    let src_dir = dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    let main_path = src_dir.join("main.rs");
+    fs::write(&main_path, "fn main() {}").unwrap();
+
+    create_doks_with_mapping(&dir, "README.md:2", "src/main.rs:1");
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("sync")
+        .arg("--check")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Hashes changed: 0"));
+
+    fs::write(&main_path, "fn main() { run(); }").unwrap();
+    let original = fs::read_to_string(dir.path().join(".doks")).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("sync")
+        .arg("--check")
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("Would update: test-mapping-123"));
+
+    let after = fs::read_to_string(dir.path().join(".doks")).unwrap();
+    assert_eq!(original, after);
+}
+
+#[test]
+fn test_test_command_from_nested_subdirectory() {
+    let dir = tempdir().unwrap();
+
+    fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+    let src_dir = dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    fs::write(src_dir.join("main.rs"), "fn main() {}").unwrap();
+
+    create_doks_with_mapping(&dir, "README.md:2", "src/main.rs:1");
+
+    let nested = dir.path().join("src").join("nested").join("deeper");
+    fs::create_dir_all(&nested).unwrap();
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&nested)
+        .arg("test")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Passed: 1/1"));
+
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&nested).arg("validate").assert().success();
+}
+
+#[test]
+fn test_test_interactive_shows_numbered_changes() {
+    let dir = tempdir().unwrap();
+
+    let readme_path = dir.path().join("README.md");
+    fs::write(&readme_path, "# Test\nLine 2\nLine 3\nLine 4").unwrap();
+    let src_dir = dir.path().join("src");
+    fs::create_dir(&src_dir).unwrap();
+    fs::write(src_dir.join("main.rs"), "fn main() {}").unwrap();
+
+    create_doks_with_mapping(&dir, "README.md:3-4", "src/main.rs:1");
+    fs::write(&readme_path, "# Test\nLine 2\nLine three\nLine 4").unwrap();
+
+    // The action prompt needs a terminal, but the change report is printed first
+    let mut cmd = Command::cargo_bin("doksnet").unwrap();
+    cmd.current_dir(&dir)
+        .arg("test-interactive")
+        .assert()
+        .stdout(predicate::str::contains(
+            "Documentation content has changed",
+        ))
+        .stdout(predicate::str::contains("3 | Line three\n4 | Line 4"));
+}
+
 // Helper functions
 
 fn create_basic_doks_file(dir: &tempfile::TempDir) {
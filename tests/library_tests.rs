@@ -0,0 +1,87 @@
+use doksnet::hash::{hash_content, HashOptions};
+use doksnet::{verify_all, DoksConfig, Mapping, Partition};
+use std::fs;
+use tempfile::tempdir;
+
+fn mapping(id: &str, doc_partition: &str, code_partition: &str, dir: &std::path::Path) -> Mapping {
+    let hash = |partition: &str| {
+        let content = Partition::parse(partition)
+            .unwrap()
+            .extract_content_in(dir)
+            .unwrap();
+        hash_content(&content, HashOptions::default())
+    };
+
+    Mapping {
+        id: id.to_string(),
+        doc_partition: doc_partition.to_string(),
+        code_partition: code_partition.to_string(),
+        doc_hash: hash(doc_partition),
+        code_hash: hash(code_partition),
+        description: None,
+        normalize_whitespace: false,
+        strip_comments: false,
+        tags: Vec::new(),
+        doc_snippet: None,
+        code_snippet: None,
+        doc_lines: Vec::new(),
+        code_lines: Vec::new(),
+        comments: Vec::new(),
+        created_at: None,
+        updated_at: None,
+        enabled: true,
+    }
+}
+
+#[test]
+fn test_verify_all_through_library_api() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("README.md"), "# Test\nRun it\nStop it").unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/main.rs"), "fn run() {}\nfn stop() {}").unwrap();
+
+    let mut config = DoksConfig::new("README.md".to_string());
+    config.add_mapping(mapping("run", "README.md:2", "src/main.rs:1", dir.path()));
+    config.add_mapping(mapping("stop", "README.md:3", "src/main.rs:2", dir.path()));
+
+    let results = verify_all(&config, dir.path());
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|r| r.passed && r.errors.is_empty()));
+
+    fs::write(
+        dir.path().join("src/main.rs"),
+        "fn run() {}\nfn stop(now: bool) {}",
+    )
+    .unwrap();
+
+    let results = verify_all(&config, dir.path());
+    assert_eq!(results[0].id, "run");
+    assert!(results[0].passed);
+    assert_eq!(results[1].id, "stop");
+    assert!(!results[1].passed);
+    assert!(!results[1].broken);
+    assert_eq!(results[1].errors.len(), 1);
+    assert!(results[1].errors[0].starts_with("Code: code content has changed"));
+
+    fs::remove_file(dir.path().join("src/main.rs")).unwrap();
+    let results = verify_all(&config, dir.path());
+    assert!(!results[0].passed);
+    assert!(results[0].broken);
+}
+
+#[test]
+fn test_config_round_trip_through_library_api() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("README.md"), "# Test\nContent").unwrap();
+
+    let mut config = DoksConfig::new("README.md".to_string());
+    config.add_mapping(mapping("readme", "README.md:2", "README.md:1", dir.path()));
+    config.to_file(dir.path().join(".doks")).unwrap();
+
+    let loaded = DoksConfig::from_file(dir.path().join(".doks")).unwrap();
+    assert_eq!(loaded.root, dir.path());
+    assert_eq!(loaded.mappings.len(), 1);
+    assert_eq!(loaded.mappings[0].id, "readme");
+    assert_eq!(loaded.mappings[0].doc_hash, config.mappings[0].doc_hash);
+    assert!(verify_all(&loaded, &loaded.root)[0].passed);
+}